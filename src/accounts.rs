@@ -0,0 +1,413 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use solana_sdk::nonce::state::{State as NonceState, Versions as NonceVersions};
+use solana_sdk_ids::system_program;
+use solana_stake_interface::{program::ID as STAKE_PROGRAM_ID, state::StakeStateV2};
+use spl_token::solana_program::{program_option::COption, program_pack::Pack};
+use spl_token_2022::{
+    extension::{BaseState, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccountState, Mint},
+};
+
+use crate::rpc::commitment_config;
+use crate::state::AppState;
+use crate::types::{
+    AccountBatchEntry, AccountBatchRequest, AccountDecodeHint, AccountInfoData, AccountInfoQuery,
+    AccountsMultipleRequest, BalanceData, BalanceQuery, DataEncoding, ProgramAccountsData, ProgramAccountsRequest,
+};
+use crate::utils::{parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const MAX_BATCH_ACCOUNTS: usize = 100;
+
+/// RPC nodes reject `getProgramAccounts` calls with more filters than this.
+const MAX_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+/// Longest memcmp pattern RPC nodes accept, in bytes.
+const MAX_MEMCMP_BYTES: usize = 128;
+const DEFAULT_PROGRAM_ACCOUNTS_PAGE_SIZE: usize = 100;
+const MAX_PROGRAM_ACCOUNTS_PAGE_SIZE: usize = 1000;
+/// Queries matching more accounts than this must be narrowed instead of paged.
+const MAX_PROGRAM_ACCOUNT_MATCHES: usize = 10_000;
+
+/// Accounts requested per `getMultipleAccounts` call. Kept below the RPC
+/// limit so large token accounts don't push a single response past provider caps.
+pub const MULTIPLE_ACCOUNTS_CHUNK: usize = 25;
+
+fn coption_to_string(value: &COption<Pubkey>) -> Option<String> {
+    match value {
+        COption::Some(key) => Some(key.to_string()),
+        COption::None => None,
+    }
+}
+
+/// Names of the Token-2022 extensions present on a mint or token account.
+pub fn extension_names<S: BaseState + Pack>(state: &StateWithExtensions<S>) -> Vec<String> {
+    state
+        .get_extension_types()
+        .unwrap_or_default()
+        .iter()
+        .map(|extension| format!("{:?}", extension))
+        .collect()
+}
+
+fn decode_token_account_data(data: &[u8]) -> Option<Value> {
+    let state = StateWithExtensions::<TokenAccountState>::unpack(data).ok()?;
+    let account = state.base;
+    Some(json!({
+        "type": "account",
+        "info": {
+            "mint": account.mint.to_string(),
+            "owner": account.owner.to_string(),
+            "amount": account.amount,
+            "delegate": coption_to_string(&account.delegate),
+            "delegated_amount": account.delegated_amount,
+            "state": format!("{:?}", account.state).to_lowercase(),
+            "is_native": account.is_native.is_some(),
+            "close_authority": coption_to_string(&account.close_authority),
+            "extensions": extension_names(&state),
+        }
+    }))
+}
+
+fn decode_mint_data(data: &[u8]) -> Option<Value> {
+    let state = StateWithExtensions::<Mint>::unpack(data).ok()?;
+    let mint = state.base;
+    Some(json!({
+        "type": "mint",
+        "info": {
+            "mint_authority": coption_to_string(&mint.mint_authority),
+            "supply": mint.supply,
+            "decimals": mint.decimals,
+            "is_initialized": mint.is_initialized,
+            "freeze_authority": coption_to_string(&mint.freeze_authority),
+            "extensions": extension_names(&state),
+        }
+    }))
+}
+
+fn decode_token_data(data: &[u8]) -> Option<Value> {
+    // Mints are shorter than token accounts, so the token account layout is tried first.
+    decode_token_account_data(data).or_else(|| decode_mint_data(data))
+}
+
+fn decode_nonce_data(data: &[u8]) -> Option<Value> {
+    let versions = bincode::deserialize::<NonceVersions>(data).ok()?;
+    match versions.state() {
+        NonceState::Initialized(nonce) => Some(json!({
+            "type": "nonce",
+            "info": {
+                "authority": nonce.authority.to_string(),
+                "nonce": nonce.blockhash().to_string(),
+                "lamports_per_signature": nonce.fee_calculator.lamports_per_signature,
+            }
+        })),
+        NonceState::Uninitialized => None,
+    }
+}
+
+fn decode_stake_data(data: &[u8]) -> Option<Value> {
+    let (kind, meta, stake) = match bincode::deserialize::<StakeStateV2>(data).ok()? {
+        StakeStateV2::Initialized(meta) => ("initialized", meta, None),
+        StakeStateV2::Stake(meta, stake, _) => ("delegated", meta, Some(stake)),
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => return None,
+    };
+    Some(json!({
+        "type": "stake",
+        "info": {
+            "state": kind,
+            "staker": meta.authorized.staker.to_string(),
+            "withdrawer": meta.authorized.withdrawer.to_string(),
+            "rent_exempt_reserve": meta.rent_exempt_reserve,
+            "lockup": {
+                "unix_timestamp": meta.lockup.unix_timestamp,
+                "epoch": meta.lockup.epoch,
+                "custodian": meta.lockup.custodian.to_string(),
+            },
+            "delegation": stake.map(|stake| json!({
+                "voter": stake.delegation.voter_pubkey.to_string(),
+                "stake": stake.delegation.stake,
+                "activation_epoch": stake.delegation.activation_epoch,
+                "deactivation_epoch": stake.delegation.deactivation_epoch,
+            })),
+        }
+    }))
+}
+
+/// Decodes account data for the layouts this service knows about: mints and
+/// token accounts of either token program, durable nonces and stake accounts.
+pub fn decode_account_data(owner: &Pubkey, data: &[u8]) -> Option<Value> {
+    if *owner == spl_token::ID || *owner == spl_token_2022::ID {
+        decode_token_data(data)
+    } else if *owner == system_program::ID && data.len() == NonceState::size() {
+        decode_nonce_data(data)
+    } else if *owner == STAKE_PROGRAM_ID {
+        decode_stake_data(data)
+    } else {
+        None
+    }
+}
+
+/// Decodes account data as the layout the caller asked for. The account must
+/// still belong to the program that owns that layout.
+fn decode_account_as(hint: AccountDecodeHint, owner: &Pubkey, data: &[u8]) -> Option<Value> {
+    let token_owned = *owner == spl_token::ID || *owner == spl_token_2022::ID;
+    match hint {
+        AccountDecodeHint::Auto => decode_account_data(owner, data),
+        AccountDecodeHint::Mint if token_owned => decode_mint_data(data),
+        AccountDecodeHint::TokenAccount if token_owned => decode_token_account_data(data),
+        AccountDecodeHint::Nonce if *owner == system_program::ID => decode_nonce_data(data),
+        AccountDecodeHint::Stake if *owner == STAKE_PROGRAM_ID => decode_stake_data(data),
+        _ => None,
+    }
+}
+
+pub fn account_to_data(account: &Account) -> AccountInfoData {
+    AccountInfoData {
+        lamports: account.lamports,
+        owner: account.owner.to_string(),
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+        data: STANDARD.encode(&account.data),
+        parsed: decode_account_data(&account.owner, &account.data),
+    }
+}
+
+/// Parses up to `MAX_BATCH_ACCOUNTS` pubkeys and fetches them in order.
+async fn fetch_batch(
+    state: &AppState,
+    pubkeys: &[String],
+    field: &str,
+) -> Result<Vec<(Pubkey, Option<Account>)>, ApiError> {
+    if pubkeys.is_empty() {
+        return Err(ApiError::bad_request(format!("Missing required fields: {field}")));
+    }
+
+    if pubkeys.len() > MAX_BATCH_ACCOUNTS {
+        return Err(ApiError::bad_request(format!(
+            "At most {} pubkeys can be fetched per request",
+            MAX_BATCH_ACCOUNTS
+        )));
+    }
+
+    let keys = pubkeys
+        .iter()
+        .map(|pubkey| parse_pubkey(pubkey, "account"))
+        .collect::<Result<Vec<Pubkey>, ApiError>>()?;
+
+    let client = state.rpc()?;
+    let mut accounts = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+        accounts.extend(client.get_multiple_accounts(chunk).await?);
+    }
+    Ok(keys.into_iter().zip(accounts).collect())
+}
+
+pub async fn accounts_batch(State(state): State<AppState>, Json(payload): Json<AccountBatchRequest>) -> ApiResult {
+    let AccountBatchRequest { pubkeys, decode } = payload;
+
+    let accounts = fetch_batch(&state, &pubkeys, "pubkeys").await?;
+
+    let entries: Vec<AccountBatchEntry> = accounts
+        .iter()
+        .map(|(pubkey, account)| AccountBatchEntry {
+            pubkey: pubkey.to_string(),
+            account: account.as_ref().map(|account| {
+                let mut data = account_to_data(account);
+                if decode == Some(false) {
+                    data.parsed = None;
+                }
+                data
+            }),
+        })
+        .collect();
+
+    Ok(success_response(entries))
+}
+
+/// Like `/accounts/batch`, but each entry says how its account is decoded.
+pub async fn accounts_multiple(
+    State(state): State<AppState>,
+    Json(payload): Json<AccountsMultipleRequest>,
+) -> ApiResult {
+    let pubkeys: Vec<String> = payload.accounts.iter().map(|entry| entry.pubkey.clone()).collect();
+    let accounts = fetch_batch(&state, &pubkeys, "accounts").await?;
+
+    let entries: Vec<AccountBatchEntry> = accounts
+        .iter()
+        .zip(&payload.accounts)
+        .map(|((pubkey, account), entry)| AccountBatchEntry {
+            pubkey: pubkey.to_string(),
+            account: account.as_ref().map(|account| AccountInfoData {
+                parsed: entry.decode.and_then(|hint| decode_account_as(hint, &account.owner, &account.data)),
+                ..account_to_data(account)
+            }),
+        })
+        .collect();
+
+    Ok(success_response(entries))
+}
+
+pub async fn account_info(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Query(query): Query<AccountInfoQuery>,
+) -> ApiResult {
+    let pubkey = parse_pubkey(&pubkey, "account")?;
+
+    if query.data_offset.is_some() && query.data_length.is_none() {
+        return Err(ApiError::bad_request("dataOffset requires dataLength"));
+    }
+    let data_slice = query.data_length.map(|length| UiDataSliceConfig {
+        offset: query.data_offset.unwrap_or_default(),
+        length,
+    });
+
+    let client = state.rpc()?;
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        data_slice,
+        commitment: Some(client.commitment()),
+        ..RpcAccountInfoConfig::default()
+    };
+    let account = client
+        .get_account_with_config(&pubkey, config)
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Account not found"))?;
+
+    let mut data = account_to_data(&account);
+    // A slice can coincidentally match a known layout's length.
+    if data_slice.is_some() {
+        data.parsed = None;
+    }
+    Ok(success_response(data))
+}
+
+pub async fn balance(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Query(query): Query<BalanceQuery>,
+) -> ApiResult {
+    let pubkey = parse_pubkey(&pubkey, "account")?;
+
+    let client = state.rpc()?;
+    let commitment = query.commitment.map(commitment_config).unwrap_or_else(|| client.commitment());
+    let lamports = client.get_balance_with_commitment(&pubkey, commitment).await?.value;
+
+    Ok(success_response(BalanceData {
+        pubkey: pubkey.to_string(),
+        lamports,
+        sol: lamports as f64 / LAMPORTS_PER_SOL as f64,
+    }))
+}
+
+pub async fn program_accounts(State(state): State<AppState>, Json(payload): Json<ProgramAccountsRequest>) -> ApiResult {
+    let ProgramAccountsRequest { program_id, data_size, memcmp, data_offset, data_length, limit, after } = payload;
+
+    if program_id.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: programId"));
+    }
+
+    let program_pubkey = parse_pubkey(&program_id, "program id")?;
+    let after = parse_optional_pubkey(after.as_deref(), "after")?;
+
+    // Unfiltered scans of large programs can return gigabytes.
+    if data_size.is_none() && memcmp.is_empty() {
+        return Err(ApiError::bad_request("At least one dataSize or memcmp filter is required"));
+    }
+    if memcmp.len() + usize::from(data_size.is_some()) > MAX_PROGRAM_ACCOUNT_FILTERS {
+        return Err(ApiError::bad_request(format!(
+            "At most {} filters can be used per request",
+            MAX_PROGRAM_ACCOUNT_FILTERS
+        )));
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_PROGRAM_ACCOUNTS_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PROGRAM_ACCOUNTS_PAGE_SIZE {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {MAX_PROGRAM_ACCOUNTS_PAGE_SIZE}"
+        )));
+    }
+    if data_offset.is_some() && data_length.is_none() {
+        return Err(ApiError::bad_request("dataOffset requires dataLength"));
+    }
+
+    let mut filters: Vec<RpcFilterType> = data_size.map(RpcFilterType::DataSize).into_iter().collect();
+    for filter in memcmp {
+        let bytes = match filter.encoding.unwrap_or(DataEncoding::Base58) {
+            DataEncoding::Base58 => bs58::decode(&filter.bytes).into_vec().ok(),
+            DataEncoding::Base64 => STANDARD.decode(&filter.bytes).ok(),
+        }
+        .ok_or_else(|| ApiError::bad_request("Invalid memcmp bytes encoding"))?;
+        if bytes.is_empty() || bytes.len() > MAX_MEMCMP_BYTES {
+            return Err(ApiError::bad_request(format!(
+                "memcmp bytes must be between 1 and {} bytes long",
+                MAX_MEMCMP_BYTES
+            )));
+        }
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(filter.offset, bytes)));
+    }
+
+    let data_slice = data_length.map(|length| UiDataSliceConfig { offset: data_offset.unwrap_or_default(), length });
+
+    let client = state.rpc()?;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice,
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let mut accounts = client.get_program_accounts_with_config(&program_pubkey, config).await?;
+
+    if accounts.len() > MAX_PROGRAM_ACCOUNT_MATCHES {
+        return Err(ApiError::bad_request(format!(
+            "Query matched {} accounts; narrow the filters to at most {}",
+            accounts.len(),
+            MAX_PROGRAM_ACCOUNT_MATCHES
+        ))
+        .with_code("TOO_MANY_RESULTS"));
+    }
+
+    // RPC results come back in no particular order; sorting makes the cursor stable.
+    accounts.sort_by_key(|(pubkey, _)| *pubkey);
+    let total = accounts.len();
+    let remaining: Vec<_> = accounts
+        .into_iter()
+        .filter(|(pubkey, _)| after.is_none_or(|after| *pubkey > after))
+        .collect();
+    let has_more = remaining.len() > limit;
+
+    let entries: Vec<AccountBatchEntry> = remaining
+        .into_iter()
+        .take(limit)
+        .map(|(pubkey, account)| {
+            let mut data = account_to_data(&account);
+            if data_slice.is_some() {
+                data.parsed = None;
+            }
+            AccountBatchEntry { pubkey: pubkey.to_string(), account: Some(data) }
+        })
+        .collect();
+    let next_after = if has_more { entries.last().map(|entry| entry.pubkey.clone()) } else { None };
+
+    Ok(success_response(ProgramAccountsData {
+        program_id: program_pubkey.to_string(),
+        total,
+        accounts: entries,
+        next_after,
+    }))
+}
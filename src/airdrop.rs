@@ -0,0 +1,59 @@
+//! Faucet airdrops for test setups on devnet, testnet and local validators.
+
+use axum::{extract::State, http::StatusCode, Json};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+use crate::rpc::commitment_config;
+use crate::state::AppState;
+use crate::tx::{await_confirmation, Confirmation};
+use crate::types::{AirdropData, AirdropRequest};
+use crate::utils::{parse_pubkey, success_response, ApiError, ApiResult};
+
+/// Genesis hash of mainnet-beta; a custom RPC URL can still point there.
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+/// Public faucets refuse larger single requests.
+const MAX_AIRDROP_LAMPORTS: u64 = 5 * LAMPORTS_PER_SOL;
+
+pub async fn airdrop(State(state): State<AppState>, Json(payload): Json<AirdropRequest>) -> ApiResult {
+    let AirdropRequest { pubkey, lamports, commitment } = payload;
+
+    if pubkey.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pubkey"));
+    }
+    if lamports == 0 || lamports > MAX_AIRDROP_LAMPORTS {
+        return Err(ApiError::bad_request(format!(
+            "lamports must be between 1 and {}",
+            MAX_AIRDROP_LAMPORTS
+        )));
+    }
+
+    let recipient = parse_pubkey(&pubkey, "pubkey")?;
+
+    let client = state.rpc()?;
+    if client.get_genesis_hash().await?.to_string() == MAINNET_GENESIS_HASH {
+        return Err(ApiError::bad_request("Airdrops are not available on mainnet").with_code("AIRDROP_UNAVAILABLE"));
+    }
+
+    let blockhash = client.get_latest_blockhash().await?;
+    let signature = client
+        .request_airdrop_with_blockhash(&recipient, lamports, &blockhash)
+        .await
+        .map_err(|err| {
+            ApiError::new(StatusCode::BAD_GATEWAY, format!("Faucet rejected the airdrop: {}", err))
+                .with_code("AIRDROP_FAILED")
+        })?;
+
+    let confirmation = match commitment {
+        Some(commitment) => await_confirmation(client, &signature, commitment_config(commitment), &blockhash).await?,
+        None => Confirmation { confirmed: false, confirmation_status: None, slot: None, err: None },
+    };
+
+    Ok(success_response(AirdropData {
+        signature: signature.to_string(),
+        pubkey: recipient.to_string(),
+        lamports,
+        confirmed: confirmation.confirmed,
+        confirmation_status: confirmation.confirmation_status,
+        err: confirmation.err,
+    }))
+}
@@ -0,0 +1,157 @@
+//! u64 amount handling for JavaScript clients.
+//!
+//! Amounts above 2^53 lose precision as JSON numbers in JS, so requests accept
+//! amounts as either numbers or decimal strings, and responses can render
+//! amount fields as strings. The output format defaults to the configured
+//! `amount_format` (`number` or `string`) and can be overridden per request
+//! with the `X-Amount-Format` header or the `amountFormat` query parameter.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde::{de, Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::state::AppState;
+use crate::utils::buffer_response;
+
+pub const AMOUNT_FORMAT_HEADER: &str = "x-amount-format";
+
+/// Response fields holding token amounts or lamports. A response type that
+/// adds an amount field adds its name here.
+const AMOUNT_FIELDS: &[&str] = &[
+    "amount",
+    "lamports",
+    "supply",
+    "stake",
+    "activated_stake",
+    "delegated_amount",
+    "rent_exempt_reserve",
+    "maximum_fee",
+    // Transaction fees.
+    "fee",
+    "base_fee",
+    "priority_fee",
+    // Balance changes, signed ones included.
+    "pre_lamports",
+    "post_lamports",
+    "change",
+    "lamports_change",
+    "pre_amount",
+    "post_amount",
+    "post_balance",
+];
+
+/// How amount fields are rendered in responses.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AmountFormat {
+    Number,
+    /// Decimal strings, for clients that parse JSON numbers as doubles.
+    String,
+}
+
+impl AmountFormat {
+    pub fn parse(format: &str) -> Option<AmountFormat> {
+        match format.to_ascii_lowercase().as_str() {
+            "number" => Some(AmountFormat::Number),
+            "string" => Some(AmountFormat::String),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
+
+impl NumberOrString {
+    fn into_u64<E: de::Error>(self) -> Result<u64, E> {
+        match self {
+            NumberOrString::Number(value) => Ok(value),
+            NumberOrString::String(value) => value
+                .trim()
+                .parse()
+                .map_err(|_| E::custom(format!("invalid amount: {:?}", value))),
+        }
+    }
+}
+
+pub fn deserialize_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    NumberOrString::deserialize(deserializer)?.into_u64()
+}
+
+pub fn deserialize_optional_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<NumberOrString>::deserialize(deserializer)?
+        .map(NumberOrString::into_u64)
+        .transpose()
+}
+
+fn wants_string_amounts(request: &Request, default: AmountFormat) -> bool {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "amountFormat")
+            .map(|(_, value)| value.to_string())
+    });
+    let from_header = request
+        .headers()
+        .get(AMOUNT_FORMAT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    match from_query.or(from_header) {
+        Some(format) => format.eq_ignore_ascii_case("string"),
+        None => default == AmountFormat::String,
+    }
+}
+
+fn stringify_amounts(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if AMOUNT_FIELDS.contains(&key.as_str()) && (field.is_u64() || field.is_i64()) {
+                    *field = Value::String(field.to_string());
+                } else {
+                    stringify_amounts(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_amounts),
+        _ => {}
+    }
+}
+
+/// Rewrites amount fields in JSON responses to strings when requested.
+pub async fn amount_format(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let as_strings = wants_string_amounts(&request, state.amount_format());
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    if !as_strings || !is_json {
+        return response;
+    }
+
+    let (mut parts, bytes) = match buffer_response(response).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let Ok(mut json) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    stringify_amounts(&mut json);
+    let body = serde_json::to_vec(&json).unwrap_or_else(|_| bytes.to_vec());
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    Response::from_parts(parts, Body::from(body))
+}
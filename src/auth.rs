@@ -0,0 +1,139 @@
+//! API key authentication.
+//!
+//! With keys configured, requests identify themselves with an `X-API-Key`
+//! header, matched against the keys' SHA-256 hashes. Each key may be limited
+//! to some route prefixes and to a number of requests per UTC day; the counts
+//! are kept in memory and start over when the server restarts. Health probes
+//! never need a key, and `/auth/key` tells a caller about its own key.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::{ApiKeyConfig, AuthConfig};
+use crate::health::PROBE_ROUTES;
+use crate::types::ApiKeyData;
+use crate::utils::{success_response, ApiError, ApiResult};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// Open to every key, whatever its routes.
+const KEY_INFO_ROUTE: &str = "/auth/key";
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// The key a request authenticated with.
+#[derive(Clone)]
+pub struct Caller(pub Arc<ApiKeyConfig>);
+
+pub struct ApiKeys {
+    required: bool,
+    by_hash: HashMap<[u8; 32], Arc<ApiKeyConfig>>,
+}
+
+impl ApiKeys {
+    pub fn from_config(config: &AuthConfig) -> Self {
+        ApiKeys {
+            required: config.required,
+            by_hash: config.keys.iter().map(|key| (key.key_sha256, Arc::new(key.clone()))).collect(),
+        }
+    }
+}
+
+/// Requests each key has made on the current day, by key name.
+fn usage() -> &'static Mutex<HashMap<String, (u64, u64)>> {
+    static USAGE: OnceLock<Mutex<HashMap<String, (u64, u64)>>> = OnceLock::new();
+    USAGE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn today() -> u64 {
+    unix_timestamp() / SECONDS_PER_DAY
+}
+
+fn used_today(name: &str) -> u64 {
+    let usage = usage().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    usage.get(name).filter(|(day, _)| *day == today()).map_or(0, |(_, count)| *count)
+}
+
+/// Counts a request against `key`'s daily quota, unless it is used up.
+fn use_quota(key: &ApiKeyConfig) -> Result<(), ApiError> {
+    let mut usage = usage().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let today = today();
+    let (day, count) = usage.entry(key.name.clone()).or_insert((today, 0));
+    if *day != today {
+        (*day, *count) = (today, 0);
+    }
+    if let Some(quota) = key.daily_quota.filter(|quota| *count >= *quota) {
+        let retry_after = Duration::from_secs((today + 1) * SECONDS_PER_DAY - unix_timestamp());
+        return Err(ApiError::new(StatusCode::TOO_MANY_REQUESTS, "Daily quota for this API key is used up")
+            .with_code("QUOTA_EXCEEDED")
+            .with_details(json!({ "daily_quota": quota, "retry_after_ms": retry_after.as_millis() as u64 }))
+            .with_retry_after(retry_after));
+    }
+    *count += 1;
+    Ok(())
+}
+
+fn allows(key: &ApiKeyConfig, path: &str) -> bool {
+    path == KEY_INFO_ROUTE || key.routes.is_empty() || key.routes.iter().any(|route| path.starts_with(route.as_str()))
+}
+
+/// Checks the request's API key, its routes and its quota, and hands the key
+/// on as a `Caller` extension.
+pub async fn authenticate(State(keys): State<Arc<ApiKeys>>, mut request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if keys.by_hash.is_empty() || PROBE_ROUTES.contains(&path) {
+        return next.run(request).await;
+    }
+    let Some(provided) = request.headers().get(API_KEY_HEADER) else {
+        if !keys.required {
+            return next.run(request).await;
+        }
+        return ApiError::new(StatusCode::UNAUTHORIZED, "Missing X-API-Key header")
+            .with_code("API_KEY_MISSING")
+            .into_response();
+    };
+    let hash: [u8; 32] = Sha256::digest(provided.as_bytes()).into();
+    let Some(key) = keys.by_hash.get(&hash) else {
+        return ApiError::new(StatusCode::UNAUTHORIZED, "Invalid API key").with_code("INVALID_API_KEY").into_response();
+    };
+    if !allows(key, path) {
+        return ApiError::new(StatusCode::FORBIDDEN, format!("API key {} may not call {path}", key.name))
+            .with_code("ROUTE_NOT_ALLOWED")
+            .into_response();
+    }
+    if let Err(err) = use_quota(key) {
+        return err.into_response();
+    }
+    request.extensions_mut().insert(Caller(key.clone()));
+    next.run(request).await
+}
+
+/// The caller's key: what it may call and how much of today's quota is used.
+pub async fn key_info(caller: Option<Extension<Caller>>) -> ApiResult {
+    let Some(Extension(Caller(key))) = caller else {
+        let err = ApiError::new(StatusCode::NOT_FOUND, "This request carried no API key");
+        return Err(err.with_code("API_KEY_MISSING"));
+    };
+    Ok(success_response(ApiKeyData {
+        name: key.name.clone(),
+        routes: key.routes.clone(),
+        daily_quota: key.daily_quota,
+        used_today: used_today(&key.name),
+        resets_at: (today() + 1) * SECONDS_PER_DAY,
+    }))
+}
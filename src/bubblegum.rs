@@ -0,0 +1,376 @@
+//! Compressed NFT instruction builders for Metaplex Bubblegum.
+//!
+//! Bubblegum is an Anchor program, so instructions are an 8-byte
+//! discriminator followed by borsh-encoded arguments, encoded by hand here.
+//! Transfers need the leaf's current hashes and Merkle proof, which only a
+//! DAS-capable RPC provider can serve.
+
+use axum::{extract::State, http::StatusCode, Json};
+use borsh::BorshSerialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::create_account,
+};
+use solana_sdk_ids::system_program;
+
+use crate::nft::metadata_data;
+use crate::state::AppState;
+use crate::types::{CnftMintRequest, CnftTransferRequest, CnftTreeCreateRequest, CnftTreeData, InstructionsData};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// `(max_depth, max_buffer_size)` pairs the account compression program accepts.
+const VALID_TREE_SIZES: &[(u32, u32)] = &[
+    (3, 8),
+    (5, 8),
+    (6, 16),
+    (7, 16),
+    (8, 16),
+    (9, 16),
+    (10, 32),
+    (11, 32),
+    (12, 32),
+    (13, 32),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+const MAX_CANOPY_DEPTH: u32 = 17;
+
+/// Account type, version, and the V1 header fields.
+const TREE_HEADER_LEN: usize = 2 + 54;
+
+/// Size of a `ConcurrentMerkleTree<max_depth, max_buffer_size>`: three u64
+/// counters, the changelog ring buffer, and the rightmost proof.
+fn tree_body_len(max_depth: u32, max_buffer_size: u32) -> usize {
+    let depth = max_depth as usize;
+    let change_log = 32 + 32 * depth + 4 + 4;
+    let path = 32 * depth + 32 + 4 + 4;
+    24 + max_buffer_size as usize * change_log + path
+}
+
+fn canopy_len(canopy_depth: u32) -> usize {
+    ((1usize << (canopy_depth + 1)) - 2) * 32
+}
+
+/// Reads the canopy depth back out of an existing tree account's size.
+fn canopy_depth(tree_data: &[u8]) -> Result<u32, ApiError> {
+    let invalid = || ApiError::bad_request("Account is not a valid concurrent Merkle tree");
+    let header = tree_data.get(..TREE_HEADER_LEN).ok_or_else(invalid)?;
+    let max_buffer_size = u32::from_le_bytes(header[2..6].try_into().unwrap());
+    let max_depth = u32::from_le_bytes(header[6..10].try_into().unwrap());
+
+    let canopy_bytes = tree_data
+        .len()
+        .checked_sub(TREE_HEADER_LEN + tree_body_len(max_depth, max_buffer_size))
+        .ok_or_else(invalid)?;
+    let nodes = canopy_bytes / 32;
+    Ok((nodes + 2).ilog2().saturating_sub(1))
+}
+
+fn tree_config(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+fn anchor_instruction(name: &str, args: impl BorshSerialize, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = Sha256::digest(format!("global:{name}").as_bytes())[..8].to_vec();
+    args.serialize(&mut data).expect("serializing into a Vec cannot fail");
+    Instruction { program_id: BUBBLEGUM_PROGRAM_ID, accounts, data }
+}
+
+#[derive(BorshSerialize)]
+struct CreateTreeArgs {
+    max_depth: u32,
+    max_buffer_size: u32,
+    public: Option<bool>,
+}
+
+#[derive(BorshSerialize)]
+struct CreatorArgs {
+    address: [u8; 32],
+    verified: bool,
+    share: u8,
+}
+
+/// Bubblegum's `MetadataArgs`; enums are written as their borsh variant index.
+#[derive(BorshSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<u8>,
+    collection: Option<(bool, [u8; 32])>,
+    uses: Option<(u8, u64, u64)>,
+    token_program_version: u8,
+    creators: Vec<CreatorArgs>,
+}
+
+#[derive(BorshSerialize)]
+struct TransferArgs {
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+}
+
+pub async fn create_tree(Json(payload): Json<CnftTreeCreateRequest>) -> ApiResult {
+    let CnftTreeCreateRequest { merkle_tree, payer, tree_creator, max_depth, max_buffer_size, canopy_depth, public } =
+        payload;
+
+    if merkle_tree.is_empty() || payer.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: merkleTree or payer"));
+    }
+
+    let tree_pubkey = parse_pubkey(&merkle_tree, "merkle tree")?;
+    let payer_pubkey = parse_pubkey(&payer, "payer")?;
+    let creator_pubkey = parse_optional_pubkey(tree_creator.as_deref(), "tree creator")?.unwrap_or(payer_pubkey);
+
+    if !VALID_TREE_SIZES.contains(&(max_depth, max_buffer_size)) {
+        return Err(ApiError::bad_request("Unsupported maxDepth and maxBufferSize combination"));
+    }
+    let canopy_depth = canopy_depth.unwrap_or(0);
+    if canopy_depth > MAX_CANOPY_DEPTH || canopy_depth >= max_depth {
+        return Err(ApiError::bad_request(format!(
+            "canopyDepth must be below maxDepth and at most {MAX_CANOPY_DEPTH}"
+        )));
+    }
+
+    let account_size = TREE_HEADER_LEN + tree_body_len(max_depth, max_buffer_size) + canopy_len(canopy_depth);
+    let tree_config = tree_config(&tree_pubkey);
+
+    let instructions = [
+        create_account(
+            &payer_pubkey,
+            &tree_pubkey,
+            Rent::default().minimum_balance(account_size),
+            account_size as u64,
+            &ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ),
+        anchor_instruction(
+            "create_tree",
+            CreateTreeArgs { max_depth, max_buffer_size, public },
+            vec![
+                AccountMeta::new(tree_config, false),
+                AccountMeta::new(tree_pubkey, false),
+                AccountMeta::new(payer_pubkey, true),
+                AccountMeta::new_readonly(creator_pubkey, true),
+                AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        ),
+    ];
+
+    Ok(success_response(CnftTreeData {
+        merkle_tree: tree_pubkey.to_string(),
+        tree_config: tree_config.to_string(),
+        account_size,
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn mint(Json(payload): Json<CnftMintRequest>) -> ApiResult {
+    let CnftMintRequest {
+        merkle_tree,
+        payer,
+        tree_delegate,
+        leaf_owner,
+        leaf_delegate,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+        is_mutable,
+    } = payload;
+
+    if merkle_tree.is_empty() || payer.is_empty() || leaf_owner.is_empty() || name.is_empty() || uri.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: merkleTree, payer, leafOwner, name, or uri",
+        ));
+    }
+
+    let tree_pubkey = parse_pubkey(&merkle_tree, "merkle tree")?;
+    let payer_pubkey = parse_pubkey(&payer, "payer")?;
+    let delegate_pubkey = parse_optional_pubkey(tree_delegate.as_deref(), "tree delegate")?.unwrap_or(payer_pubkey);
+    let owner_pubkey = parse_pubkey(&leaf_owner, "leaf owner")?;
+    let leaf_delegate_pubkey =
+        parse_optional_pubkey(leaf_delegate.as_deref(), "leaf delegate")?.unwrap_or(owner_pubkey);
+
+    // Same limits as Token Metadata; the tree delegate signs, so it is the
+    // only creator that can be verified at mint time.
+    let data = metadata_data(name, symbol, uri, seller_fee_basis_points, creators, &delegate_pubkey)?;
+    let creators = data.creators.unwrap_or_default();
+
+    let mut accounts = vec![
+        AccountMeta::new(tree_config(&tree_pubkey), false),
+        AccountMeta::new_readonly(owner_pubkey, false),
+        AccountMeta::new_readonly(leaf_delegate_pubkey, false),
+        AccountMeta::new(tree_pubkey, false),
+        AccountMeta::new_readonly(payer_pubkey, true),
+        AccountMeta::new_readonly(delegate_pubkey, true),
+        AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(
+        creators
+            .iter()
+            .filter(|creator| creator.verified)
+            .map(|creator| AccountMeta::new_readonly(creator.address, true)),
+    );
+
+    let args = MetadataArgs {
+        name: data.name,
+        symbol: data.symbol,
+        uri: data.uri,
+        seller_fee_basis_points: data.seller_fee_basis_points,
+        primary_sale_happened: false,
+        is_mutable: is_mutable.unwrap_or(true),
+        edition_nonce: None,
+        // TokenStandard::NonFungible
+        token_standard: Some(0),
+        collection: None,
+        uses: None,
+        // TokenProgramVersion::Original
+        token_program_version: 0,
+        creators: creators
+            .iter()
+            .map(|creator| CreatorArgs {
+                address: creator.address.to_bytes(),
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect(),
+    };
+
+    let ix = anchor_instruction("mint_v1", args, accounts);
+
+    Ok(success_response(InstructionsData { instructions: vec![instruction_to_data(&ix)] }))
+}
+
+fn das_field<'a>(value: &'a Value, path: &str) -> Result<&'a Value, ApiError> {
+    value
+        .pointer(path)
+        .filter(|field| !field.is_null())
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, format!("DAS response is missing {path}")))
+}
+
+fn das_hash(value: &Value, path: &str) -> Result<[u8; 32], ApiError> {
+    das_field(value, path)?
+        .as_str()
+        .and_then(|hash| bs58::decode(hash.trim()).into_vec().ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, format!("DAS response has an invalid {path}")))
+}
+
+fn das_pubkey(value: &Value, path: &str) -> Result<Pubkey, ApiError> {
+    das_hash(value, path).map(Pubkey::new_from_array)
+}
+
+pub async fn transfer(State(state): State<AppState>, Json(payload): Json<CnftTransferRequest>) -> ApiResult {
+    let CnftTransferRequest { asset_id, new_leaf_owner } = payload;
+
+    if asset_id.is_empty() || new_leaf_owner.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: assetId or newLeafOwner"));
+    }
+
+    let asset_pubkey = parse_pubkey(&asset_id, "asset id")?;
+    let new_owner_pubkey = parse_pubkey(&new_leaf_owner, "new leaf owner")?;
+
+    let client = state.rpc()?;
+    let params = json!([asset_pubkey.to_string()]);
+    let asset: Value = client.send(RpcRequest::Custom { method: "getAsset" }, params.clone()).await?;
+    let proof: Value = client.send(RpcRequest::Custom { method: "getAssetProof" }, params).await?;
+
+    if das_field(&asset, "/compression/compressed")?.as_bool() != Some(true) {
+        return Err(ApiError::bad_request("Asset is not a compressed NFT"));
+    }
+
+    let tree_pubkey = das_pubkey(&proof, "/tree_id")?;
+    let owner_pubkey = das_pubkey(&asset, "/ownership/owner")?;
+    let delegate_pubkey = match asset.pointer("/ownership/delegate").and_then(Value::as_str) {
+        Some(delegate) => parse_pubkey(delegate, "leaf delegate")?,
+        None => owner_pubkey,
+    };
+    let nonce = das_field(&asset, "/compression/leaf_id")?
+        .as_u64()
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "DAS response has an invalid leaf_id"))?;
+    let index = u32::try_from(nonce).map_err(|_| ApiError::new(StatusCode::BAD_GATEWAY, "Leaf index out of range"))?;
+
+    let proof_nodes = das_field(&proof, "/proof")?
+        .as_array()
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "DAS response has an invalid proof"))?
+        .iter()
+        .map(|node| {
+            node.as_str()
+                .and_then(|node| Pubkey::try_from(node).ok())
+                .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "DAS response has an invalid proof"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Nodes covered by the on-chain canopy must be left out of the proof.
+    let tree_data = client.get_account_data(&tree_pubkey).await?;
+    let canopy_depth = canopy_depth(&tree_data)? as usize;
+    let proof_len = proof_nodes.len().saturating_sub(canopy_depth);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(tree_config(&tree_pubkey), false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new_readonly(delegate_pubkey, false),
+        AccountMeta::new_readonly(new_owner_pubkey, false),
+        AccountMeta::new(tree_pubkey, false),
+        AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(proof_nodes[..proof_len].iter().map(|node| AccountMeta::new_readonly(*node, false)));
+
+    let args = TransferArgs {
+        root: das_hash(&proof, "/root")?,
+        data_hash: das_hash(&asset, "/compression/data_hash")?,
+        creator_hash: das_hash(&asset, "/compression/creator_hash")?,
+        nonce,
+        index,
+    };
+
+    let ix = anchor_instruction("transfer", args, accounts);
+
+    Ok(success_response(InstructionsData { instructions: vec![instruction_to_data(&ix)] }))
+}
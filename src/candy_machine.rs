@@ -0,0 +1,353 @@
+//! Candy Machine v3 mint composition.
+//!
+//! Mints go through the Candy Guard program's `mint_v2`, whose guards each
+//! expect their own accounts appended in guard order. Guards that need
+//! per-buyer inputs (allow list proofs, specific NFTs, gateway tokens) or
+//! freeze escrows are rejected rather than guessed at.
+
+use axum::{extract::State, http::StatusCode, Json};
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::{
+    accounts::{CollectionAuthorityRecord, MasterEdition, Metadata, MetadataDelegateRecord, TokenRecord},
+    types::MetadataDelegateRole,
+    ID as TOKEN_METADATA_PROGRAM_ID,
+};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    sysvar::{instructions, slot_hashes},
+};
+use solana_sdk_ids::system_program;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::state::AppState;
+use crate::types::{CandyMachineMintData, CandyMachineMintRequest};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const CANDY_MACHINE_PROGRAM_ID: Pubkey = pubkey!("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhSefbAnjHG3JR");
+pub const CANDY_GUARD_PROGRAM_ID: Pubkey = pubkey!("Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g");
+
+/// Discriminator, account version, token standard, and feature bytes.
+const CANDY_MACHINE_HEADER_LEN: usize = 8 + 1 + 1 + 6;
+/// Discriminator, base, bump, and authority precede the guard data.
+const CANDY_GUARD_DATA_OFFSET: usize = 8 + 32 + 1 + 32;
+/// Group labels are stored as fixed-width, NUL-padded bytes.
+const GROUP_LABEL_LEN: usize = 6;
+
+/// Guards in feature-bit order, with the serialized size of their settings.
+/// `program_gate` is variable-length and handled separately.
+const GUARDS: &[(&str, usize)] = &[
+    ("botTax", 9),
+    ("solPayment", 40),
+    ("tokenPayment", 72),
+    ("startDate", 8),
+    ("thirdPartySigner", 32),
+    ("tokenGate", 40),
+    ("gatekeeper", 33),
+    ("endDate", 8),
+    ("allowList", 32),
+    ("mintLimit", 3),
+    ("nftPayment", 64),
+    ("redeemedAmount", 8),
+    ("addressGate", 32),
+    ("nftGate", 32),
+    ("nftBurn", 32),
+    ("tokenBurn", 40),
+    ("freezeSolPayment", 40),
+    ("freezeTokenPayment", 72),
+    ("programGate", 0),
+    ("allocation", 5),
+    ("token2022Payment", 72),
+];
+
+/// Settings of the enabled guards that contribute mint accounts.
+enum Guard {
+    SolPayment { destination: Pubkey },
+    TokenPayment { mint: Pubkey, destination_ata: Pubkey },
+    ThirdPartySigner { signer: Pubkey },
+    TokenGate { mint: Pubkey },
+    MintLimit { id: u8 },
+    TokenBurn { mint: Pubkey },
+    Allocation { id: u8 },
+    NoAccounts,
+    Unsupported(&'static str),
+}
+
+fn invalid_guard() -> ApiError {
+    ApiError::bad_request("Account is not a valid candy guard")
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], ApiError> {
+    if data.len() < len {
+        return Err(invalid_guard());
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+fn read<T: BorshDeserialize>(data: &mut &[u8]) -> Result<T, ApiError> {
+    T::deserialize(data).map_err(|_| invalid_guard())
+}
+
+fn read_pubkey(data: &mut &[u8]) -> Result<Pubkey, ApiError> {
+    read::<[u8; 32]>(data).map(Pubkey::new_from_array)
+}
+
+/// Parses one serialized guard set, advancing `data` past it.
+fn parse_guard_set(data: &mut &[u8]) -> Result<Vec<Option<Guard>>, ApiError> {
+    let features: u64 = read(data)?;
+    if features >> GUARDS.len() != 0 {
+        return Err(ApiError::bad_request("Candy guard uses a guard this endpoint does not recognize"));
+    }
+
+    let mut guards = Vec::with_capacity(GUARDS.len());
+    for (index, &(name, size)) in GUARDS.iter().enumerate() {
+        if features & (1 << index) == 0 {
+            guards.push(None);
+            continue;
+        }
+
+        let guard = match name {
+            "solPayment" => {
+                let _lamports: u64 = read(data)?;
+                Guard::SolPayment { destination: read_pubkey(data)? }
+            }
+            "tokenPayment" => {
+                let _amount: u64 = read(data)?;
+                Guard::TokenPayment { mint: read_pubkey(data)?, destination_ata: read_pubkey(data)? }
+            }
+            "thirdPartySigner" => Guard::ThirdPartySigner { signer: read_pubkey(data)? },
+            "tokenGate" => {
+                let _amount: u64 = read(data)?;
+                Guard::TokenGate { mint: read_pubkey(data)? }
+            }
+            "mintLimit" => {
+                let id: u8 = read(data)?;
+                let _limit: u16 = read(data)?;
+                Guard::MintLimit { id }
+            }
+            "tokenBurn" => {
+                let _amount: u64 = read(data)?;
+                Guard::TokenBurn { mint: read_pubkey(data)? }
+            }
+            "allocation" => {
+                let id: u8 = read(data)?;
+                let _limit: u32 = read(data)?;
+                Guard::Allocation { id }
+            }
+            "programGate" => {
+                let count: u32 = read(data)?;
+                take(data, count as usize * 32)?;
+                Guard::NoAccounts
+            }
+            "botTax" | "startDate" | "endDate" | "redeemedAmount" | "addressGate" => {
+                take(data, size)?;
+                Guard::NoAccounts
+            }
+            _ => {
+                take(data, size)?;
+                Guard::Unsupported(name)
+            }
+        };
+        guards.push(Some(guard));
+    }
+
+    Ok(guards)
+}
+
+/// Resolves the guard set in effect for `label`: group guards override defaults.
+fn active_guards(guard_data: &[u8], label: Option<&str>) -> Result<Vec<Option<Guard>>, ApiError> {
+    let mut data = guard_data.get(CANDY_GUARD_DATA_OFFSET..).ok_or_else(invalid_guard)?;
+    let default = parse_guard_set(&mut data)?;
+
+    let group_count: u32 = read(&mut data)?;
+    let mut groups = Vec::with_capacity(group_count as usize);
+    for _ in 0..group_count {
+        let raw_label = take(&mut data, GROUP_LABEL_LEN)?;
+        let group_label = String::from_utf8_lossy(raw_label).trim_end_matches('\0').to_string();
+        groups.push((group_label, parse_guard_set(&mut data)?));
+    }
+
+    match (label, groups.is_empty()) {
+        (None, true) => Ok(default),
+        (None, false) => Err(ApiError::bad_request(format!(
+            "Candy machine has guard groups; group is required (one of: {})",
+            groups.iter().map(|(label, _)| label.as_str()).collect::<Vec<_>>().join(", ")
+        ))),
+        (Some(label), _) => {
+            let (_, group) = groups
+                .into_iter()
+                .find(|(group_label, _)| group_label == label)
+                .ok_or_else(|| ApiError::bad_request(format!("Guard group {label} not found")))?;
+            Ok(group.into_iter().zip(default).map(|(group, default)| group.or(default)).collect())
+        }
+    }
+}
+
+#[derive(BorshSerialize)]
+struct MintV2Args {
+    mint_args: Vec<u8>,
+    label: Option<String>,
+}
+
+pub async fn mint(State(state): State<AppState>, Json(payload): Json<CandyMachineMintRequest>) -> ApiResult {
+    let CandyMachineMintRequest { candy_machine, buyer, nft_mint, payer, group } = payload;
+
+    if candy_machine.is_empty() || buyer.is_empty() || nft_mint.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: candyMachine, buyer, or nftMint"));
+    }
+
+    let candy_machine_pubkey = parse_pubkey(&candy_machine, "candy machine")?;
+    let buyer_pubkey = parse_pubkey(&buyer, "buyer")?;
+    let nft_mint_pubkey = parse_pubkey(&nft_mint, "nft mint")?;
+    let payer_pubkey = parse_optional_pubkey(payer.as_deref(), "payer")?.unwrap_or(buyer_pubkey);
+
+    let client = state.rpc()?;
+    let machine = client
+        .get_account_with_commitment(&candy_machine_pubkey, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Candy machine account not found"))?;
+    if machine.owner != CANDY_MACHINE_PROGRAM_ID {
+        return Err(ApiError::bad_request("Account is not owned by the Candy Machine program"));
+    }
+
+    let invalid_machine = || ApiError::bad_request("Account is not a valid candy machine");
+    let header = machine.data.get(..CANDY_MACHINE_HEADER_LEN).ok_or_else(invalid_machine)?;
+    let (account_version, token_standard) = (header[8], header[9]);
+    let mut fields = machine.data.get(CANDY_MACHINE_HEADER_LEN..).ok_or_else(invalid_machine)?;
+    let (_authority, mint_authority, collection_mint, items_redeemed, items_available) =
+        <([u8; 32], [u8; 32], [u8; 32], u64, u64)>::deserialize(&mut fields).map_err(|_| invalid_machine())?;
+    let candy_guard = Pubkey::new_from_array(mint_authority);
+    let collection_mint = Pubkey::new_from_array(collection_mint);
+
+    if items_redeemed >= items_available {
+        return Err(ApiError::bad_request("Candy machine is sold out"));
+    }
+
+    let (collection_metadata, _) = Metadata::find_pda(&collection_mint);
+    let accounts = client.get_multiple_accounts(&[candy_guard, collection_metadata]).await?;
+    let guard_account = accounts[0]
+        .as_ref()
+        .filter(|account| account.owner == CANDY_GUARD_PROGRAM_ID)
+        .ok_or_else(|| ApiError::bad_request("Candy machine is not governed by a candy guard"))?;
+    let collection_update_authority = accounts[1]
+        .as_ref()
+        .and_then(|account| Metadata::safe_deserialize(&account.data).ok())
+        .map(|metadata| metadata.update_authority)
+        .ok_or_else(|| ApiError::bad_request("Collection metadata account not found"))?;
+
+    let guards = active_guards(&guard_account.data, group.as_deref())?;
+    for guard in guards.iter().flatten() {
+        if let Guard::Unsupported(name) = guard {
+            return Err(ApiError::bad_request(format!("The {name} guard is not supported by this endpoint")));
+        }
+    }
+
+    let (authority_pda, _) =
+        Pubkey::find_program_address(&[b"candy_machine", candy_machine_pubkey.as_ref()], &CANDY_MACHINE_PROGRAM_ID);
+    // AccountVersion::V1 machines still use legacy collection authority records.
+    let collection_delegate_record = if account_version == 0 {
+        CollectionAuthorityRecord::find_pda(&collection_mint, &authority_pda).0
+    } else {
+        MetadataDelegateRecord::find_pda(
+            &collection_mint,
+            MetadataDelegateRole::Collection,
+            &collection_update_authority,
+            &authority_pda,
+        )
+        .0
+    };
+
+    let token_account = get_associated_token_address(&buyer_pubkey, &nft_mint_pubkey);
+    // TokenStandard::ProgrammableNonFungible
+    let token_record = if token_standard == 4 {
+        TokenRecord::find_pda(&nft_mint_pubkey, &token_account).0
+    } else {
+        CANDY_GUARD_PROGRAM_ID
+    };
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(candy_guard, false),
+        AccountMeta::new_readonly(CANDY_MACHINE_PROGRAM_ID, false),
+        AccountMeta::new(candy_machine_pubkey, false),
+        AccountMeta::new(authority_pda, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new(buyer_pubkey, true),
+        AccountMeta::new(nft_mint_pubkey, true),
+        AccountMeta::new_readonly(buyer_pubkey, true),
+        AccountMeta::new(Metadata::find_pda(&nft_mint_pubkey).0, false),
+        AccountMeta::new(MasterEdition::find_pda(&nft_mint_pubkey).0, false),
+        AccountMeta::new(token_account, false),
+        AccountMeta::new(token_record, false),
+        AccountMeta::new_readonly(collection_delegate_record, false),
+        AccountMeta::new_readonly(collection_mint, false),
+        AccountMeta::new(collection_metadata, false),
+        AccountMeta::new_readonly(MasterEdition::find_pda(&collection_mint).0, false),
+        AccountMeta::new_readonly(collection_update_authority, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(instructions::ID, false),
+        AccountMeta::new_readonly(slot_hashes::ID, false),
+        // Optional authorization rules program and rule set, left unset.
+        AccountMeta::new_readonly(CANDY_GUARD_PROGRAM_ID, false),
+        AccountMeta::new_readonly(CANDY_GUARD_PROGRAM_ID, false),
+    ];
+
+    for guard in guards.iter().flatten() {
+        match guard {
+            Guard::SolPayment { destination } => accounts.push(AccountMeta::new(*destination, false)),
+            Guard::TokenPayment { mint, destination_ata } => {
+                accounts.push(AccountMeta::new(get_associated_token_address(&buyer_pubkey, mint), false));
+                accounts.push(AccountMeta::new(*destination_ata, false));
+            }
+            Guard::ThirdPartySigner { signer } => accounts.push(AccountMeta::new_readonly(*signer, true)),
+            Guard::TokenGate { mint } => {
+                accounts.push(AccountMeta::new_readonly(get_associated_token_address(&buyer_pubkey, mint), false));
+            }
+            Guard::MintLimit { id } => {
+                let (counter, _) = Pubkey::find_program_address(
+                    &[
+                        b"mint_limit",
+                        &[*id],
+                        buyer_pubkey.as_ref(),
+                        candy_guard.as_ref(),
+                        candy_machine_pubkey.as_ref(),
+                    ],
+                    &CANDY_GUARD_PROGRAM_ID,
+                );
+                accounts.push(AccountMeta::new(counter, false));
+            }
+            Guard::TokenBurn { mint } => {
+                accounts.push(AccountMeta::new(get_associated_token_address(&buyer_pubkey, mint), false));
+                accounts.push(AccountMeta::new(*mint, false));
+            }
+            Guard::Allocation { id } => {
+                let (tracker, _) = Pubkey::find_program_address(
+                    &[b"allocation", &[*id], candy_guard.as_ref(), candy_machine_pubkey.as_ref()],
+                    &CANDY_GUARD_PROGRAM_ID,
+                );
+                accounts.push(AccountMeta::new(tracker, false));
+            }
+            Guard::NoAccounts | Guard::Unsupported(_) => {}
+        }
+    }
+
+    let mut data = Sha256::digest(b"global:mint_v2")[..8].to_vec();
+    MintV2Args { mint_args: Vec::new(), label: group }
+        .serialize(&mut data)
+        .expect("serializing into a Vec cannot fail");
+    let ix = Instruction { program_id: CANDY_GUARD_PROGRAM_ID, accounts, data };
+
+    Ok(success_response(CandyMachineMintData {
+        nft_mint: nft_mint_pubkey.to_string(),
+        candy_guard: candy_guard.to_string(),
+        items_remaining: items_available - items_redeemed,
+        instructions: vec![instruction_to_data(&ix)],
+    }))
+}
@@ -0,0 +1,125 @@
+//! Single-use challenges for proving control of a key.
+//!
+//! `/message/challenge` issues a nonce bound to a pubkey. The client signs a
+//! message containing a `Nonce: <nonce>` line, and `/message/verify` with
+//! `requireChallenge` accepts the signature only while that nonce is unused
+//! and unexpired, consuming it so the signed message cannot be replayed.
+
+use axum::{extract::Query, http::StatusCode};
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::types::{ChallengeData, ChallengeQuery};
+use crate::utils::{format_utc_timestamp, parse_pubkey, success_response, ApiError, ApiResult};
+
+const DEFAULT_CHALLENGE_TTL: Duration = Duration::from_secs(300);
+const MAX_CHALLENGE_TTL: Duration = Duration::from_secs(3600);
+const NONCE_PREFIX: &str = "Nonce: ";
+/// Outstanding challenges across all keys and for any one key; issuing is
+/// unauthenticated, so without these the map grows with every request.
+const MAX_PENDING_CHALLENGES: usize = 10_000;
+const MAX_CHALLENGES_PER_PUBKEY: usize = 16;
+
+struct Challenge {
+    pubkey: String,
+    expires: Instant,
+}
+
+/// Issued challenges by nonce, until they are used or expire.
+fn challenges() -> &'static Mutex<HashMap<String, Challenge>> {
+    static CHALLENGES: OnceLock<Mutex<HashMap<String, Challenge>>> = OnceLock::new();
+    CHALLENGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn challenge_error(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED, message).with_code("CHALLENGE_INVALID")
+}
+
+/// 429 until the soonest of `expiries` frees a slot.
+fn too_many_challenges<'a>(message: &str, expiries: impl Iterator<Item = &'a Instant>) -> ApiError {
+    let retry_after =
+        expiries.min().map_or(Duration::ZERO, |expires| expires.saturating_duration_since(Instant::now()));
+    ApiError::new(StatusCode::TOO_MANY_REQUESTS, message)
+        .with_code("TOO_MANY_CHALLENGES")
+        .with_retry_after(retry_after)
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+pub async fn issue_challenge(Query(query): Query<ChallengeQuery>) -> ApiResult {
+    let ChallengeQuery { pubkey, ttl_seconds } = query;
+
+    let Some(pubkey) = pubkey.filter(|pubkey| !pubkey.is_empty()) else {
+        return Err(ApiError::bad_request("Missing required fields: pubkey"));
+    };
+    let pubkey = parse_pubkey(&pubkey, "pubkey")?.to_string();
+    let ttl = ttl_seconds.map_or(DEFAULT_CHALLENGE_TTL, Duration::from_secs);
+    if ttl.is_zero() || ttl > MAX_CHALLENGE_TTL {
+        return Err(ApiError::bad_request(format!(
+            "ttlSeconds must be between 1 and {}",
+            MAX_CHALLENGE_TTL.as_secs()
+        )));
+    }
+
+    let nonce = hex::encode(rand::random::<[u8; 16]>());
+    let expires_at = format_utc_timestamp(unix_timestamp() + ttl.as_secs());
+    let message =
+        format!("Sign this message to prove you control {pubkey}.\n\n{NONCE_PREFIX}{nonce}\nExpires At: {expires_at}");
+
+    {
+        let mut challenges = challenges().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        challenges.retain(|_, challenge| challenge.expires > Instant::now());
+        let outstanding: Vec<&Instant> = challenges
+            .values()
+            .filter(|challenge| challenge.pubkey == pubkey)
+            .map(|challenge| &challenge.expires)
+            .collect();
+        if outstanding.len() >= MAX_CHALLENGES_PER_PUBKEY {
+            return Err(too_many_challenges(
+                &format!("At most {MAX_CHALLENGES_PER_PUBKEY} challenges can be outstanding for one pubkey"),
+                outstanding.into_iter(),
+            ));
+        }
+        if challenges.len() >= MAX_PENDING_CHALLENGES {
+            return Err(too_many_challenges(
+                "Too many challenges are outstanding; try again later",
+                challenges.values().map(|challenge| &challenge.expires),
+            ));
+        }
+        challenges.insert(nonce.clone(), Challenge { pubkey: pubkey.clone(), expires: Instant::now() + ttl });
+    }
+
+    Ok(success_response(ChallengeData { nonce, pubkey, expires_at, message }))
+}
+
+/// The nonce of the `Nonce:` line in a signed message.
+pub fn challenge_nonce(message: &[u8]) -> Result<&str, ApiError> {
+    std::str::from_utf8(message)
+        .ok()
+        .and_then(|text| text.lines().find_map(|line| line.trim().strip_prefix(NONCE_PREFIX)))
+        .map(str::trim)
+        .ok_or_else(|| {
+            ApiError::bad_request("Message does not contain a challenge nonce").with_code("CHALLENGE_MISSING")
+        })
+}
+
+/// Uses up `nonce` for `pubkey`. Fails when it was never issued, has expired,
+/// was already used or belongs to another key, which leaves it untouched.
+pub fn consume_challenge(nonce: &str, pubkey: &str) -> Result<(), ApiError> {
+    let mut challenges = challenges().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    challenges.retain(|_, challenge| challenge.expires > Instant::now());
+    match challenges.get(nonce) {
+        None => Err(challenge_error("Challenge is unknown, expired or already used")),
+        Some(challenge) if challenge.pubkey != pubkey => Err(challenge_error("Challenge was issued to another pubkey")),
+        Some(_) => {
+            challenges.remove(nonce);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Cluster-wide reads: slot and epoch progress, and the validator set.
+
+use axum::extract::{Query, State};
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::rpc::provider_name;
+use crate::state::AppState;
+use crate::types::{ClusterInfoData, SortOrder, ValidatorData, ValidatorSort, ValidatorsData, ValidatorsQuery};
+use crate::utils::{page_offset, success_response, ApiError, ApiResult};
+
+const DEFAULT_VALIDATORS_PAGE_SIZE: usize = 100;
+const MAX_VALIDATORS_PAGE_SIZE: usize = 1000;
+
+pub async fn cluster_info(State(state): State<AppState>) -> ApiResult {
+    let client = state.rpc()?;
+    let slot = client.get_slot_with_commitment(CommitmentConfig::processed()).await?;
+    let epoch = client.get_epoch_info().await?;
+    let version = client.get_version().await?;
+
+    Ok(success_response(ClusterInfoData {
+        rpc: provider_name(state.rpc_url()),
+        slot,
+        epoch: epoch.epoch,
+        slot_index: epoch.slot_index,
+        slots_in_epoch: epoch.slots_in_epoch,
+        absolute_slot: epoch.absolute_slot,
+        block_height: epoch.block_height,
+        transaction_count: epoch.transaction_count,
+        version: version.solana_core,
+        feature_set: version.feature_set,
+    }))
+}
+
+pub async fn validators(State(state): State<AppState>, Query(query): Query<ValidatorsQuery>) -> ApiResult {
+    let limit = query.limit.unwrap_or(DEFAULT_VALIDATORS_PAGE_SIZE);
+    if limit == 0 || limit > MAX_VALIDATORS_PAGE_SIZE {
+        return Err(ApiError::bad_request(format!("limit must be between 1 and {MAX_VALIDATORS_PAGE_SIZE}")));
+    }
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::bad_request("page must be at least 1"));
+    }
+
+    let client = state.rpc()?;
+    let vote_accounts = client.get_vote_accounts().await?;
+
+    let delinquent = if query.include_delinquent { vote_accounts.delinquent } else { Vec::new() };
+    let mut validators: Vec<ValidatorData> = vote_accounts
+        .current
+        .into_iter()
+        .map(|info| (info, false))
+        .chain(delinquent.into_iter().map(|info| (info, true)))
+        .filter(|(info, _)| query.max_commission.is_none_or(|max| info.commission <= max))
+        .filter(|(info, _)| query.min_stake.is_none_or(|min| info.activated_stake >= min))
+        .map(|(info, delinquent)| ValidatorData {
+            vote_account: info.vote_pubkey,
+            node: info.node_pubkey,
+            activated_stake: info.activated_stake,
+            commission: info.commission,
+            last_vote: info.last_vote,
+            root_slot: info.root_slot,
+            epoch_vote_account: info.epoch_vote_account,
+            delinquent,
+        })
+        .collect();
+
+    let sort = query.sort.unwrap_or(ValidatorSort::Stake);
+    let order = query.order.unwrap_or(match sort {
+        ValidatorSort::Commission => SortOrder::Asc,
+        ValidatorSort::Stake | ValidatorSort::LastVote => SortOrder::Desc,
+    });
+    validators.sort_by(|a, b| {
+        let ordering = match sort {
+            ValidatorSort::Stake => a.activated_stake.cmp(&b.activated_stake),
+            ValidatorSort::Commission => a.commission.cmp(&b.commission),
+            ValidatorSort::LastVote => a.last_vote.cmp(&b.last_vote),
+        };
+        let ordering = if order == SortOrder::Desc { ordering.reverse() } else { ordering };
+        // Ties keep a stable order across pages.
+        ordering.then_with(|| a.vote_account.cmp(&b.vote_account))
+    });
+
+    let total = validators.len();
+    let validators = validators.into_iter().skip(page_offset(page, limit)).take(limit).collect();
+
+    Ok(success_response(ValidatorsData { total, page, limit, validators }))
+}
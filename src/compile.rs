@@ -0,0 +1,304 @@
+//! Ready-to-sign output for instruction endpoints.
+//!
+//! Any instruction-building endpoint accepts a `compile` object in its request
+//! body, e.g. `{"feePayer": "...", "recentBlockhash": "auto"}`. When present,
+//! the response also carries `compiled`: the returned instructions (compute
+//! budget ones first, a memo last) assembled into a message and an unsigned
+//! transaction, so clients can hand them straight to a wallet.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+use crate::compute_budget::is_instruction_payload;
+use crate::state::AppState;
+use crate::tx::{encode_transaction, parse_blockhash, required_signers};
+use crate::types::{CompileOptions, CompiledMessageData, TokenData};
+use crate::utils::{buffer_request, buffer_response, parse_pubkey, ApiError};
+
+/// Blockhash value asking the service to fetch a fresh one.
+const AUTO_BLOCKHASH: &str = "auto";
+
+/// Fields making up an instruction returned directly as `data`.
+const INSTRUCTION_DATA_FIELDS: [&str; 3] = ["program_id", "accounts", "instruction_data"];
+
+/// Whether a response value is, or lists, instructions.
+fn holds_instructions(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => object.contains_key("program_id"),
+        Value::Array(values) => values.iter().any(holds_instructions),
+        _ => false,
+    }
+}
+
+/// Parses one instruction in the shape `instruction_to_data` returns.
+fn parse_response_instruction(field: &str, value: &Value) -> Result<Instruction, ApiError> {
+    let cannot_compile = |reason: String| ApiError::bad_request(format!("Cannot compile {field}: {reason}"));
+    let data = TokenData::deserialize(value).map_err(|err| cannot_compile(err.to_string()))?;
+    let accounts = data
+        .accounts
+        .iter()
+        .map(|account| {
+            Ok(AccountMeta {
+                pubkey: parse_pubkey(&account.pubkey, "account")?,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    Ok(Instruction {
+        program_id: parse_pubkey(&data.program_id, "program id")?,
+        accounts,
+        data: bs58::decode(&data.instruction_data)
+            .into_vec()
+            .map_err(|_| cannot_compile("instruction_data is not base58".to_string()))?,
+    })
+}
+
+/// Instructions in a response `data` object in the order they run: compute
+/// budget ones, the endpoint's own, then its memo. Any other field holding
+/// instructions is an error rather than something silently left out.
+fn response_instructions(data: &Value) -> Result<Vec<Instruction>, ApiError> {
+    let Some(fields) = data.as_object() else {
+        return Err(ApiError::bad_request("This endpoint's response cannot be compiled"));
+    };
+    let single = fields.contains_key("program_id");
+
+    let mut instructions = Vec::new();
+    for field in ["compute_budget_instructions", "instructions"] {
+        for (index, value) in fields.get(field).and_then(Value::as_array).into_iter().flatten().enumerate() {
+            instructions.push(parse_response_instruction(&format!("{field}[{index}]"), value)?);
+        }
+    }
+    if single {
+        instructions.push(parse_response_instruction("data", data)?);
+    }
+    if let Some(memo) = fields.get("memo_instruction") {
+        instructions.push(parse_response_instruction("memo_instruction", memo)?);
+    }
+
+    let mapped = |field: &str| {
+        ["compute_budget_instructions", "instructions", "memo_instruction"].contains(&field)
+            || (single && INSTRUCTION_DATA_FIELDS.contains(&field))
+    };
+    let unmapped = fields.iter().find(|(field, value)| !mapped(field) && holds_instructions(value));
+    if let Some((field, _)) = unmapped {
+        return Err(ApiError::bad_request(format!("Cannot compile {field}: it is not part of the compiled message")));
+    }
+    Ok(instructions)
+}
+
+async fn compile(
+    state: &AppState,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    blockhash: Option<&str>,
+) -> Result<Value, ApiError> {
+    let blockhash = match blockhash {
+        Some(blockhash) if blockhash != AUTO_BLOCKHASH => parse_blockhash(blockhash)?,
+        _ => state.rpc()?.get_latest_blockhash().await?,
+    };
+
+    let message = Message::new_with_blockhash(instructions, Some(fee_payer), &blockhash);
+    let compiled = CompiledMessageData {
+        message: STANDARD.encode(message.serialize()),
+        signers: required_signers(&message),
+        transaction: encode_transaction(&Transaction::new_unsigned(message))?,
+        recent_blockhash: blockhash.to_string(),
+    };
+    Ok(json!(compiled))
+}
+
+/// Attaches a compiled message to instruction endpoint responses on request.
+pub async fn compile_option(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let (parts, bytes) = match buffer_request(request).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+
+    let options = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|mut json| json.get_mut("compile").map(Value::take));
+    let request = Request::from_parts(parts, Body::from(bytes));
+    let Some(options) = options else {
+        return next.run(request).await;
+    };
+
+    let options: CompileOptions = match serde_json::from_value(options) {
+        Ok(options) => options,
+        Err(_) => return ApiError::bad_request("compile must be an object with a feePayer").into_response(),
+    };
+    let fee_payer = match parse_pubkey(&options.fee_payer, "fee payer") {
+        Ok(fee_payer) => fee_payer,
+        Err(err) => return err.into_response(),
+    };
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (mut parts, bytes) = match buffer_response(response).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let mut json = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(json) if json.get("data").is_some_and(is_instruction_payload) => json,
+        _ => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    let compiled = match response_instructions(&json["data"]) {
+        Ok(instructions) => compile(&state, &instructions, &fee_payer, options.recent_blockhash.as_deref()).await,
+        Err(err) => Err(err),
+    };
+    json["data"]["compiled"] = match compiled {
+        Ok(compiled) => compiled,
+        Err(err) => return err.into_response(),
+    };
+
+    let body = serde_json::to_vec(&json).unwrap_or_else(|_| bytes.to_vec());
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::to_bytes, Json};
+    use solana_sdk_ids::{compute_budget, system_program};
+    use solana_stake_interface::program::ID as STAKE_PROGRAM_ID;
+    use spl_associated_token_account::get_associated_token_address;
+
+    use crate::types::{
+        ComputeBudgetRequest, MemoRequest, ProgramSetAuthorityRequest, SendSOLRequest, SendTokenRequest,
+        StakeDeactivateRequest,
+    };
+    use crate::utils::instruction_to_data;
+
+    async fn compiled(response: impl IntoResponse) -> Result<Vec<Instruction>, ApiError> {
+        let response = response.into_response();
+        assert!(response.status().is_success(), "{}", response.status());
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        response_instructions(&json["data"])
+    }
+
+    fn signers(instruction: &Instruction) -> Vec<Pubkey> {
+        instruction.accounts.iter().filter(|account| account.is_signer).map(|account| account.pubkey).collect()
+    }
+
+    #[tokio::test]
+    async fn system_transfer_keeps_its_memo() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payload =
+            SendSOLRequest { from: from.to_string(), to: to.to_string(), lamports: 5, memo: Some("invoice 7".into()) };
+        let instructions = compiled(crate::send_sol(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, system_program::ID);
+        assert_eq!(signers(&instructions[0]), [from]);
+        assert!(instructions[0].accounts[1].is_writable);
+        assert_eq!(instructions[1].program_id, spl_memo::ID);
+        assert_eq!(instructions[1].data, b"invoice 7");
+    }
+
+    #[tokio::test]
+    async fn token_transfer_uses_real_account_metas() {
+        let (owner, destination, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let payload = SendTokenRequest {
+            destination: Some(destination.to_string()),
+            mint: Some(mint.to_string()),
+            owner: Some(owner.to_string()),
+            amount: Some(10),
+            memo: None,
+        };
+        let instructions = compiled(crate::send_token(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 1);
+        let accounts = &instructions[0].accounts;
+        assert_eq!(accounts[0].pubkey, get_associated_token_address(&owner, &mint));
+        assert_eq!(accounts[1].pubkey, get_associated_token_address(&destination, &mint));
+        assert!(accounts[0].is_writable && accounts[1].is_writable);
+        assert_eq!(signers(&instructions[0]), [owner]);
+    }
+
+    #[tokio::test]
+    async fn memo_compiles_with_its_signers() {
+        let signer = Pubkey::new_unique();
+        let payload = MemoRequest { memo: "hello".into(), signers: vec![signer.to_string()] };
+        let instructions = compiled(crate::memo::memo(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].program_id, spl_memo::ID);
+        assert_eq!(signers(&instructions[0]), [signer]);
+    }
+
+    #[tokio::test]
+    async fn stake_instructions_compile() {
+        let (stake_account, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payload =
+            StakeDeactivateRequest { stake_account: stake_account.to_string(), authority: authority.to_string() };
+        let instructions = compiled(crate::stake::stake_deactivate(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].program_id, STAKE_PROGRAM_ID);
+        assert_eq!(signers(&instructions[0]), [authority]);
+    }
+
+    #[tokio::test]
+    async fn compute_budget_instructions_compile() {
+        let payload = ComputeBudgetRequest { compute_unit_limit: Some(200_000), compute_unit_price: Some(1_000) };
+        let instructions = compiled(crate::compute_budget::compute_budget(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().all(|instruction| instruction.program_id == compute_budget::ID));
+    }
+
+    #[tokio::test]
+    async fn program_authority_compiles() {
+        let (program, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payload = ProgramSetAuthorityRequest {
+            program: program.to_string(),
+            authority: authority.to_string(),
+            new_authority: Some(Pubkey::new_unique().to_string()),
+            make_immutable: false,
+            checked: false,
+        };
+        let instructions = compiled(crate::loader::set_program_authority(Json(payload)).await).await.unwrap();
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(signers(&instructions[0]), [authority]);
+    }
+
+    #[test]
+    fn unmapped_instruction_fields_are_errors() {
+        let instruction = json!(instruction_to_data(&spl_memo::build_memo(b"hi", &[])));
+        let data = json!({ "instructions": [instruction.clone()], "cleanup_instruction": instruction });
+        let err = response_instructions(&data).unwrap_err();
+        assert!(err.message.contains("cleanup_instruction"), "{}", err.message);
+    }
+
+    #[test]
+    fn account_metas_without_flags_are_errors() {
+        let data = json!({
+            "program_id": spl_token::ID.to_string(),
+            "accounts": [{ "pubkey": Pubkey::new_unique().to_string(), "isSigner": false }],
+            "instruction_data": "",
+        });
+        assert!(response_instructions(&data).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Compute budget instructions, both as a standalone endpoint and as a shared
+//! option on instruction endpoints.
+//!
+//! Any instruction-building endpoint accepts `computeUnitLimit` and
+//! `computeUnitPrice` query parameters; when present, the matching compute
+//! budget instructions are returned under `compute_budget_instructions`, to be
+//! placed at the front of the transaction.
+
+use axum::{
+    body::Body,
+    extract::{Query, Request},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use borsh::BorshDeserialize;
+use serde_json::{json, Value};
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, instruction::Instruction, message::VersionedMessage};
+use solana_sdk_ids::compute_budget;
+
+use crate::types::{ComputeBudgetRequest, InstructionsData};
+use crate::utils::{buffer_response, instruction_to_data, success_response, ApiError, ApiResult};
+
+/// Maximum compute units a single transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Units the runtime grants each instruction when the transaction sets no limit.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Compute unit limit and price a transaction will execute with.
+pub struct ComputeBudgetSettings {
+    pub unit_limit: u32,
+    /// Price per compute unit in micro-lamports.
+    pub unit_price: u64,
+}
+
+impl ComputeBudgetSettings {
+    /// Prioritization fee in lamports, rounded up the same way the runtime does.
+    pub fn priority_fee(&self) -> u64 {
+        let micro_lamports = u128::from(self.unit_price) * u128::from(self.unit_limit);
+        micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT).try_into().unwrap_or(u64::MAX)
+    }
+}
+
+/// Position of the message's `SetComputeUnitPrice` instruction, if it has one.
+pub fn compute_unit_price_instruction(message: &VersionedMessage) -> Option<usize> {
+    let keys = message.static_account_keys();
+    message.instructions().iter().position(|ix| {
+        keys.get(ix.program_id_index as usize) == Some(&compute_budget::ID)
+            && matches!(
+                ComputeBudgetInstruction::try_from_slice(&ix.data),
+                Ok(ComputeBudgetInstruction::SetComputeUnitPrice(_))
+            )
+    })
+}
+
+/// Rewrites the price of the message's existing `SetComputeUnitPrice` instruction.
+/// Returns false when there is none to rewrite.
+pub fn set_message_compute_unit_price(message: &mut VersionedMessage, price: u64) -> bool {
+    let Some(index) = compute_unit_price_instruction(message) else {
+        return false;
+    };
+    let instructions = match message {
+        VersionedMessage::Legacy(message) => &mut message.instructions,
+        VersionedMessage::V0(message) => &mut message.instructions,
+    };
+    instructions[index].data = ComputeBudgetInstruction::set_compute_unit_price(price).data;
+    true
+}
+
+/// Reads the compute budget a message requests, falling back to the runtime defaults.
+pub fn message_compute_budget(message: &VersionedMessage) -> ComputeBudgetSettings {
+    let keys = message.static_account_keys();
+    let mut unit_limit = None;
+    let mut unit_price = 0;
+    let mut other_instructions = 0u32;
+
+    for ix in message.instructions() {
+        if keys.get(ix.program_id_index as usize) != Some(&compute_budget::ID) {
+            other_instructions += 1;
+            continue;
+        }
+        match ComputeBudgetInstruction::try_from_slice(&ix.data) {
+            Ok(ComputeBudgetInstruction::SetComputeUnitLimit(limit)) => unit_limit = Some(limit),
+            Ok(ComputeBudgetInstruction::SetComputeUnitPrice(price)) => unit_price = price,
+            _ => {}
+        }
+    }
+
+    let unit_limit = unit_limit
+        .unwrap_or_else(|| other_instructions.saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT))
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    ComputeBudgetSettings { unit_limit, unit_price }
+}
+
+pub fn compute_budget_instructions(params: &ComputeBudgetRequest) -> Result<Vec<Instruction>, ApiError> {
+    let mut instructions = Vec::new();
+
+    if let Some(limit) = params.compute_unit_limit {
+        if limit == 0 || limit > MAX_COMPUTE_UNIT_LIMIT {
+            return Err(ApiError::bad_request(format!(
+                "computeUnitLimit must be between 1 and {}",
+                MAX_COMPUTE_UNIT_LIMIT
+            )));
+        }
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+
+    if let Some(price) = params.compute_unit_price {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+
+    Ok(instructions)
+}
+
+pub async fn compute_budget(Json(payload): Json<ComputeBudgetRequest>) -> ApiResult {
+    if payload.compute_unit_limit.is_none() && payload.compute_unit_price.is_none() {
+        return Err(ApiError::bad_request(
+            "At least one of computeUnitLimit or computeUnitPrice must be provided",
+        ));
+    }
+
+    let instructions = compute_budget_instructions(&payload)?;
+
+    Ok(success_response(InstructionsData {
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+/// Whether a response `data` object describes one or more instructions.
+pub fn is_instruction_payload(data: &Value) -> bool {
+    data.get("program_id").is_some() || data.get("instructions").is_some()
+}
+
+/// Attaches compute budget instructions to instruction endpoint responses.
+pub async fn compute_budget_option(request: Request, next: Next) -> Response {
+    let params = match Query::<ComputeBudgetRequest>::try_from_uri(request.uri()) {
+        Ok(Query(params)) => params,
+        Err(_) => return ApiError::bad_request("Invalid computeUnitLimit or computeUnitPrice").into_response(),
+    };
+
+    let instructions = match compute_budget_instructions(&params) {
+        Ok(instructions) if !instructions.is_empty() => instructions,
+        Ok(_) => return next.run(request).await,
+        Err(err) => return err.into_response(),
+    };
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (mut parts, bytes) = match buffer_response(response).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let mut json = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(json) if json.get("data").is_some_and(is_instruction_payload) => json,
+        _ => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    json["data"]["compute_budget_instructions"] =
+        json!(instructions.iter().map(instruction_to_data).collect::<Vec<_>>());
+    let body = serde_json::to_vec(&json).unwrap_or_else(|_| bytes.to_vec());
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    Response::from_parts(parts, Body::from(body))
+}
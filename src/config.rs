@@ -0,0 +1,919 @@
+//! Server configuration.
+//!
+//! Settings are layered, later layers winning: built-in defaults, a TOML file
+//! (`--config`, or the path in `SOLANA_API_CONFIG`), environment variables and
+//! command-line flags. A file looks like:
+//!
+//! ```toml
+//! bind = "0.0.0.0:3000"
+//! request_timeout_secs = 150
+//! shutdown_timeout_secs = 30     # drain time after SIGTERM
+//! log_format = "json"            # or "text"
+//! otlp_endpoint = "http://otel-collector:4318"   # export traces over OTLP/HTTP
+//! sentry_dsn = "https://key@o0.ingest.sentry.io/0"  # report panics and 5xx responses
+//! amount_format = "string"       # default rendering of amounts; "number" otherwise
+//! pkcs11_tool = "/usr/bin/pkcs11-tool"
+//!
+//! [rpc]
+//! cluster = "mainnet"          # or url = "https://..."
+//! commitment = "confirmed"
+//! clusters = ["devnet"]        # others requests may select
+//! broadcast_urls = ["https://..."]
+//! timeout_secs = 30
+//!
+//! [rpc.cluster_urls]
+//! mainnet = "https://my-provider.example"
+//!
+//! [rate_limit]                 # token buckets holding ten seconds' worth
+//! requests_per_minute = 600    # per client address; 0 (the default) turns it off
+//! keypair_requests_per_minute = 60   # /keypair routes, vanity grinding included
+//!
+//! [rate_limit.api_keys]        # allowances of their own, by key name; 0 exempts
+//! payments = 3000
+//!
+//! [auth]                       # require an X-API-Key header
+//! required = true              # the default once keys are listed
+//!
+//! [[auth.keys]]
+//! name = "payments"
+//! key_sha256 = "9f86d081..."   # `printf %s KEY | sha256sum`, or key = "KEY"
+//! routes = ["/tx/", "/balance/"]   # path prefixes it may call; all when left out
+//! daily_quota = 100000         # requests per UTC day
+//!
+//! [cursor]                     # pagination cursors
+//! ttl_secs = 86400
+//! secret_env = "CURSOR_SECRET" # the default; cursors survive restarts when it is set
+//!
+//! [jito]
+//! block_engine_url = "https://mainnet.block-engine.jito.wtf"
+//! auth_uuid_env = "JITO_AUTH_UUID"   # the default
+//!
+//! [fee_payers]                 # name = "env:VAR", "file:/path" or "vault:path#field"
+//! ops = "vault:secret/data/fee-payers#ops"
+//!
+//! [vault]                      # where vault: fee payers are read from
+//! addr = "https://vault.example:8200"
+//! namespace = "solana"
+//! token_env = "VAULT_TOKEN"    # the default
+//!
+//! [keystore]                   # keep keystore keys across restarts
+//! path = "/var/lib/solana-api/keystore.json"
+//! passphrase_env = "KEYSTORE_PASSPHRASE"   # the default; the file is encrypted under it
+//!
+//! [pkcs11.hsm]                 # PKCS#11 tokens keystore keys may name as backend "hsm"
+//! module = "/usr/lib/softhsm/libsofthsm2.so"
+//! slot = 0
+//! pin_env = "HSM_PIN"          # variable holding the user PIN
+//!
+//! [tls]                        # serve HTTPS instead of HTTP
+//! cert = "/etc/solana-api/cert.pem"
+//! key = "/etc/solana-api/key.pem"
+//!
+//! [features]
+//! airdrop = false
+//! off_chain_metadata = false   # never fetch metadata uris
+//! raw_secrets = false
+//! ```
+//!
+//! Every setting but the API keys and PKCS#11 tokens keeps its environment variable
+//! (`AUTH_REQUIRED`, `BIND_ADDR`, `REQUEST_TIMEOUT_SECS`, `SHUTDOWN_TIMEOUT_SECS`, `LOG_FORMAT`,
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`),
+//! `SENTRY_DSN`, `AMOUNT_FORMAT`, `PKCS11_TOOL`, `SOLANA_RPC_URL`, `SOLANA_CLUSTER`,
+//! `SOLANA_COMMITMENT`, `SOLANA_CLUSTERS`, `SOLANA_RPC_URL_<CLUSTER>`, `SOLANA_BROADCAST_RPC_URLS`,
+//! `SOLANA_RPC_TIMEOUT_SECS`, `CURSOR_TTL_SECS`, `JITO_BLOCK_ENGINE_URL`, `SOLANA_FEE_PAYERS`
+//! (comma-separated `name=source`), `VAULT_ADDR`, `VAULT_NAMESPACE`, `KEYSTORE_PATH`,
+//! `RATE_LIMIT_PER_MINUTE`, `RATE_LIMIT_KEYPAIR_PER_MINUTE`, `TLS_CERT_PATH`, `TLS_KEY_PATH`,
+//! `SOLANA_ALLOW_RAW_SECRETS` and `SOLANA_DISABLED_FEATURES`). Secrets are only
+//! ever read from the variables the `*_env` settings name; run with `--help`
+//! for the flags.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use clap::Parser;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use std::{collections::HashMap, net::SocketAddr, num::NonZeroU32, path::PathBuf, sync::Arc, time::Duration};
+
+use crate::amount::AmountFormat;
+use crate::jito::DEFAULT_BLOCK_ENGINE_URL;
+use crate::logging::LogFormat;
+use crate::rpc::{canonical_cluster, cluster_url, parse_commitment, DEFAULT_RPC_URL};
+use crate::types::Commitment;
+use crate::utils::ApiError;
+
+const DEFAULT_BIND: &str = "127.0.0.1:3000";
+const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_KEYPAIR_REQUESTS_PER_MINUTE: u64 = 60;
+const CONFIG_FILE_VAR: &str = "SOLANA_API_CONFIG";
+const DEFAULT_KEYSTORE_PASSPHRASE_VAR: &str = "KEYSTORE_PASSPHRASE";
+const DEFAULT_CURSOR_SECRET_VAR: &str = "CURSOR_SECRET";
+const DEFAULT_CURSOR_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_JITO_AUTH_UUID_VAR: &str = "JITO_AUTH_UUID";
+const DEFAULT_VAULT_TOKEN_VAR: &str = "VAULT_TOKEN";
+const DEFAULT_PKCS11_TOOL: &str = "pkcs11-tool";
+
+/// Features that can be switched off, with the routes each one serves.
+/// `raw_secrets` and `off_chain_metadata` have no routes of their own:
+/// turning them off limits signing endpoints to keystore keys, and stops
+/// metadata lookups from fetching the documents their uris point at.
+const FEATURES: [(&str, &[&str]); 9] = [
+    ("airdrop", &["/airdrop"]),
+    ("jito", &["/tx/bundle", "/jito/"]),
+    ("keystore", &["/keys"]),
+    ("nft", &["/nft/", "/candy-machine/", "/cnft/"]),
+    ("off_chain_metadata", &[]),
+    ("programs", &["/program/"]),
+    ("raw_secrets", &[]),
+    ("siws", &["/siws/"]),
+    ("vanity", &["/keypair/vanity"]),
+];
+
+#[derive(Parser)]
+#[command(version, about = "HTTP API for Solana keys, messages and transactions")]
+struct Cli {
+    /// TOML configuration file.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Address to listen on.
+    #[arg(long, value_name = "ADDR")]
+    bind: Option<String>,
+    /// RPC endpoint; takes precedence over --cluster.
+    #[arg(long, value_name = "URL")]
+    rpc_url: Option<String>,
+    /// devnet, testnet, mainnet or localnet.
+    #[arg(long)]
+    cluster: Option<String>,
+    /// Default commitment: processed, confirmed or finalized.
+    #[arg(long)]
+    commitment: Option<String>,
+    /// Other clusters requests may select, comma-separated.
+    #[arg(long, value_delimiter = ',')]
+    clusters: Option<Vec<String>>,
+    /// Extra endpoints submissions are broadcast to, comma-separated.
+    #[arg(long, value_delimiter = ',', value_name = "URLS")]
+    broadcast_rpc_urls: Option<Vec<String>>,
+    /// Timeout for each RPC call.
+    #[arg(long, value_name = "SECS")]
+    rpc_timeout_secs: Option<u64>,
+    /// Timeout for a whole request; 0 disables it.
+    #[arg(long, value_name = "SECS")]
+    request_timeout_secs: Option<u64>,
+    /// How long shutdown waits for in-flight requests and tracked submissions.
+    #[arg(long, value_name = "SECS")]
+    shutdown_timeout_secs: Option<u64>,
+    /// Log output: text or json.
+    #[arg(long, value_name = "FORMAT")]
+    log_format: Option<String>,
+    /// OTLP/HTTP collector to export traces to, e.g. http://localhost:4318.
+    #[arg(long, value_name = "URL")]
+    otlp_endpoint: Option<String>,
+    /// Sentry DSN to report panics and server errors to.
+    #[arg(long, value_name = "DSN")]
+    sentry_dsn: Option<String>,
+    /// How amounts are rendered unless a request asks otherwise: number or string.
+    #[arg(long, value_name = "FORMAT")]
+    amount_format: Option<String>,
+    /// How long pagination cursors stay valid.
+    #[arg(long, value_name = "SECS")]
+    cursor_ttl_secs: Option<u64>,
+    /// Jito block engine bundles are sent to.
+    #[arg(long, value_name = "URL")]
+    jito_block_engine_url: Option<String>,
+    /// Server-held fee payer as name=source (env:VAR, file:PATH or vault:PATH#FIELD); repeatable.
+    #[arg(long, value_name = "NAME=SOURCE")]
+    fee_payer: Vec<String>,
+    /// Vault server vault: fee payers are read from.
+    #[arg(long, value_name = "URL")]
+    vault_addr: Option<String>,
+    /// Vault namespace, for Vault Enterprise.
+    #[arg(long, value_name = "NAMESPACE")]
+    vault_namespace: Option<String>,
+    /// pkcs11-tool binary PKCS#11 keys are signed with.
+    #[arg(long, value_name = "PATH")]
+    pkcs11_tool: Option<PathBuf>,
+    /// File the keystore is kept in, encrypted under $KEYSTORE_PASSPHRASE.
+    #[arg(long, value_name = "PATH")]
+    keystore_path: Option<PathBuf>,
+    /// Requests each client address may make per minute; 0 turns limiting off.
+    #[arg(long, value_name = "N")]
+    rate_limit_per_minute: Option<u64>,
+    /// Requests per minute to /keypair routes, per client; 0 turns it off.
+    #[arg(long, value_name = "N")]
+    rate_limit_keypair_per_minute: Option<u64>,
+    /// PEM certificate chain to serve HTTPS with; needs --tls-key.
+    #[arg(long, value_name = "PATH")]
+    tls_cert: Option<PathBuf>,
+    /// PEM private key for --tls-cert.
+    #[arg(long, value_name = "PATH")]
+    tls_key: Option<PathBuf>,
+    /// Turn a feature off; repeatable.
+    #[arg(long, value_name = "FEATURE")]
+    disable: Vec<String>,
+    /// Turn a feature on; repeatable.
+    #[arg(long, value_name = "FEATURE")]
+    enable: Vec<String>,
+}
+
+/// One layer of settings; anything left unset falls through to the layer below.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    bind: Option<String>,
+    request_timeout_secs: Option<u64>,
+    shutdown_timeout_secs: Option<u64>,
+    log_format: Option<String>,
+    /// Collector base URL; traces go to its `/v1/traces`.
+    otlp_endpoint: Option<String>,
+    /// Full traces URL, which only the environment sets.
+    #[serde(skip)]
+    otlp_traces_endpoint: Option<String>,
+    sentry_dsn: Option<String>,
+    amount_format: Option<String>,
+    pkcs11_tool: Option<PathBuf>,
+    rpc: RpcSettings,
+    rate_limit: RateLimitSettings,
+    auth: AuthSettings,
+    cursor: CursorSettings,
+    jito: JitoSettings,
+    /// Fee payer sources by name.
+    fee_payers: HashMap<String, String>,
+    vault: VaultSettings,
+    keystore: KeystoreSettings,
+    pkcs11: HashMap<String, Pkcs11Settings>,
+    tls: TlsSettings,
+    features: HashMap<String, bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RpcSettings {
+    url: Option<String>,
+    cluster: Option<String>,
+    commitment: Option<String>,
+    clusters: Option<Vec<String>>,
+    cluster_urls: HashMap<String, String>,
+    broadcast_urls: Option<Vec<String>>,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RateLimitSettings {
+    requests_per_minute: Option<u64>,
+    keypair_requests_per_minute: Option<u64>,
+    api_keys: HashMap<String, u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AuthSettings {
+    required: Option<bool>,
+    keys: Vec<ApiKeySettings>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApiKeySettings {
+    name: String,
+    key: Option<String>,
+    /// Hex SHA-256 of the key, so the file need not hold the key itself.
+    key_sha256: Option<String>,
+    routes: Option<Vec<String>>,
+    daily_quota: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CursorSettings {
+    ttl_secs: Option<u64>,
+    /// Variable holding the key cursors are signed with.
+    secret_env: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct JitoSettings {
+    block_engine_url: Option<String>,
+    /// Variable holding the block engine auth UUID.
+    auth_uuid_env: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct VaultSettings {
+    addr: Option<String>,
+    namespace: Option<String>,
+    /// Variable holding the Vault token.
+    token_env: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct KeystoreSettings {
+    path: Option<PathBuf>,
+    /// Variable holding the passphrase; the passphrase itself never sits in a file.
+    passphrase_env: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pkcs11Settings {
+    module: PathBuf,
+    slot: Option<u64>,
+    pin_env: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct TlsSettings {
+    cert: Option<PathBuf>,
+    key: Option<PathBuf>,
+}
+
+impl Settings {
+    /// `self` with everything `over` sets replaced.
+    fn overlay(mut self, over: Settings) -> Settings {
+        // The default endpoint is one choice, so a layer naming either a URL or
+        // a cluster replaces both.
+        if over.rpc.url.is_some() || over.rpc.cluster.is_some() {
+            self.rpc.url = over.rpc.url;
+            self.rpc.cluster = over.rpc.cluster;
+        }
+        self.bind = over.bind.or(self.bind);
+        self.request_timeout_secs = over.request_timeout_secs.or(self.request_timeout_secs);
+        self.shutdown_timeout_secs = over.shutdown_timeout_secs.or(self.shutdown_timeout_secs);
+        self.log_format = over.log_format.or(self.log_format);
+        if over.otlp_endpoint.is_some() || over.otlp_traces_endpoint.is_some() {
+            self.otlp_endpoint = over.otlp_endpoint;
+            self.otlp_traces_endpoint = over.otlp_traces_endpoint;
+        }
+        self.sentry_dsn = over.sentry_dsn.or(self.sentry_dsn);
+        self.amount_format = over.amount_format.or(self.amount_format);
+        self.pkcs11_tool = over.pkcs11_tool.or(self.pkcs11_tool);
+        self.rpc.commitment = over.rpc.commitment.or(self.rpc.commitment);
+        self.rpc.clusters = over.rpc.clusters.or(self.rpc.clusters);
+        self.rpc.broadcast_urls = over.rpc.broadcast_urls.or(self.rpc.broadcast_urls);
+        self.rpc.timeout_secs = over.rpc.timeout_secs.or(self.rpc.timeout_secs);
+        self.rate_limit.requests_per_minute =
+            over.rate_limit.requests_per_minute.or(self.rate_limit.requests_per_minute);
+        self.rate_limit.keypair_requests_per_minute =
+            over.rate_limit.keypair_requests_per_minute.or(self.rate_limit.keypair_requests_per_minute);
+        self.rate_limit.api_keys.extend(over.rate_limit.api_keys);
+        self.auth.required = over.auth.required.or(self.auth.required);
+        self.auth.keys.extend(over.auth.keys);
+        self.cursor.ttl_secs = over.cursor.ttl_secs.or(self.cursor.ttl_secs);
+        self.cursor.secret_env = over.cursor.secret_env.or(self.cursor.secret_env);
+        self.jito.block_engine_url = over.jito.block_engine_url.or(self.jito.block_engine_url);
+        self.jito.auth_uuid_env = over.jito.auth_uuid_env.or(self.jito.auth_uuid_env);
+        self.fee_payers.extend(over.fee_payers);
+        self.vault.addr = over.vault.addr.or(self.vault.addr);
+        self.vault.namespace = over.vault.namespace.or(self.vault.namespace);
+        self.vault.token_env = over.vault.token_env.or(self.vault.token_env);
+        self.keystore.path = over.keystore.path.or(self.keystore.path);
+        self.keystore.passphrase_env = over.keystore.passphrase_env.or(self.keystore.passphrase_env);
+        self.pkcs11.extend(over.pkcs11);
+        self.tls.cert = over.tls.cert.or(self.tls.cert);
+        self.tls.key = over.tls.key.or(self.tls.key);
+        self.rpc.cluster_urls.extend(over.rpc.cluster_urls);
+        self.features.extend(over.features);
+        self
+    }
+
+    /// Fee payers given as `name=source` entries.
+    fn fee_payer_entries<'a>(
+        entries: impl Iterator<Item = &'a str>,
+        origin: &str,
+    ) -> Result<HashMap<String, String>, String> {
+        let mut fee_payers = HashMap::new();
+        for entry in entries.map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (name, source) = entry
+                .split_once('=')
+                .map(|(name, source)| (name.trim(), source.trim()))
+                .filter(|(name, _)| !name.is_empty())
+                .ok_or_else(|| format!("Invalid {origin} entry {entry:?}; use name=source"))?;
+            if fee_payers.insert(name.to_string(), source.to_string()).is_some() {
+                return Err(format!("Fee payer {name} is configured twice"));
+            }
+        }
+        Ok(fee_payers)
+    }
+
+    fn from_file(path: &PathBuf) -> Result<Settings, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid config file {}: {e}", path.display()))
+    }
+
+    fn from_env() -> Result<Settings, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let list = |name: &str| var(name).map(|value| value.split(',').map(str::to_string).collect());
+        let number = |name: &str| {
+            var(name).map(|value| value.trim().parse().map_err(|_| format!("Invalid {name} {value:?}"))).transpose()
+        };
+
+        let mut features = HashMap::new();
+        for feature in var("SOLANA_DISABLED_FEATURES").unwrap_or_default().split(',') {
+            if !feature.trim().is_empty() {
+                features.insert(feature.trim().to_string(), false);
+            }
+        }
+        match var("SOLANA_ALLOW_RAW_SECRETS").as_deref() {
+            None => {}
+            Some("true") | Some("1") => {
+                features.insert("raw_secrets".to_string(), true);
+            }
+            Some("false") | Some("0") => {
+                features.insert("raw_secrets".to_string(), false);
+            }
+            Some(other) => return Err(format!("Invalid SOLANA_ALLOW_RAW_SECRETS {other:?}; use true or false")),
+        }
+
+        let mut cluster_urls = HashMap::new();
+        for cluster in ["devnet", "testnet", "mainnet", "localnet"] {
+            if let Some(url) = var(&format!("SOLANA_RPC_URL_{}", cluster.to_ascii_uppercase())) {
+                cluster_urls.insert(cluster.to_string(), url);
+            }
+        }
+
+        let required = match var("AUTH_REQUIRED").as_deref() {
+            None => None,
+            Some("true") | Some("1") => Some(true),
+            Some("false") | Some("0") => Some(false),
+            Some(other) => return Err(format!("Invalid AUTH_REQUIRED {other:?}; use true or false")),
+        };
+
+        let url = var("SOLANA_RPC_URL");
+        Ok(Settings {
+            bind: var("BIND_ADDR"),
+            request_timeout_secs: number("REQUEST_TIMEOUT_SECS")?,
+            shutdown_timeout_secs: number("SHUTDOWN_TIMEOUT_SECS")?,
+            log_format: var("LOG_FORMAT"),
+            otlp_endpoint: var("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            otlp_traces_endpoint: var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"),
+            sentry_dsn: var("SENTRY_DSN"),
+            amount_format: var("AMOUNT_FORMAT"),
+            pkcs11_tool: var("PKCS11_TOOL").map(PathBuf::from),
+            rpc: RpcSettings {
+                // A URL wins over a cluster name set alongside it.
+                cluster: if url.is_some() { None } else { var("SOLANA_CLUSTER") },
+                url,
+                commitment: var("SOLANA_COMMITMENT"),
+                clusters: list("SOLANA_CLUSTERS"),
+                cluster_urls,
+                broadcast_urls: list("SOLANA_BROADCAST_RPC_URLS"),
+                timeout_secs: number("SOLANA_RPC_TIMEOUT_SECS")?,
+            },
+            rate_limit: RateLimitSettings {
+                requests_per_minute: number("RATE_LIMIT_PER_MINUTE")?,
+                keypair_requests_per_minute: number("RATE_LIMIT_KEYPAIR_PER_MINUTE")?,
+                api_keys: HashMap::new(),
+            },
+            auth: AuthSettings { required, keys: Vec::new() },
+            cursor: CursorSettings { ttl_secs: number("CURSOR_TTL_SECS")?, secret_env: None },
+            jito: JitoSettings { block_engine_url: var("JITO_BLOCK_ENGINE_URL"), auth_uuid_env: None },
+            fee_payers: Settings::fee_payer_entries(
+                var("SOLANA_FEE_PAYERS").unwrap_or_default().split(','),
+                "SOLANA_FEE_PAYERS",
+            )?,
+            vault: VaultSettings { addr: var("VAULT_ADDR"), namespace: var("VAULT_NAMESPACE"), token_env: None },
+            keystore: KeystoreSettings { path: var("KEYSTORE_PATH").map(PathBuf::from), passphrase_env: None },
+            pkcs11: HashMap::new(),
+            tls: TlsSettings {
+                cert: var("TLS_CERT_PATH").map(PathBuf::from),
+                key: var("TLS_KEY_PATH").map(PathBuf::from),
+            },
+            features,
+        })
+    }
+
+    fn from_cli(cli: Cli) -> Result<Settings, String> {
+        let mut features = HashMap::new();
+        features.extend(cli.enable.into_iter().map(|feature| (feature, true)));
+        features.extend(cli.disable.into_iter().map(|feature| (feature, false)));
+        Ok(Settings {
+            bind: cli.bind,
+            request_timeout_secs: cli.request_timeout_secs,
+            shutdown_timeout_secs: cli.shutdown_timeout_secs,
+            log_format: cli.log_format,
+            otlp_endpoint: cli.otlp_endpoint,
+            otlp_traces_endpoint: None,
+            sentry_dsn: cli.sentry_dsn,
+            amount_format: cli.amount_format,
+            pkcs11_tool: cli.pkcs11_tool,
+            rpc: RpcSettings {
+                cluster: if cli.rpc_url.is_some() { None } else { cli.cluster },
+                url: cli.rpc_url,
+                commitment: cli.commitment,
+                clusters: cli.clusters,
+                cluster_urls: HashMap::new(),
+                broadcast_urls: cli.broadcast_rpc_urls,
+                timeout_secs: cli.rpc_timeout_secs,
+            },
+            rate_limit: RateLimitSettings {
+                requests_per_minute: cli.rate_limit_per_minute,
+                keypair_requests_per_minute: cli.rate_limit_keypair_per_minute,
+                api_keys: HashMap::new(),
+            },
+            auth: AuthSettings::default(),
+            cursor: CursorSettings { ttl_secs: cli.cursor_ttl_secs, secret_env: None },
+            jito: JitoSettings { block_engine_url: cli.jito_block_engine_url, auth_uuid_env: None },
+            fee_payers: Settings::fee_payer_entries(cli.fee_payer.iter().map(String::as_str), "--fee-payer")?,
+            vault: VaultSettings { addr: cli.vault_addr, namespace: cli.vault_namespace, token_env: None },
+            keystore: KeystoreSettings { path: cli.keystore_path, passphrase_env: None },
+            pkcs11: HashMap::new(),
+            tls: TlsSettings { cert: cli.tls_cert, key: cli.tls_key },
+            features,
+        })
+    }
+}
+
+/// Requests per minute allowed to each client, `None` meaning unlimited.
+pub struct RateLimitConfig {
+    pub requests_per_minute: Option<NonZeroU32>,
+    /// Limit on /keypair routes, on top of the general one.
+    pub keypair_requests_per_minute: Option<NonZeroU32>,
+    /// Allowances of their own, by API key name.
+    pub api_keys: HashMap<String, Option<NonZeroU32>>,
+}
+
+#[derive(Clone)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key_sha256: [u8; 32],
+    /// Path prefixes the key may call; any path when empty.
+    pub routes: Vec<String>,
+    /// Requests allowed per UTC day.
+    pub daily_quota: Option<u64>,
+}
+
+pub struct AuthConfig {
+    /// Whether requests without a key are turned away.
+    pub required: bool,
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+/// How pagination cursors are signed, and how long they stay valid.
+#[derive(Clone)]
+pub struct CursorConfig {
+    /// From the configured variable, so cursors survive restarts and work
+    /// across replicas; otherwise random per process.
+    pub secret: Vec<u8>,
+    pub ttl: Duration,
+}
+
+#[derive(Clone)]
+pub struct JitoConfig {
+    pub block_engine_url: String,
+    pub auth_uuid: Option<String>,
+}
+
+/// The Vault server `vault:` fee payer sources are read from.
+pub struct VaultConfig {
+    pub addr: Option<String>,
+    pub namespace: Option<String>,
+    pub token: Option<String>,
+}
+
+/// Where the keystore is kept, and the passphrase its file is encrypted under.
+pub struct KeystoreFileConfig {
+    pub path: PathBuf,
+    pub passphrase: String,
+}
+
+/// A PKCS#11 token keystore keys may use, named in requests by its key in
+/// `Config::pkcs11`.
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    /// `pkcs11-tool` binary to run.
+    pub tool: PathBuf,
+    /// PKCS#11 module library `pkcs11-tool` loads.
+    pub module: PathBuf,
+    pub slot: Option<u64>,
+    /// Environment variable holding the user PIN.
+    pub pin_env: Option<String>,
+}
+
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// The effective configuration, validated.
+pub struct Config {
+    pub bind: SocketAddr,
+    /// Default RPC endpoint.
+    pub rpc_url: String,
+    /// Name of the default cluster, when it was chosen by name rather than URL.
+    pub cluster: Option<&'static str>,
+    pub commitment: Commitment,
+    /// Other clusters requests may select, with their endpoints.
+    pub clusters: Vec<(&'static str, String)>,
+    pub broadcast_rpc_urls: Vec<String>,
+    pub rpc_timeout: Duration,
+    pub request_timeout: Option<Duration>,
+    pub shutdown_timeout: Duration,
+    pub log_format: LogFormat,
+    /// OTLP/HTTP URL traces are exported to; no export without one.
+    pub otlp_traces_endpoint: Option<String>,
+    /// Where panics and 5xx responses are reported; not reported without one.
+    pub sentry_dsn: Option<String>,
+    pub rate_limit: RateLimitConfig,
+    pub auth: AuthConfig,
+    /// Whether amounts render as strings unless a request asks otherwise.
+    pub amount_format: AmountFormat,
+    pub cursor: CursorConfig,
+    pub jito: JitoConfig,
+    /// Fee payer sources by name, loaded by `fee_payer::load_fee_payers`.
+    pub fee_payers: HashMap<String, String>,
+    pub vault: VaultConfig,
+    /// Keystore file; the keystore only lives in memory without one.
+    pub keystore_file: Option<KeystoreFileConfig>,
+    /// PKCS#11 tokens by backend name.
+    pub pkcs11: HashMap<String, Pkcs11Config>,
+    /// Certificate and key to serve HTTPS with; plain HTTP without them.
+    pub tls: Option<TlsConfig>,
+    /// Whether signing endpoints accept secret keys in requests, or only keystore ids.
+    pub allow_raw_secrets: bool,
+    disabled_features: Vec<&'static str>,
+}
+
+impl Config {
+    /// Reads the command line, config file and environment. Exits with usage
+    /// on invalid flags or `--help`.
+    pub fn load() -> Result<Config, String> {
+        let cli = Cli::parse();
+        let file = match cli.config.clone().or_else(|| std::env::var_os(CONFIG_FILE_VAR).map(PathBuf::from)) {
+            Some(path) => Settings::from_file(&path)?,
+            None => Settings::default(),
+        };
+        Config::resolve(file.overlay(Settings::from_env()?).overlay(Settings::from_cli(cli)?))
+    }
+
+    fn resolve(settings: Settings) -> Result<Config, String> {
+        let Settings {
+            bind,
+            request_timeout_secs,
+            shutdown_timeout_secs,
+            log_format,
+            otlp_endpoint,
+            otlp_traces_endpoint,
+            sentry_dsn,
+            amount_format,
+            pkcs11_tool,
+            rpc,
+            rate_limit,
+            auth,
+            cursor,
+            jito,
+            fee_payers,
+            vault,
+            keystore,
+            pkcs11,
+            tls,
+            features,
+        } = settings;
+        // Secrets only ever come from the environment, never from the file or flags.
+        let secret_var = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+
+        let bind = bind.as_deref().unwrap_or(DEFAULT_BIND);
+        let bind = bind.parse().map_err(|_| format!("Invalid bind address {bind:?}"))?;
+
+        let cluster_rpc_url = |cluster: &'static str| {
+            rpc.cluster_urls
+                .get(cluster)
+                .cloned()
+                .or_else(|| cluster_url(cluster).map(str::to_string))
+                .unwrap_or_default()
+        };
+        for name in rpc.cluster_urls.keys() {
+            canonical_cluster(name).ok_or_else(|| format!("Unknown cluster {name:?} in cluster_urls"))?;
+        }
+
+        let (rpc_url, cluster) = match (rpc.url, rpc.cluster) {
+            (Some(url), _) => (url, None),
+            (None, Some(name)) => {
+                let cluster = canonical_cluster(&name)
+                    .ok_or_else(|| format!("Unknown cluster {name:?}; use devnet, testnet, mainnet or localnet"))?;
+                (cluster_rpc_url(cluster), Some(cluster))
+            }
+            (None, None) => (DEFAULT_RPC_URL.to_string(), Some("devnet")),
+        };
+
+        let commitment = match rpc.commitment {
+            Some(commitment) => parse_commitment(&commitment).ok_or_else(|| {
+                format!("Unknown commitment {commitment:?}; use processed, confirmed or finalized")
+            })?,
+            None => Commitment::Finalized,
+        };
+
+        let mut clusters: Vec<(&'static str, String)> = Vec::new();
+        for name in rpc.clusters.unwrap_or_default() {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let name = canonical_cluster(name).ok_or_else(|| {
+                format!("Unknown cluster {name:?} in clusters; use devnet, testnet, mainnet or localnet")
+            })?;
+            if Some(name) != cluster && !clusters.iter().any(|(known, _)| *known == name) {
+                clusters.push((name, cluster_rpc_url(name)));
+            }
+        }
+
+        let mut broadcast_rpc_urls: Vec<String> = Vec::new();
+        for candidate in rpc.broadcast_urls.unwrap_or_default() {
+            let candidate = candidate.trim();
+            let known = candidate == rpc_url || broadcast_rpc_urls.iter().any(|known| known == candidate);
+            if !candidate.is_empty() && !known {
+                broadcast_rpc_urls.push(candidate.to_string());
+            }
+        }
+
+        let log_format = match log_format {
+            Some(format) => {
+                LogFormat::parse(&format).ok_or_else(|| format!("Unknown log format {format:?}; use text or json"))?
+            }
+            None => LogFormat::Text,
+        };
+
+        let amount_format = match amount_format {
+            Some(format) => AmountFormat::parse(&format)
+                .ok_or_else(|| format!("Unknown amount format {format:?}; use number or string"))?,
+            None => AmountFormat::Number,
+        };
+
+        let cursor = CursorConfig {
+            secret: secret_var(cursor.secret_env.as_deref().unwrap_or(DEFAULT_CURSOR_SECRET_VAR))
+                .map_or_else(|| rand::random::<[u8; 32]>().to_vec(), String::into_bytes),
+            ttl: cursor.ttl_secs.map_or(DEFAULT_CURSOR_TTL, Duration::from_secs),
+        };
+        let jito = JitoConfig {
+            block_engine_url: jito.block_engine_url.unwrap_or_else(|| DEFAULT_BLOCK_ENGINE_URL.to_string()),
+            auth_uuid: secret_var(jito.auth_uuid_env.as_deref().unwrap_or(DEFAULT_JITO_AUTH_UUID_VAR)),
+        };
+        let vault = VaultConfig {
+            addr: vault.addr,
+            namespace: vault.namespace,
+            token: secret_var(vault.token_env.as_deref().unwrap_or(DEFAULT_VAULT_TOKEN_VAR)),
+        };
+
+        let tls = match (tls.cert, tls.key) {
+            (Some(cert), Some(key)) => Some(TlsConfig { cert, key }),
+            (None, None) => None,
+            _ => return Err("TLS needs both a certificate and a key".to_string()),
+        };
+
+        // Zero turns a limit off.
+        let per_minute = |limit: u64| {
+            u32::try_from(limit).map(NonZeroU32::new).map_err(|_| format!("Rate limit {limit} per minute is too high"))
+        };
+        let mut keys: Vec<ApiKeyConfig> = Vec::new();
+        for key in auth.keys {
+            let key_sha256 = match (key.key, key.key_sha256) {
+                (Some(plain), None) => Sha256::digest(plain.as_bytes()).into(),
+                (None, Some(hash)) => hex::decode(hash.trim())
+                    .ok()
+                    .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+                    .ok_or_else(|| format!("Invalid key_sha256 for API key {:?}; use 64 hex digits", key.name))?,
+                _ => return Err(format!("API key {:?} needs exactly one of key and key_sha256", key.name)),
+            };
+            if keys.iter().any(|known| known.name == key.name || known.key_sha256 == key_sha256) {
+                return Err(format!("API key {:?} repeats another key's name or key", key.name));
+            }
+            let routes = key.routes.unwrap_or_default();
+            if let Some(route) = routes.iter().find(|route| !route.starts_with('/')) {
+                return Err(format!("Invalid route {route:?} for API key {:?}; routes start with /", key.name));
+            }
+            keys.push(ApiKeyConfig { name: key.name, key_sha256, routes, daily_quota: key.daily_quota });
+        }
+        let required = auth.required.unwrap_or(!keys.is_empty());
+        if required && keys.is_empty() {
+            return Err("Requiring API keys needs at least one key under auth.keys".to_string());
+        }
+        let auth = AuthConfig { required, keys };
+
+        let mut api_keys = HashMap::new();
+        for (name, limit) in rate_limit.api_keys {
+            if !auth.keys.iter().any(|key| key.name == name) {
+                return Err(format!("Unknown API key {name:?} in rate_limit.api_keys"));
+            }
+            api_keys.insert(name, per_minute(limit)?);
+        }
+        let rate_limit = RateLimitConfig {
+            requests_per_minute: per_minute(rate_limit.requests_per_minute.unwrap_or_default())?,
+            keypair_requests_per_minute: per_minute(
+                rate_limit.keypair_requests_per_minute.unwrap_or(DEFAULT_KEYPAIR_REQUESTS_PER_MINUTE),
+            )?,
+            api_keys,
+        };
+
+        let keystore_file = match keystore.path {
+            Some(path) => {
+                let var = keystore.passphrase_env.as_deref().unwrap_or(DEFAULT_KEYSTORE_PASSPHRASE_VAR);
+                let passphrase =
+                    secret_var(var).ok_or_else(|| format!("The keystore file needs a passphrase in {var}"))?;
+                Some(KeystoreFileConfig { path, passphrase })
+            }
+            None => None,
+        };
+
+        let pkcs11_tool = pkcs11_tool.unwrap_or_else(|| PathBuf::from(DEFAULT_PKCS11_TOOL));
+        let mut pkcs11_tokens = HashMap::new();
+        for (name, token) in pkcs11 {
+            if !token.module.is_absolute() {
+                return Err(format!("PKCS#11 module for {name:?} needs an absolute path"));
+            }
+            if token.pin_env.as_deref().is_some_and(|var| var.is_empty() || var.contains('=')) {
+                return Err(format!("Invalid pin_env for PKCS#11 token {name:?}"));
+            }
+            let Pkcs11Settings { module, slot, pin_env } = token;
+            pkcs11_tokens.insert(name, Pkcs11Config { tool: pkcs11_tool.clone(), module, slot, pin_env });
+        }
+
+        let mut disabled_features = Vec::new();
+        for (name, enabled) in &features {
+            let feature = FEATURES
+                .iter()
+                .map(|(feature, _)| *feature)
+                .find(|feature| feature == name)
+                .ok_or_else(|| {
+                    let known: Vec<&str> = FEATURES.iter().map(|(feature, _)| *feature).collect();
+                    format!("Unknown feature {name:?}; use {}", known.join(", "))
+                })?;
+            if !enabled {
+                disabled_features.push(feature);
+            }
+        }
+
+        Ok(Config {
+            bind,
+            rpc_url,
+            cluster,
+            commitment,
+            clusters,
+            broadcast_rpc_urls,
+            rpc_timeout: rpc.timeout_secs.map_or(DEFAULT_RPC_TIMEOUT, Duration::from_secs),
+            request_timeout: request_timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs),
+            log_format,
+            otlp_traces_endpoint: otlp_traces_endpoint
+                .or_else(|| otlp_endpoint.map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))),
+            sentry_dsn,
+            rate_limit,
+            auth,
+            amount_format,
+            cursor,
+            jito,
+            fee_payers,
+            vault,
+            keystore_file,
+            pkcs11: pkcs11_tokens,
+            shutdown_timeout: shutdown_timeout_secs.map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_secs),
+            tls,
+            allow_raw_secrets: !disabled_features.contains(&"raw_secrets"),
+            disabled_features,
+        })
+    }
+
+    pub fn feature_enabled(&self, feature: &str) -> bool {
+        !self.disabled_features.contains(&feature)
+    }
+
+    /// The disabled feature serving `path`, if any.
+    fn disabled_feature(&self, path: &str) -> Option<&'static str> {
+        FEATURES
+            .iter()
+            .filter(|(feature, _)| self.disabled_features.contains(feature))
+            .find(|(_, prefixes)| prefixes.iter().any(|prefix| path.starts_with(prefix)))
+            .map(|(feature, _)| *feature)
+    }
+}
+
+/// Answers requests for disabled features with a 404.
+pub async fn feature_gate(State(config): State<Arc<Config>>, request: Request, next: Next) -> Response {
+    match config.disabled_feature(request.uri().path()) {
+        Some(feature) => {
+            ApiError::new(StatusCode::NOT_FOUND, format!("The {feature} feature is disabled on this server"))
+                .with_code("FEATURE_DISABLED")
+                .into_response()
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Fails requests that run longer than the configured request timeout.
+pub async fn request_timeout(State(config): State<Arc<Config>>, request: Request, next: Next) -> Response {
+    let Some(timeout) = config.request_timeout else {
+        return next.run(request).await;
+    };
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ApiError::new(StatusCode::GATEWAY_TIMEOUT, "Request timed out")
+            .with_code("REQUEST_TIMEOUT")
+            .into_response(),
+    }
+}
@@ -0,0 +1,114 @@
+//! Opaque, HMAC-signed pagination cursors for list endpoints.
+//!
+//! A cursor encodes `(slot, signature, direction)` plus the time it was
+//! issued. Clients treat it as an opaque string; any tampering fails the MAC
+//! check and cursors older than the configured TTL are rejected as stale.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::CursorConfig;
+use crate::utils::ApiError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const MAC_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Before,
+    After,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub slot: u64,
+    pub signature: String,
+    pub direction: Direction,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CursorError {
+    Malformed,
+    InvalidSignature,
+    Stale,
+}
+
+impl From<CursorError> for ApiError {
+    fn from(err: CursorError) -> Self {
+        match err {
+            CursorError::Malformed => {
+                ApiError::bad_request("Malformed pagination cursor").with_code("CURSOR_MALFORMED")
+            }
+            CursorError::InvalidSignature => {
+                ApiError::bad_request("Pagination cursor failed integrity check").with_code("CURSOR_INVALID")
+            }
+            CursorError::Stale => {
+                ApiError::bad_request("Pagination cursor has expired, restart from the first page")
+                    .with_code("CURSOR_STALE")
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorPayload {
+    slot: u64,
+    signature: String,
+    direction: Direction,
+    issued_at: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn mac_for(config: &CursorConfig, payload: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(&config.secret).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac
+}
+
+impl Cursor {
+    pub fn encode(&self, config: &CursorConfig) -> String {
+        let payload = serde_json::to_vec(&CursorPayload {
+            slot: self.slot,
+            signature: self.signature.clone(),
+            direction: self.direction,
+            issued_at: now_secs(),
+        })
+        .expect("cursor payload is always serializable");
+
+        let tag = mac_for(config, &payload).finalize().into_bytes();
+
+        let mut token = payload;
+        token.extend_from_slice(&tag);
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    pub fn decode(config: &CursorConfig, token: &str) -> Result<Cursor, CursorError> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| CursorError::Malformed)?;
+        if bytes.len() <= MAC_LEN {
+            return Err(CursorError::Malformed);
+        }
+
+        let (payload, tag) = bytes.split_at(bytes.len() - MAC_LEN);
+        mac_for(config, payload).verify_slice(tag).map_err(|_| CursorError::InvalidSignature)?;
+
+        let payload: CursorPayload = serde_json::from_slice(payload).map_err(|_| CursorError::Malformed)?;
+        if now_secs().saturating_sub(payload.issued_at) > config.ttl.as_secs() {
+            return Err(CursorError::Stale);
+        }
+
+        Ok(Cursor {
+            slot: payload.slot,
+            signature: payload.signature,
+            direction: payload.direction,
+        })
+    }
+}
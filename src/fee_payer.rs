@@ -0,0 +1,85 @@
+//! Named fee-payer keys configured on the server.
+//!
+//! The `[fee_payers]` config maps names to sources: `env:VAR`,
+//! `file:/path/to/keypair.json` or `vault:secret/data/path#field`. Vault
+//! secrets are read once at startup from the `[vault]` server (and namespace
+//! when set); both KV v1 and v2 mounts work. Requests then name a fee payer
+//! instead of carrying its key.
+
+use axum::extract::State;
+use serde_json::Value;
+
+use std::{collections::HashMap, time::Duration};
+
+use crate::config::{Config, VaultConfig};
+use crate::signer::KeySigner;
+use crate::state::AppState;
+use crate::types::FeePayerData;
+use crate::utils::{parse_keypair, success_response, ApiResult};
+
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn read_vault_secret(vault: &VaultConfig, reference: &str) -> Result<String, String> {
+    let (path, field) = reference
+        .split_once('#')
+        .ok_or_else(|| format!("Vault reference {reference:?} needs a #field"))?;
+    let address = vault.addr.as_deref().ok_or_else(|| "No Vault address is configured".to_string())?;
+    let token = vault.token.as_deref().ok_or_else(|| "No Vault token is set".to_string())?;
+
+    let client = reqwest::Client::builder().timeout(VAULT_TIMEOUT).build().map_err(|e| e.to_string())?;
+    let mut request = client
+        .get(format!("{}/v1/{}", address.trim_end_matches('/'), path.trim_start_matches('/')))
+        .header("X-Vault-Token", token);
+    if let Some(namespace) = &vault.namespace {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+    let body: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Vault request for {path} failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Vault response for {path}: {e}"))?;
+
+    // KV v2 nests the secret one level deeper than v1.
+    let data = &body["data"];
+    let value = data["data"].get(field).or_else(|| data.get(field));
+    match value {
+        Some(Value::String(secret)) => Ok(secret.clone()),
+        // A keypair file stored as-is arrives as a JSON array.
+        Some(value @ Value::Array(_)) => Ok(value.to_string()),
+        _ => Err(format!("Vault secret {path} has no field {field}")),
+    }
+}
+
+async fn read_secret(vault: &VaultConfig, source: &str) -> Result<String, String> {
+    match source.split_once(':') {
+        Some(("env", var)) => std::env::var(var).map_err(|_| format!("Environment variable {var} is not set")),
+        Some(("file", path)) => std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}")),
+        Some(("vault", reference)) => read_vault_secret(vault, reference).await,
+        _ => Err(format!("Invalid fee payer source {source:?}; use env:, file: or vault:")),
+    }
+}
+
+/// Loads every configured fee payer.
+pub async fn load_fee_payers(config: &Config) -> Result<HashMap<String, KeySigner>, String> {
+    let mut fee_payers = HashMap::new();
+    for (name, source) in &config.fee_payers {
+        let secret = read_secret(&config.vault, source).await?;
+        let keypair = parse_keypair(&secret).map_err(|_| format!("Fee payer {name} has an invalid secret key"))?;
+        fee_payers.insert(name.clone(), keypair.into());
+    }
+    Ok(fee_payers)
+}
+
+/// Names and addresses of the configured fee payers.
+pub async fn list_fee_payers(State(state): State<AppState>) -> ApiResult {
+    let mut fee_payers: Vec<FeePayerData> = state
+        .fee_payers()
+        .iter()
+        .map(|(name, signer)| FeePayerData { name: name.clone(), pubkey: signer.pubkey().to_string() })
+        .collect();
+    fee_payers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(success_response(fee_payers))
+}
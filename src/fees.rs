@@ -0,0 +1,55 @@
+//! Priority fee suggestions from recently landed transactions.
+
+use axum::extract::{Query, State};
+
+use crate::state::AppState;
+use crate::types::{PriorityFeesData, PriorityFeesQuery};
+use crate::utils::{parse_pubkey, success_response, ApiError, ApiResult};
+
+/// `getRecentPrioritizationFees` rejects requests for more accounts than this.
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+pub async fn priority_fees(State(state): State<AppState>, Query(query): Query<PriorityFeesQuery>) -> ApiResult {
+    let accounts = query
+        .accounts
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|account| !account.is_empty())
+        .map(|account| parse_pubkey(account, "account"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if accounts.len() > MAX_FEE_ACCOUNTS {
+        return Err(ApiError::bad_request(format!("At most {MAX_FEE_ACCOUNTS} accounts are supported")));
+    }
+
+    // Without accounts the node reports the cheapest fee that landed in each slot,
+    // which understates the price needed to write to contended accounts.
+    let client = state.rpc()?;
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(&accounts)
+        .await?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    fees.sort_unstable();
+
+    Ok(success_response(PriorityFeesData {
+        accounts: accounts.iter().map(|account| account.to_string()).collect(),
+        samples: fees.len(),
+        p50: percentile(&fees, 50),
+        p75: percentile(&fees, 75),
+        p90: percentile(&fees, 90),
+        max: fees.last().copied().unwrap_or_default(),
+    }))
+}
@@ -0,0 +1,99 @@
+//! Liveness and readiness probes.
+//!
+//! `/health` answers whenever the process is serving requests. `/ready` also
+//! checks what requests depend on: each RPC endpoint, the keystore and its
+//! file, and the queue of tracked submissions, reporting each one. It answers 503 when a
+//! required dependency is down, and from the moment shutdown begins so that
+//! traffic moves elsewhere while in-flight work drains.
+
+use axum::{extract::State, http::StatusCode};
+use serde_json::json;
+use solana_client::{client_error::ClientErrorKind, nonblocking::rpc_client::RpcClient};
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::keystore::{key_counts, load_error};
+use crate::shutdown::is_shutting_down;
+use crate::state::AppState;
+use crate::tracker::queue_counts;
+use crate::types::{CheckStatus, DependencyCheck, HealthData, ReadyData};
+use crate::utils::{success_response, ApiError, ApiResult};
+
+pub const PROBE_ROUTES: [&str; 2] = ["/health", "/ready"];
+/// Probes answer quickly, so a slow RPC endpoint counts as down.
+const RPC_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn health() -> ApiResult {
+    Ok(success_response(HealthData { status: "ok", version: env!("CARGO_PKG_VERSION") }))
+}
+
+fn check(name: String, status: CheckStatus, required: bool, detail: Option<String>) -> DependencyCheck {
+    DependencyCheck { name, status, required, latency_ms: None, detail }
+}
+
+/// Asks the node whether it is healthy. The detail never carries the URL,
+/// which may embed a provider API key.
+async fn rpc_check(name: String, client: Arc<RpcClient>, required: bool) -> DependencyCheck {
+    let started = Instant::now();
+    let (status, detail) = match tokio::time::timeout(RPC_CHECK_TIMEOUT, client.get_health()).await {
+        Ok(Ok(())) => (CheckStatus::Ok, None),
+        Ok(Err(err)) => match err.kind() {
+            ClientErrorKind::RpcError(err) => (CheckStatus::Down, Some(err.to_string())),
+            _ => (CheckStatus::Down, Some("RPC endpoint unreachable".to_string())),
+        },
+        Err(_) => (CheckStatus::Down, Some("RPC endpoint timed out".to_string())),
+    };
+    DependencyCheck { latency_ms: Some(started.elapsed().as_millis() as u64), ..check(name, status, required, detail) }
+}
+
+fn keystore_check(enabled: bool) -> DependencyCheck {
+    if !enabled {
+        return check("keystore".to_string(), CheckStatus::Disabled, false, None);
+    }
+    if let Some(error) = load_error() {
+        return check("keystore".to_string(), CheckStatus::Down, true, Some(error.to_string()));
+    }
+    let (keys, disabled) = key_counts();
+    check("keystore".to_string(), CheckStatus::Ok, true, Some(format!("{keys} keys, {disabled} disabled")))
+}
+
+/// Down when a tracked submission has outlived its worker.
+fn queue_check() -> DependencyCheck {
+    let (pending, stalled) = queue_counts();
+    let status = if stalled > 0 { CheckStatus::Down } else { CheckStatus::Ok };
+    check("queue".to_string(), status, true, Some(format!("{pending} pending, {stalled} stalled")))
+}
+
+/// Reports every dependency; only the default RPC endpoint is required, as
+/// requests for other clusters opt into them.
+pub async fn ready(State(state): State<AppState>) -> ApiResult {
+    let probes: Vec<_> = state
+        .rpc_endpoints()
+        .into_iter()
+        .enumerate()
+        .map(|(index, (cluster, endpoint))| {
+            let name = format!("rpc:{cluster}");
+            (name.clone(), index == 0, tokio::spawn(rpc_check(name, endpoint.client.clone(), index == 0)))
+        })
+        .collect();
+    let mut checks = Vec::new();
+    for (name, required, probe) in probes {
+        checks.push(probe.await.unwrap_or_else(|_| check(name, CheckStatus::Down, required, None)));
+    }
+    checks.push(keystore_check(state.keystore_enabled()));
+    checks.push(queue_check());
+    if is_shutting_down() {
+        checks.push(check("server".to_string(), CheckStatus::Down, true, Some("Shutting down".to_string())));
+    }
+
+    let ready = checks.iter().all(|check| !check.required || check.status != CheckStatus::Down);
+    if !ready {
+        return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Not ready")
+            .with_code("NOT_READY")
+            .with_details(json!(ReadyData { ready, checks })));
+    }
+    Ok(success_response(ReadyData { ready, checks }))
+}
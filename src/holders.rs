@@ -0,0 +1,224 @@
+//! Holder snapshots for airdrop eligibility and distribution checks.
+//!
+//! Scanning every token account of a large mint can take longer than a client
+//! wants to hold a request open, so `/token/holders/{mint}` runs the scan in the
+//! background and returns `pending` until it finishes. Finished snapshots are
+//! kept for a while and served page by page; `refresh=true` takes a new one.
+//! Snapshots belong to the RPC endpoint they were taken from, so a mint
+//! scanned on one cluster is scanned afresh on another.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::state::AppState;
+use crate::token::is_token_program;
+use crate::types::{HolderData, HoldersQuery, HoldersSnapshotData, SnapshotStatus};
+use crate::utils::{page_offset, parse_pubkey, success_response, ApiError, ApiResult};
+
+const DEFAULT_HOLDERS_PAGE_SIZE: usize = 100;
+const MAX_HOLDERS_PAGE_SIZE: usize = 1000;
+/// How long finished snapshots stay queryable.
+const SNAPSHOT_RETENTION: Duration = Duration::from_secs(600);
+/// Token accounts store the mint first, then the owner and the amount.
+const OWNER_OFFSET: usize = 32;
+const OWNER_AND_AMOUNT_LEN: usize = 40;
+
+struct Snapshot {
+    status: SnapshotStatus,
+    decimals: u8,
+    slot: Option<u64>,
+    taken_at: Option<u64>,
+    total_accounts: usize,
+    /// Owners with a non-zero balance, largest first.
+    holders: Vec<(String, u64)>,
+    err: Option<String>,
+    finished_at: Option<Instant>,
+}
+
+/// RPC endpoint URL and mint.
+type SnapshotKey = (String, Pubkey);
+
+fn snapshots() -> &'static Mutex<HashMap<SnapshotKey, Snapshot>> {
+    static SNAPSHOTS: OnceLock<Mutex<HashMap<SnapshotKey, Snapshot>>> = OnceLock::new();
+    SNAPSHOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Sums every token account of `mint` per owner. Returns the slot the scan
+/// started at, the balances, and how many token accounts were read.
+async fn scan_holders(
+    client: &RpcClient,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<(u64, Vec<(String, u64)>, usize), ClientError> {
+    let slot = client.get_slot().await?;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+            RpcFilterType::TokenAccountState,
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            // Only the owner and amount are needed, which keeps huge mints manageable.
+            data_slice: Some(UiDataSliceConfig { offset: OWNER_OFFSET, length: OWNER_AND_AMOUNT_LEN }),
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client.get_program_accounts_with_config(program_id, config).await?;
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for (_, account) in &accounts {
+        let Some((owner, amount)) = account.data.split_first_chunk::<32>() else {
+            continue;
+        };
+        let amount = amount.first_chunk::<8>().map_or(0, |amount| u64::from_le_bytes(*amount));
+        if amount > 0 {
+            let balance = balances.entry(Pubkey::new_from_array(*owner)).or_default();
+            *balance = balance.saturating_add(amount);
+        }
+    }
+
+    let mut holders: Vec<(String, u64)> =
+        balances.into_iter().map(|(owner, amount)| (owner.to_string(), amount)).collect();
+    holders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok((slot, holders, accounts.len()))
+}
+
+async fn take_snapshot(client: Arc<RpcClient>, key: SnapshotKey, program_id: Pubkey) {
+    let result = scan_holders(&client, &key.1, &program_id).await;
+
+    let mut snapshots = snapshots().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(snapshot) = snapshots.get_mut(&key) else {
+        return;
+    };
+    match result {
+        Ok((slot, holders, total_accounts)) => {
+            snapshot.status = SnapshotStatus::Ready;
+            snapshot.slot = Some(slot);
+            snapshot.taken_at = Some(unix_timestamp());
+            snapshot.total_accounts = total_accounts;
+            snapshot.holders = holders;
+        }
+        Err(err) => {
+            snapshot.status = SnapshotStatus::Failed;
+            snapshot.err = Some(err.to_string());
+        }
+    }
+    snapshot.finished_at = Some(Instant::now());
+}
+
+fn snapshot_data(mint: &Pubkey, snapshot: &Snapshot, page: usize, limit: usize) -> HoldersSnapshotData {
+    HoldersSnapshotData {
+        mint: mint.to_string(),
+        status: snapshot.status,
+        slot: snapshot.slot,
+        taken_at: snapshot.taken_at,
+        decimals: snapshot.decimals,
+        total_accounts: snapshot.total_accounts,
+        total_holders: snapshot.holders.len(),
+        page,
+        limit,
+        holders: snapshot
+            .holders
+            .iter()
+            .skip(page_offset(page, limit))
+            .take(limit)
+            .map(|(owner, amount)| HolderData {
+                owner: owner.clone(),
+                amount: *amount,
+                ui_amount: spl_token::amount_to_ui_amount(*amount, snapshot.decimals),
+            })
+            .collect(),
+        err: snapshot.err.clone(),
+    }
+}
+
+pub async fn token_holders(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(query): Query<HoldersQuery>,
+) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_HOLDERS_PAGE_SIZE);
+    if limit == 0 || limit > MAX_HOLDERS_PAGE_SIZE {
+        return Err(ApiError::bad_request(format!("limit must be between 1 and {MAX_HOLDERS_PAGE_SIZE}")));
+    }
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::bad_request("page must be at least 1"));
+    }
+
+    let key = (state.rpc_url().to_string(), mint_pubkey);
+    {
+        let mut snapshots = snapshots().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        snapshots.retain(|_, snapshot| snapshot.finished_at.is_none_or(|at| at.elapsed() < SNAPSHOT_RETENTION));
+        if let Some(snapshot) = snapshots.get(&key) {
+            // A running scan is never restarted; refresh only replaces finished ones.
+            if !query.refresh || snapshot.status == SnapshotStatus::Pending {
+                return Ok(success_response(snapshot_data(&mint_pubkey, snapshot, page, limit)));
+            }
+        }
+    }
+
+    let client = state.rpc()?;
+    let mint_account = client
+        .get_account_with_commitment(&mint_pubkey, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+
+    if !is_token_program(&mint_account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?
+        .base
+        .decimals;
+
+    let snapshot = Snapshot {
+        status: SnapshotStatus::Pending,
+        decimals,
+        slot: None,
+        taken_at: None,
+        total_accounts: 0,
+        holders: Vec::new(),
+        err: None,
+        finished_at: None,
+    };
+    let data = snapshot_data(&mint_pubkey, &snapshot, page, limit);
+    {
+        let mut snapshots = snapshots().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Another request may have started a scan while the mint was being fetched.
+        let running = snapshots.get(&key).filter(|existing| existing.status == SnapshotStatus::Pending);
+        if let Some(existing) = running {
+            return Ok(success_response(snapshot_data(&mint_pubkey, existing, page, limit)));
+        }
+        snapshots.insert(key.clone(), snapshot);
+    }
+
+    tokio::spawn(take_snapshot(state.rpc_handle(), key, mint_account.owner));
+    Ok(success_response(data))
+}
@@ -0,0 +1,144 @@
+//! Jito bundle submission and tip instructions.
+//!
+//! Bundles go to the configured block engine (mainnet by default),
+//! authenticated with the `[jito]` auth UUID when one is set. The
+//! block engine only accepts bundles that pay a tip to one of its tip accounts.
+
+use axum::{extract::State, http::StatusCode, Json};
+use rand::seq::SliceRandom;
+use serde_json::{json, Value};
+use solana_sdk::{
+    message::VersionedMessage,
+    pubkey,
+    pubkey::Pubkey,
+    system_instruction::{transfer, SystemInstruction},
+};
+use solana_sdk_ids::system_program;
+
+use std::time::Duration;
+
+use crate::config::JitoConfig;
+use crate::state::AppState;
+use crate::tx::{decode_transaction, encode_transaction};
+use crate::types::{BundleSubmitData, BundleSubmitRequest, JitoTipRequest};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const DEFAULT_BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf";
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+/// Smallest tip the block engine accepts.
+pub const MIN_TIP_LAMPORTS: u64 = 1_000;
+const BLOCK_ENGINE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// Whether the message transfers SOL to a tip account.
+fn pays_tip(message: &VersionedMessage) -> bool {
+    let keys = message.static_account_keys();
+    message.instructions().iter().any(|ix| {
+        keys.get(ix.program_id_index as usize) == Some(&system_program::ID)
+            && matches!(bincode::deserialize(&ix.data), Ok(SystemInstruction::Transfer { .. }))
+            && ix
+                .accounts
+                .get(1)
+                .and_then(|index| keys.get(*index as usize))
+                .is_some_and(|recipient| TIP_ACCOUNTS.contains(recipient))
+    })
+}
+
+/// Sends a JSON-RPC request to the block engine's bundle API.
+async fn block_engine_request(config: &JitoConfig, method: &str, params: Value) -> Result<Value, ApiError> {
+    let client = reqwest::Client::builder()
+        .timeout(BLOCK_ENGINE_TIMEOUT)
+        .build()
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut request = client
+        .post(format!("{}/api/v1/bundles", config.block_engine_url.trim_end_matches('/')))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }));
+    if let Some(uuid) = &config.auth_uuid {
+        request = request.header("x-jito-auth", uuid);
+    }
+
+    let mut response: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, format!("Block engine request failed: {e}")))?
+        .json()
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, format!("Invalid block engine response: {e}")))?;
+
+    if let Some(error) = response.get("error") {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        return Err(ApiError::new(StatusCode::BAD_GATEWAY, format!("Block engine rejected bundle: {message}")));
+    }
+    Ok(response["result"].take())
+}
+
+pub async fn tip(Json(payload): Json<JitoTipRequest>) -> ApiResult {
+    let JitoTipRequest { from, lamports, tip_account } = payload;
+
+    if from.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: from"));
+    }
+
+    if lamports < MIN_TIP_LAMPORTS {
+        return Err(ApiError::bad_request(format!("Tip must be at least {MIN_TIP_LAMPORTS} lamports")));
+    }
+
+    let from_pubkey = parse_pubkey(&from, "from")?;
+    let tip_account = match parse_optional_pubkey(tip_account.as_deref(), "tip account")? {
+        Some(account) if !TIP_ACCOUNTS.contains(&account) => {
+            return Err(ApiError::bad_request("tipAccount is not a Jito tip account"));
+        }
+        Some(account) => account,
+        // Spreading tips across accounts avoids write-lock contention on any one of them.
+        None => *TIP_ACCOUNTS.choose(&mut rand::thread_rng()).expect("tip accounts are not empty"),
+    };
+
+    Ok(success_response(instruction_to_data(&transfer(&from_pubkey, &tip_account, lamports))))
+}
+
+pub async fn submit_bundle(State(state): State<AppState>, Json(payload): Json<BundleSubmitRequest>) -> ApiResult {
+    let BundleSubmitRequest { transactions } = payload;
+
+    if transactions.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transactions"));
+    }
+
+    if transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+        return Err(ApiError::bad_request(format!(
+            "A bundle holds at most {MAX_BUNDLE_TRANSACTIONS} transactions"
+        )));
+    }
+
+    let transactions = transactions.iter().map(|tx| decode_transaction(tx)).collect::<Result<Vec<_>, _>>()?;
+    for (index, transaction) in transactions.iter().enumerate() {
+        let required = transaction.message.header().num_required_signatures as usize;
+        let signed = transaction.signatures.len() == required
+            && transaction.signatures.iter().all(|signature| *signature != Default::default());
+        if !signed {
+            return Err(ApiError::bad_request(format!("Transaction {index} is not fully signed")));
+        }
+    }
+    if !transactions.iter().any(|transaction| pays_tip(&transaction.message)) {
+        return Err(ApiError::bad_request("Bundle must include a transfer to a Jito tip account"));
+    }
+
+    let encoded = transactions.iter().map(encode_transaction).collect::<Result<Vec<_>, _>>()?;
+    let bundle_id = block_engine_request(state.jito(), "sendBundle", json!([encoded, { "encoding": "base64" }])).await?;
+
+    Ok(success_response(BundleSubmitData {
+        bundle_id: bundle_id.as_str().unwrap_or_default().to_string(),
+        signatures: transactions.iter().map(|transaction| transaction.signatures[0].to_string()).collect(),
+    }))
+}
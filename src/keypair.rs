@@ -0,0 +1,207 @@
+//! Key derivation: random keys, keys from raw seed bytes, keys derived from
+//! BIP39 mnemonics matching what Phantom, Solflare and the Solana CLI produce
+//! for the same phrase and path, and program derived addresses.
+
+use axum::{extract::Query, Json};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bip39::{Language, Mnemonic};
+use serde_json::{json, Value};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN as MAX_PDA_SEED_LEN},
+    signer::{
+        keypair::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed, keypair_from_seed_and_derivation_path,
+            Keypair,
+        },
+        Signer,
+    },
+};
+use sha2::{Digest, Sha256};
+
+use crate::types::{
+    DeriveKeypairRequest, DerivePdaRequest, DerivedKeypairData, DerivedKeypairsData, KeypairData, KeypairFromSeedData,
+    KeypairFromSeedRequest, KeypairQuery, PdaData, PdaSeed, PdaSeedType, SecretFormat, SeedDerivation, SeedEncoding,
+};
+use crate::utils::{parse_pubkey, success_response, ApiError, ApiResult};
+
+/// The path wallets use for the n-th account of a phrase.
+const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/{index}'/0'";
+const INDEX_PLACEHOLDER: &str = "{index}";
+const MAX_DERIVED_KEYPAIRS: u32 = 100;
+const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+const ED25519_SEED_LEN: usize = 32;
+const MAX_SEED_LEN: usize = 1024;
+
+/// Renders the 64-byte secret key in the requested encoding.
+pub fn encode_secret(keypair: &Keypair, format: SecretFormat) -> Value {
+    match format {
+        SecretFormat::Base58 => json!(keypair.to_base58_string()),
+        // The same byte array solana-keygen writes to keypair files.
+        SecretFormat::Json => json!(keypair.to_bytes().to_vec()),
+        SecretFormat::Hex => json!(hex::encode(keypair.to_bytes())),
+    }
+}
+
+pub async fn generate_keypair(Query(query): Query<KeypairQuery>) -> ApiResult {
+    let keypair = Keypair::new();
+
+    Ok(success_response(KeypairData {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_secret(&keypair, query.format.unwrap_or(SecretFormat::Base58)),
+    }))
+}
+
+/// Deterministic keypair from caller-supplied bytes. A 32-byte seed is the
+/// ed25519 secret itself, as with `Keypair::new_from_array`; any other length
+/// is hashed with SHA-256 first, so no input byte is ever ignored.
+pub async fn keypair_from_seed_bytes(Json(payload): Json<KeypairFromSeedRequest>) -> ApiResult {
+    let KeypairFromSeedRequest { seed, encoding, format } = payload;
+
+    if seed.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: seed"));
+    }
+    let bytes = match encoding.unwrap_or(SeedEncoding::Hex) {
+        SeedEncoding::Hex => hex::decode(seed.strip_prefix("0x").unwrap_or(&seed))
+            .map_err(|_| ApiError::bad_request("Invalid hex seed"))?,
+        SeedEncoding::Base64 => STANDARD.decode(&seed).map_err(|_| ApiError::bad_request("Invalid base64 seed"))?,
+    };
+    if bytes.is_empty() || bytes.len() > MAX_SEED_LEN {
+        return Err(ApiError::bad_request(format!("seed must be between 1 and {MAX_SEED_LEN} bytes")));
+    }
+
+    let (seed, derivation) = if bytes.len() == ED25519_SEED_LEN {
+        (bytes, SeedDerivation::Raw)
+    } else {
+        (Sha256::digest(&bytes).to_vec(), SeedDerivation::Sha256)
+    };
+    let keypair = keypair_from_seed(&seed).map_err(|_| ApiError::bad_request("Failed to create keypair from seed"))?;
+
+    Ok(success_response(KeypairFromSeedData {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_secret(&keypair, format.unwrap_or(SecretFormat::Base58)),
+        derivation,
+    }))
+}
+
+/// Checks a path like `m/44'/501'/0'/0'`. Ed25519 under SLIP-0010 has no
+/// public derivation, so every index must be hardened.
+pub fn parse_derivation_path(path: &str) -> Result<DerivationPath, ApiError> {
+    let invalid = || ApiError::bad_request(format!("Invalid derivation path: {path}"));
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    for segment in segments {
+        let index = segment.strip_suffix('\'').or_else(|| segment.strip_suffix('h'));
+        match index {
+            Some(index) => {
+                index.parse::<u32>().ok().filter(|index| *index < 1 << 31).ok_or_else(invalid)?;
+            }
+            None if segment.parse::<u32>().is_ok() => {
+                return Err(ApiError::bad_request(format!(
+                    "Derivation path index {segment} must be hardened; ed25519 only supports hardened derivation"
+                )));
+            }
+            None => return Err(invalid()),
+        }
+    }
+    DerivationPath::from_absolute_path_str(&path.replace('h', "'")).map_err(|_| invalid())
+}
+
+pub async fn derive_keypair(Json(payload): Json<DeriveKeypairRequest>) -> ApiResult {
+    let DeriveKeypairRequest { mnemonic, passphrase, path, start_index, count } = payload;
+
+    if mnemonic.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: mnemonic"));
+    }
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    if !MNEMONIC_WORD_COUNTS.contains(&words.len()) {
+        return Err(ApiError::bad_request("Mnemonic must have 12, 15, 18, 21 or 24 words"));
+    }
+    // A typo still derives a valid-looking key, so check the words and checksum like a wallet would.
+    let phrase = words.join(" ");
+    Mnemonic::parse_in(Language::English, &phrase)
+        .map_err(|err| ApiError::bad_request(format!("Invalid mnemonic: {err}")).with_code("INVALID_MNEMONIC"))?;
+
+    let path = path.unwrap_or_else(|| DEFAULT_DERIVATION_PATH.to_string());
+    let start_index = start_index.unwrap_or(0);
+    let count = count.unwrap_or(1);
+    if count == 0 || count > MAX_DERIVED_KEYPAIRS {
+        return Err(ApiError::bad_request(format!("count must be between 1 and {MAX_DERIVED_KEYPAIRS}")));
+    }
+    if !path.contains(INDEX_PLACEHOLDER) && (count > 1 || start_index > 0) {
+        return Err(ApiError::bad_request(format!("path must contain {INDEX_PLACEHOLDER} to derive a range")));
+    }
+
+    // The seed is the expensive part, so it is shared by every index.
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&phrase, passphrase.as_deref().unwrap_or(""));
+
+    let mut accounts = Vec::new();
+    for offset in 0..count {
+        let index = start_index
+            .checked_add(offset)
+            .ok_or_else(|| ApiError::bad_request("startIndex + count is out of range"))?;
+        let account_path = path.replace(INDEX_PLACEHOLDER, &index.to_string());
+        let derivation_path = parse_derivation_path(&account_path)?;
+        let keypair = keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+            .map_err(|err| ApiError::bad_request(format!("Failed to derive keypair: {err}")))?;
+        accounts.push(DerivedKeypairData {
+            index,
+            path: account_path,
+            pubkey: keypair.pubkey().to_string(),
+            secret: keypair.to_base58_string(),
+        });
+    }
+
+    Ok(success_response(DerivedKeypairsData { accounts }))
+}
+
+fn pda_seed_bytes(index: usize, seed: &PdaSeed) -> Result<Vec<u8>, ApiError> {
+    let invalid = |kind: &str| ApiError::bad_request(format!("Seed {index} is not valid {kind}"));
+    let bytes = match seed.kind {
+        PdaSeedType::Utf8 => seed.value.as_bytes().to_vec(),
+        PdaSeedType::Base58 => bs58::decode(&seed.value).into_vec().map_err(|_| invalid("base58"))?,
+        PdaSeedType::Hex => {
+            hex::decode(seed.value.strip_prefix("0x").unwrap_or(&seed.value)).map_err(|_| invalid("hex"))?
+        }
+        PdaSeedType::Pubkey => parse_pubkey(&seed.value, &format!("seed {index}"))?.to_bytes().to_vec(),
+    };
+    if bytes.len() > MAX_PDA_SEED_LEN {
+        return Err(ApiError::bad_request(format!(
+            "Seed {index} is {} bytes; seeds are at most {MAX_PDA_SEED_LEN}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+/// The canonical program derived address for `seeds`, with its bump seed.
+pub async fn derive_pda(Json(payload): Json<DerivePdaRequest>) -> ApiResult {
+    let DerivePdaRequest { program_id, seeds } = payload;
+
+    if program_id.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: programId"));
+    }
+    let program_id = parse_pubkey(&program_id, "program")?;
+    // The bump takes up the last seed slot.
+    if seeds.len() >= MAX_SEEDS {
+        return Err(ApiError::bad_request(format!("At most {} seeds are allowed", MAX_SEEDS - 1)));
+    }
+
+    let seeds = seeds
+        .iter()
+        .enumerate()
+        .map(|(index, seed)| pda_seed_bytes(index, seed))
+        .collect::<Result<Vec<_>, _>>()?;
+    let seed_refs: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    let (address, bump) = Pubkey::try_find_program_address(&seed_refs, &program_id)
+        .ok_or_else(|| ApiError::bad_request("No valid bump seed exists for these seeds"))?;
+
+    Ok(success_response(PdaData {
+        address: address.to_string(),
+        bump,
+        program_id: program_id.to_string(),
+        seeds: seeds.iter().map(hex::encode).collect(),
+    }))
+}
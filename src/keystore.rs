@@ -0,0 +1,639 @@
+//! Server-held signing keys.
+//!
+//! Keys are generated or imported once through `/keys` and afterwards named
+//! by id, so clients of the signing endpoints never handle key material. The
+//! keystore lives in memory; with a keystore file configured it is loaded
+//! from there at startup and written back, in the archive format below, after
+//! every change. A file that fails to load leaves the keystore unavailable
+//! rather than empty, so it is never overwritten.
+//!
+//! Every signature made with a key is counted, and disabled keys stay listed
+//! but can no longer sign.
+//!
+//! A key can also be registered by reference to a KMS or PKCS#11 backend
+//! (see `signer`), in which case only its address and backend settings are
+//! held here.
+//!
+//! For disaster recovery the whole keystore can be exported as one archive,
+//! encrypted with AES-256-GCM-SIV under a key stretched from a passphrase with
+//! PBKDF2, and imported again with ids, labels and usage history intact.
+//! Single keys move to and from wallet apps in their own private key formats.
+
+use aes_gcm_siv::{
+    aead::{Aead, KeyInit},
+    Aes256GcmSiv, Nonce,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::Hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_keypair::Keypair;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use tokio::sync::Notify;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::{KeystoreFileConfig, Pkcs11Config};
+use crate::keypair::encode_secret;
+use crate::signer::{connect_remote, restore_remote, KeySigner};
+use crate::state::AppState;
+use crate::types::{
+    CreateKeyRequest, ExportKeysRequest, ImportKeysData, ImportKeysRequest, KeyBackendConfig, KeysQuery,
+    KeystoreArchive, SecretKey, StoredKeyData, StoredKeysData, UpdateKeyRequest, WalletExportData,
+    WalletExportRequest, WalletImportRequest,
+};
+use crate::utils::{page_offset, parse_pubkey, parse_secret_key, success_response, ApiError, ApiResult};
+
+const DEFAULT_KEYS_PAGE_SIZE: usize = 100;
+const MAX_KEYS_PAGE_SIZE: usize = 1000;
+const ARCHIVE_VERSION: u8 = 1;
+const ARCHIVE_KDF: &str = "pbkdf2-sha256";
+const ARCHIVE_CIPHER: &str = "aes-256-gcm-siv";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Imports refuse archives claiming more rounds than this, which would only tie up the server.
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+struct StoredKey {
+    signer: KeySigner,
+    /// Set for keys held by a remote backend.
+    backend: Option<KeyBackendConfig>,
+    labels: Vec<String>,
+    created_at: u64,
+    usage_count: u64,
+    last_used_at: Option<u64>,
+    disabled: bool,
+}
+
+impl StoredKey {
+    fn data(&self, id: &str) -> StoredKeyData {
+        StoredKeyData {
+            id: id.to_string(),
+            pubkey: self.signer.pubkey().to_string(),
+            backend: self.backend.as_ref().map_or("local", KeyBackendConfig::name).to_string(),
+            labels: self.labels.clone(),
+            created_at: self.created_at,
+            usage_count: self.usage_count,
+            last_used_at: self.last_used_at,
+            disabled: self.disabled,
+        }
+    }
+
+    /// Whether `search` appears in the id, the address or any label, ignoring case.
+    fn matches(&self, id: &str, search: &str) -> bool {
+        let search = search.to_lowercase();
+        id.contains(&search)
+            || self.signer.pubkey().to_string().to_lowercase().contains(&search)
+            || self.labels.iter().any(|label| label.to_lowercase().contains(&search))
+    }
+}
+
+/// A key as stored inside an encrypted archive.
+#[derive(Serialize, Deserialize)]
+struct ArchivedKey {
+    id: String,
+    pubkey: String,
+    /// Base58 64-byte keypair, for local keys.
+    #[serde(default)]
+    secret: Option<String>,
+    /// Backend settings, for remote keys.
+    #[serde(default)]
+    backend: Option<KeyBackendConfig>,
+    labels: Vec<String>,
+    created_at: u64,
+    usage_count: u64,
+    last_used_at: Option<u64>,
+    disabled: bool,
+}
+
+fn unknown_key(id: &str) -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, format!("No key with id {id}")).with_code("UNKNOWN_KEY")
+}
+
+fn keys() -> &'static Mutex<HashMap<String, StoredKey>> {
+    static KEYS: OnceLock<Mutex<HashMap<String, StoredKey>>> = OnceLock::new();
+    KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The keystore file, with the cipher its passphrase stretches to. Deriving
+/// it once keeps writes cheap; each write still gets a fresh nonce.
+struct KeystoreFile {
+    path: PathBuf,
+    salt: [u8; 16],
+    cipher: Aes256GcmSiv,
+}
+
+static KEYSTORE_FILE: OnceLock<KeystoreFile> = OnceLock::new();
+/// Why the keystore file could not be loaded.
+static LOAD_ERROR: OnceLock<String> = OnceLock::new();
+/// Wakes the writer after a change.
+static CHANGED: Notify = Notify::const_new();
+
+/// The stored keys, unless the keystore file failed to load.
+fn stored_keys() -> Result<MutexGuard<'static, HashMap<String, StoredKey>>, ApiError> {
+    if LOAD_ERROR.get().is_some() {
+        return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "The keystore failed to load")
+            .with_code("KEYSTORE_UNAVAILABLE"));
+    }
+    Ok(keys().lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Schedules the keystore file to be rewritten.
+fn changed() {
+    if KEYSTORE_FILE.get().is_some() {
+        CHANGED.notify_one();
+    }
+}
+
+pub fn load_error() -> Option<&'static str> {
+    LOAD_ERROR.get().map(String::as_str)
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// The signer stored under `id`; counts as a use of the key.
+pub fn resolve_key(id: &str) -> Result<KeySigner, ApiError> {
+    let mut keys = stored_keys()?;
+    let key = keys.get_mut(id).ok_or_else(|| unknown_key(id))?;
+    if key.disabled {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, format!("Key {id} is disabled")).with_code("KEY_DISABLED"));
+    }
+    key.usage_count += 1;
+    key.last_used_at = Some(unix_timestamp());
+    changed();
+    Ok(key.signer.clone())
+}
+
+/// Collects signers from raw secrets and keystore ids. Raw secrets are
+/// refused when the server is configured to sign only with stored keys.
+pub fn resolve_signers(
+    state: &AppState,
+    secret_keys: &[SecretKey],
+    key_ids: &[String],
+) -> Result<Vec<KeySigner>, ApiError> {
+    if !secret_keys.is_empty() && !state.allow_raw_secrets() {
+        return Err(raw_secrets_disabled());
+    }
+    let mut signers = secret_keys
+        .iter()
+        .map(|secret| parse_secret_key(secret).map(KeySigner::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    for id in key_ids {
+        signers.push(resolve_key(id)?);
+    }
+    Ok(signers)
+}
+
+/// The one signer a request names, by raw secret or keystore id.
+pub fn resolve_signer(
+    state: &AppState,
+    secret: Option<&SecretKey>,
+    key_id: Option<&str>,
+) -> Result<KeySigner, ApiError> {
+    match (secret, key_id) {
+        (Some(_), _) if !state.allow_raw_secrets() => Err(raw_secrets_disabled()),
+        // Accepts 32-byte seeds and 64-byte keypairs in any supported encoding.
+        (Some(secret), None) => parse_secret_key(secret).map(KeySigner::from),
+        (None, Some(key_id)) => resolve_key(key_id),
+        (Some(_), Some(_)) => Err(ApiError::bad_request("Provide either secret or keyId, not both")),
+        (None, None) => Err(ApiError::bad_request("Missing required fields: secret or keyId")),
+    }
+}
+
+pub fn raw_secrets_disabled() -> ApiError {
+    ApiError::new(StatusCode::FORBIDDEN, "Raw secret keys are disabled on this server; use a keyId")
+        .with_code("RAW_SECRETS_DISABLED")
+}
+
+/// Generates a key, imports the supplied secret, or registers a remote
+/// backend key, and returns its id.
+pub async fn create_key(State(state): State<AppState>, Json(payload): Json<CreateKeyRequest>) -> ApiResult {
+    let CreateKeyRequest { secret, backend, labels } = payload;
+
+    let signer = match (secret, &backend) {
+        (Some(_), Some(_)) => return Err(ApiError::bad_request("Provide either secret or backend, not both")),
+        (Some(secret), None) => parse_secret_key(&secret)?.into(),
+        (None, Some(config)) => connect_remote(config, state.pkcs11_tokens()).await?,
+        (None, None) => Keypair::new().into(),
+    };
+
+    Ok(success_response(store_key(signer, backend, labels)?))
+}
+
+fn store_key(
+    signer: KeySigner,
+    backend: Option<KeyBackendConfig>,
+    labels: Vec<String>,
+) -> Result<StoredKeyData, ApiError> {
+    let id = hex::encode(rand::random::<[u8; 16]>());
+    let key = StoredKey {
+        signer,
+        backend,
+        labels,
+        created_at: unix_timestamp(),
+        usage_count: 0,
+        last_used_at: None,
+        disabled: false,
+    };
+    let data = key.data(&id);
+    stored_keys()?.insert(id, key);
+    changed();
+    Ok(data)
+}
+
+/// Refuses a key whose address is not the one the caller expects, which
+/// catches keys pasted from the wrong wallet account.
+fn check_expected_address(pubkey: &Pubkey, expected: Option<&str>) -> Result<(), ApiError> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    if parse_pubkey(expected, "expectedAddress")? != *pubkey {
+        return Err(ApiError::bad_request(format!("Key address {pubkey} does not match expected address {expected}"))
+            .with_code("ADDRESS_MISMATCH"));
+    }
+    Ok(())
+}
+
+/// Imports a private key exported from Phantom, Solflare, Backpack or
+/// solana-keygen. Base58 strings and JSON keypair files are both recognized.
+pub async fn import_wallet_key(Json(payload): Json<WalletImportRequest>) -> ApiResult {
+    let WalletImportRequest { secret, expected_address, labels } = payload;
+
+    if secret.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: secret"));
+    }
+    let keypair = parse_secret_key(&secret)?;
+    check_expected_address(&keypair.pubkey(), expected_address.as_deref())?;
+
+    Ok(success_response(store_key(keypair.into(), None, labels)?))
+}
+
+/// Exports a stored key in the form a wallet app imports. Remote keys have
+/// no secret to export, and none leaves a server with raw secrets disabled.
+pub async fn export_wallet_key(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<WalletExportRequest>,
+) -> ApiResult {
+    let WalletExportRequest { wallet, expected_address } = payload;
+
+    if !state.allow_raw_secrets() {
+        return Err(raw_secrets_disabled());
+    }
+
+    let signer = {
+        let keys = stored_keys()?;
+        keys.get(&id).ok_or_else(|| unknown_key(&id))?.signer.clone()
+    };
+    let KeySigner::Local(keypair) = signer else {
+        return Err(ApiError::bad_request(format!("Key {id} is held by a remote backend and cannot be exported"))
+            .with_code("KEY_NOT_EXPORTABLE"));
+    };
+    check_expected_address(&keypair.pubkey(), expected_address.as_deref())?;
+
+    Ok(success_response(WalletExportData {
+        id,
+        pubkey: keypair.pubkey().to_string(),
+        wallet,
+        secret: encode_secret(&keypair, wallet.secret_format()),
+    }))
+}
+
+/// How many keys are stored, and how many of them are disabled.
+pub fn key_counts() -> (usize, usize) {
+    let keys = keys().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    (keys.len(), keys.values().filter(|key| key.disabled).count())
+}
+
+/// Stored keys, oldest first, optionally narrowed by label, search text or state.
+pub async fn list_keys(Query(query): Query<KeysQuery>) -> ApiResult {
+    let limit = query.limit.unwrap_or(DEFAULT_KEYS_PAGE_SIZE);
+    if limit == 0 || limit > MAX_KEYS_PAGE_SIZE {
+        return Err(ApiError::bad_request(format!("limit must be between 1 and {MAX_KEYS_PAGE_SIZE}")));
+    }
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::bad_request("page must be at least 1"));
+    }
+
+    let keys = stored_keys()?;
+    let mut matching: Vec<StoredKeyData> = keys
+        .iter()
+        .filter(|(_, key)| query.label.as_ref().is_none_or(|label| key.labels.contains(label)))
+        .filter(|(id, key)| query.search.as_deref().is_none_or(|search| key.matches(id, search)))
+        .filter(|(_, key)| query.disabled.is_none_or(|disabled| key.disabled == disabled))
+        .map(|(id, key)| key.data(id))
+        .collect();
+    matching.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+
+    let total = matching.len();
+    let keys = matching.into_iter().skip(page_offset(page, limit)).take(limit).collect();
+    Ok(success_response(StoredKeysData { total, page, limit, keys }))
+}
+
+pub async fn get_key(Path(id): Path<String>) -> ApiResult {
+    let keys = stored_keys()?;
+    let key = keys.get(&id).ok_or_else(|| unknown_key(&id))?;
+    Ok(success_response(key.data(&id)))
+}
+
+/// Relabels a key or disables and re-enables it. Disabled keys keep their
+/// history but are refused by every signing endpoint.
+pub async fn update_key(Path(id): Path<String>, Json(payload): Json<UpdateKeyRequest>) -> ApiResult {
+    let UpdateKeyRequest { labels, disabled } = payload;
+
+    let mut keys = stored_keys()?;
+    let key = keys.get_mut(&id).ok_or_else(|| unknown_key(&id))?;
+    if let Some(labels) = labels {
+        key.labels = labels;
+    }
+    if let Some(disabled) = disabled {
+        key.disabled = disabled;
+    }
+    changed();
+    Ok(success_response(key.data(&id)))
+}
+
+fn archive_cipher(passphrase: &str, salt: &[u8], iterations: u32) -> Aes256GcmSiv {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, iterations, &mut key);
+    Aes256GcmSiv::new(&key.into())
+}
+
+fn check_passphrase(passphrase: &str) -> Result<(), ApiError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(ApiError::bad_request(format!(
+            "passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn archived_keys(keys: &HashMap<String, StoredKey>) -> Vec<ArchivedKey> {
+    keys.iter()
+        .map(|(id, key)| ArchivedKey {
+            id: id.clone(),
+            pubkey: key.signer.pubkey().to_string(),
+            secret: match &key.signer {
+                KeySigner::Local(keypair) => Some(keypair.to_base58_string()),
+                KeySigner::Remote { .. } => None,
+            },
+            backend: key.backend.clone(),
+            labels: key.labels.clone(),
+            created_at: key.created_at,
+            usage_count: key.usage_count,
+            last_used_at: key.last_used_at,
+            disabled: key.disabled,
+        })
+        .collect()
+}
+
+/// Encrypts `archived` under `cipher`, which `salt` stretched the passphrase to.
+fn seal(cipher: &Aes256GcmSiv, salt: &[u8], archived: &[ArchivedKey]) -> Result<KeystoreArchive, ApiError> {
+    let plaintext = serde_json::to_vec(archived)
+        .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to serialize keystore"))?;
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to encrypt keystore"))?;
+    Ok(KeystoreArchive {
+        version: ARCHIVE_VERSION,
+        kdf: ARCHIVE_KDF.to_string(),
+        iterations: PBKDF2_ITERATIONS,
+        salt: STANDARD.encode(salt),
+        cipher: ARCHIVE_CIPHER.to_string(),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+        keys: archived.len(),
+    })
+}
+
+/// Decrypts an archive made by `seal`, returning its keys along with the
+/// salt and cipher it was encrypted under.
+async fn open_archive(
+    archive: &KeystoreArchive,
+    passphrase: String,
+) -> Result<(Vec<ArchivedKey>, Vec<u8>, Aes256GcmSiv), ApiError> {
+    if archive.version != ARCHIVE_VERSION || archive.kdf != ARCHIVE_KDF || archive.cipher != ARCHIVE_CIPHER {
+        return Err(ApiError::bad_request(format!(
+            "Unsupported archive; expected version {ARCHIVE_VERSION} with {ARCHIVE_KDF} and {ARCHIVE_CIPHER}"
+        )));
+    }
+    if archive.iterations == 0 || archive.iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(ApiError::bad_request(format!(
+            "Archive iterations must be between 1 and {MAX_PBKDF2_ITERATIONS}"
+        )));
+    }
+    let decode = |value: &str, field: &str| {
+        STANDARD.decode(value).map_err(|_| ApiError::bad_request(format!("Invalid archive {field}")))
+    };
+    let salt = decode(&archive.salt, "salt")?;
+    let nonce = decode(&archive.nonce, "nonce")?;
+    let ciphertext = decode(&archive.ciphertext, "ciphertext")?;
+    if nonce.len() != 12 {
+        return Err(ApiError::bad_request("Invalid archive nonce"));
+    }
+
+    let iterations = archive.iterations;
+    // Key stretching is deliberately slow, so it stays off the async workers.
+    let (cipher, plaintext, salt) = tokio::task::spawn_blocking(move || {
+        let cipher = archive_cipher(&passphrase, &salt, iterations);
+        let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice());
+        (cipher, plaintext, salt)
+    })
+    .await
+    .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Keystore import was interrupted"))?;
+    // Authenticated decryption cannot tell a wrong passphrase from a tampered archive.
+    let plaintext = plaintext.map_err(|_| {
+        ApiError::bad_request("Wrong passphrase or corrupted archive").with_code("ARCHIVE_DECRYPTION_FAILED")
+    })?;
+    let archived: Vec<ArchivedKey> =
+        serde_json::from_slice(&plaintext).map_err(|_| ApiError::bad_request("Archive contents are not a keystore"))?;
+    Ok((archived, salt, cipher))
+}
+
+/// Rebuilds stored keys from archive entries, failing on the first invalid one.
+fn restore_keys(
+    archived: Vec<ArchivedKey>,
+    pkcs11_tokens: &HashMap<String, Pkcs11Config>,
+) -> Result<Vec<(String, StoredKey)>, ApiError> {
+    let mut restored = Vec::with_capacity(archived.len());
+    for entry in archived {
+        let invalid = || ApiError::bad_request(format!("Archive holds an invalid entry for key {}", entry.id));
+        // Remote keys are restored without contacting their backend, which may be unreachable during recovery.
+        let signer = match (&entry.secret, &entry.backend) {
+            (Some(secret), None) => bs58::decode(secret)
+                .into_vec()
+                .ok()
+                .and_then(|bytes| Keypair::try_from(bytes.as_slice()).ok())
+                .ok_or_else(invalid)?
+                .into(),
+            (None, Some(config)) => {
+                let pubkey = entry.pubkey.parse::<Pubkey>().map_err(|_| invalid())?;
+                restore_remote(config, pubkey, pkcs11_tokens)?
+            }
+            _ => return Err(invalid()),
+        };
+        let key = StoredKey {
+            signer,
+            backend: entry.backend,
+            labels: entry.labels,
+            created_at: entry.created_at,
+            usage_count: entry.usage_count,
+            last_used_at: entry.last_used_at,
+            disabled: entry.disabled,
+        };
+        restored.push((entry.id, key));
+    }
+    Ok(restored)
+}
+
+/// Exports every stored key, disabled ones included, as one encrypted archive.
+/// The archive carries local secrets, so it needs raw secrets enabled.
+pub async fn export_keys(State(state): State<AppState>, Json(payload): Json<ExportKeysRequest>) -> ApiResult {
+    let ExportKeysRequest { passphrase } = payload;
+    if !state.allow_raw_secrets() {
+        return Err(raw_secrets_disabled());
+    }
+    check_passphrase(&passphrase)?;
+
+    let archived = archived_keys(&*stored_keys()?);
+    let salt: [u8; 16] = rand::random();
+    let archive = tokio::task::spawn_blocking(move || {
+        seal(&archive_cipher(&passphrase, &salt, PBKDF2_ITERATIONS), &salt, &archived)
+    })
+    .await
+    .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Keystore export was interrupted"))??;
+
+    Ok(success_response(archive))
+}
+
+/// Restores an archive made by `export_keys`. Ids already in the keystore are
+/// skipped unless `overwrite` is set. Like export, it needs raw secrets enabled.
+pub async fn import_keys(State(state): State<AppState>, Json(payload): Json<ImportKeysRequest>) -> ApiResult {
+    let ImportKeysRequest { passphrase, archive, overwrite } = payload;
+    if !state.allow_raw_secrets() {
+        return Err(raw_secrets_disabled());
+    }
+
+    let (archived, ..) = open_archive(&archive, passphrase).await?;
+    // Every key is checked before any is stored, so a bad archive changes nothing.
+    let restored = restore_keys(archived, state.pkcs11_tokens())?;
+
+    let mut keys = stored_keys()?;
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    for (id, key) in restored {
+        if keys.contains_key(&id) && !overwrite {
+            skipped.push(id);
+        } else {
+            keys.insert(id.clone(), key);
+            imported.push(id);
+        }
+    }
+    changed();
+
+    Ok(success_response(ImportKeysData { imported, skipped }))
+}
+
+/// Reads the keystore file, creating it on the first start, and keeps it
+/// up to date from then on. On failure the keystore stays unavailable.
+pub async fn load(config: Option<&KeystoreFileConfig>, pkcs11_tokens: &HashMap<String, Pkcs11Config>) {
+    let Some(config) = config else {
+        return;
+    };
+    match read_file(config, pkcs11_tokens).await {
+        Ok(file) => {
+            let _ = KEYSTORE_FILE.set(file);
+            tokio::spawn(async {
+                loop {
+                    CHANGED.notified().await;
+                    save().await;
+                }
+            });
+        }
+        Err(err) => {
+            tracing::error!(path = %config.path.display(), error = %err, "Failed to load the keystore");
+            let _ = LOAD_ERROR.set(err);
+        }
+    }
+}
+
+async fn read_file(
+    config: &KeystoreFileConfig,
+    pkcs11_tokens: &HashMap<String, Pkcs11Config>,
+) -> Result<KeystoreFile, String> {
+    check_passphrase(&config.passphrase).map_err(|err| err.message)?;
+    let path = config.path.clone();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let salt: [u8; 16] = rand::random();
+            let passphrase = config.passphrase.clone();
+            let cipher = tokio::task::spawn_blocking(move || archive_cipher(&passphrase, &salt, PBKDF2_ITERATIONS))
+                .await
+                .map_err(|_| "Keystore load was interrupted".to_string())?;
+            let file = KeystoreFile { path, salt, cipher };
+            write_file(&file, &[])?;
+            return Ok(file);
+        }
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    let archive: KeystoreArchive =
+        serde_json::from_str(&text).map_err(|e| format!("{} is not a keystore archive: {e}", path.display()))?;
+    if archive.iterations != PBKDF2_ITERATIONS {
+        return Err(format!("Keystore file must use {PBKDF2_ITERATIONS} iterations"));
+    }
+    let (archived, salt, cipher) =
+        open_archive(&archive, config.passphrase.clone()).await.map_err(|err| err.message)?;
+    // The file keeps the salt it was created with, so one stretched key serves every write.
+    let salt = <[u8; 16]>::try_from(salt).map_err(|_| "Invalid archive salt".to_string())?;
+    let restored = restore_keys(archived, pkcs11_tokens).map_err(|err| err.message)?;
+    keys().lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend(restored);
+    Ok(KeystoreFile { path, salt, cipher })
+}
+
+/// Replaces the keystore file, by way of a private temporary file renamed
+/// over it, so a crash never leaves it half written.
+fn write_file(file: &KeystoreFile, archived: &[ArchivedKey]) -> Result<(), String> {
+    let archive = seal(&file.cipher, &file.salt, archived).map_err(|err| err.message)?;
+    let json = serde_json::to_vec_pretty(&archive).map_err(|e| e.to_string())?;
+    let temporary = file.path.with_extension("tmp");
+    let write = || -> std::io::Result<()> {
+        let mut out = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(&temporary)?;
+        out.write_all(&json)?;
+        out.sync_all()?;
+        fs::rename(&temporary, &file.path)
+    };
+    write().map_err(|e| format!("Failed to write {}: {e}", file.path.display()))
+}
+
+/// Writes the current keys to the keystore file, when there is one.
+pub async fn save() {
+    static SAVING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+    let Some(file) = KEYSTORE_FILE.get() else {
+        return;
+    };
+    let _saving = SAVING.lock().await;
+    let archived = archived_keys(&keys().lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+    let result = tokio::task::spawn_blocking(move || write_file(file, &archived)).await;
+    if let Err(err) = result.map_err(|e| e.to_string()).and_then(|result| result) {
+        tracing::error!(error = %err, "Failed to save the keystore");
+    }
+}
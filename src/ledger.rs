@@ -0,0 +1,302 @@
+//! Ledger hardware wallets as a keystore signing backend.
+//!
+//! The Solana app is driven over the device's raw HID interface
+//! (`/dev/hidrawN`), so this works on Linux hosts with the Ledger attached,
+//! unlocked and the app open. Every signature is confirmed on the device, and
+//! the app only signs transaction messages and Solana offchain messages, not
+//! arbitrary bytes.
+
+use axum::http::StatusCode;
+use solana_offchain_message::OffchainMessage;
+use solana_sdk::{derivation_path::DerivationPath, pubkey::Pubkey, signature::Signature};
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::keypair::parse_derivation_path;
+use crate::signer::{RemoteSigner, SignFuture};
+use crate::utils::ApiError;
+
+/// USB vendor id of Ledger devices, as it appears in a hidraw `uevent`.
+const LEDGER_VENDOR_ID: &str = "00002C97";
+const HID_PACKET_SIZE: usize = 64;
+const HID_CHANNEL: [u8; 2] = [0x01, 0x01];
+const HID_TAG_APDU: u8 = 0x05;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const INS_SIGN_OFFCHAIN_MESSAGE: u8 = 0x07;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+/// Largest APDU payload; longer messages are sent in several chunks.
+const MAX_CHUNK_SIZE: usize = 255;
+const SW_OK: u16 = 0x9000;
+/// How often a non-blocking read or write is retried while the device is busy.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Long enough for someone to review a transaction on the device and confirm it.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(120);
+const PUBKEY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The first account, as Solana wallets derive it.
+const DEFAULT_LEDGER_PATH: &str = "m/44'/501'/0'/0'";
+
+/// The device handles one exchange at a time.
+static DEVICE_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct LedgerSigner {
+    device: Option<String>,
+    path: DerivationPath,
+}
+
+fn ledger_error(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::BAD_GATEWAY, message).with_code("LEDGER_FAILED")
+}
+
+fn status_error(status: u16) -> ApiError {
+    match status {
+        0x6985 => {
+            ApiError::new(StatusCode::FORBIDDEN, "Signing was rejected on the Ledger").with_code("LEDGER_REJECTED")
+        }
+        0x6a80 => ledger_error("The Ledger refused the message; it signs transactions and offchain messages only"),
+        0x6d00 | 0x6e00 => ledger_error("Open the Solana app on the Ledger"),
+        0x5515 | 0x6982 => ledger_error("Unlock the Ledger"),
+        0x6808 => ledger_error("Enable blind signing in the Solana app settings"),
+        _ => ledger_error(format!("Ledger returned status {status:04x}")),
+    }
+}
+
+/// Whether the hidraw node at `node` (under `/sys/class/hidraw`) is a
+/// Ledger's APDU interface: interface 0, as interface 1 is U2F.
+fn is_ledger_node(node: &Path) -> bool {
+    let is_ledger = fs::read_to_string(node.join("device/uevent"))
+        .is_ok_and(|uevent| uevent.lines().any(|line| line.to_uppercase().contains(LEDGER_VENDOR_ID)));
+    let interface = fs::canonicalize(node.join("device"))
+        .ok()
+        .and_then(|device| device.parent().and_then(|parent| parent.file_name()).map(|name| name.to_owned()));
+    is_ledger && interface.is_some_and(|name| name.to_string_lossy().ends_with(".0"))
+}
+
+/// The first hidraw node belonging to a Ledger's APDU interface.
+fn find_device() -> Result<PathBuf, ApiError> {
+    let entries = fs::read_dir("/sys/class/hidraw").map_err(|_| ledger_error("No HID devices available"))?;
+    let mut nodes: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+    nodes.sort();
+    nodes
+        .into_iter()
+        .find(|node| is_ledger_node(node))
+        .and_then(|node| node.file_name().map(|name| Path::new("/dev").join(name)))
+        .ok_or_else(|| ledger_error("No Ledger device found"))
+}
+
+/// The hidraw node name in `device`, which must be `/dev/hidrawN`.
+fn hidraw_name(device: &str) -> Option<&str> {
+    let name = device.strip_prefix("/dev/")?;
+    let number = name.strip_prefix("hidraw")?;
+    (!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())).then_some(name)
+}
+
+/// `device` once it is known to be a Ledger, so that nothing else is ever
+/// opened for writing.
+fn verified_device(device: &str) -> Result<PathBuf, ApiError> {
+    let name = hidraw_name(device).ok_or_else(|| invalid_device(device))?;
+    if !is_ledger_node(&Path::new("/sys/class/hidraw").join(name)) {
+        return Err(ledger_error(format!("{device} is not a Ledger")));
+    }
+    Ok(PathBuf::from(device))
+}
+
+fn invalid_device(device: &str) -> ApiError {
+    ApiError::bad_request(format!("Invalid Ledger device {device}; expected /dev/hidrawN")).with_code("INVALID_DEVICE")
+}
+
+fn timed_out() -> ApiError {
+    ledger_error("Timed out waiting for the Ledger")
+}
+
+/// Writes `packet` whole, retrying while the device is busy until `deadline`.
+fn write_packet(device: &mut File, packet: &[u8], deadline: Instant) -> Result<(), ApiError> {
+    loop {
+        match device.write(packet) {
+            Ok(written) if written == packet.len() => return Ok(()),
+            Ok(_) => return Err(ledger_error("Short write to Ledger")),
+            Err(e) if e.kind() == ErrorKind::WouldBlock && Instant::now() < deadline => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Err(timed_out()),
+            Err(e) => return Err(ledger_error(format!("Failed to write to Ledger: {e}"))),
+        }
+    }
+}
+
+/// Reads one HID report, waiting for it no later than `deadline`.
+fn read_packet(device: &mut File, deadline: Instant) -> Result<[u8; HID_PACKET_SIZE], ApiError> {
+    let mut packet = [0u8; HID_PACKET_SIZE];
+    loop {
+        match device.read(&mut packet) {
+            Ok(HID_PACKET_SIZE) => return Ok(packet),
+            Ok(_) => return Err(ledger_error("Unexpected response framing from Ledger")),
+            Err(e) if e.kind() == ErrorKind::WouldBlock && Instant::now() < deadline => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Err(timed_out()),
+            Err(e) => return Err(ledger_error(format!("Failed to read from Ledger: {e}"))),
+        }
+    }
+}
+
+fn write_apdu(device: &mut File, apdu: &[u8], deadline: Instant) -> Result<(), ApiError> {
+    // The first packet carries the APDU length ahead of the data.
+    let mut data = (apdu.len() as u16).to_be_bytes().to_vec();
+    data.extend_from_slice(apdu);
+    for (sequence, chunk) in data.chunks(HID_PACKET_SIZE - 5).enumerate() {
+        // hidraw expects a leading report id.
+        let mut packet = vec![0x00];
+        packet.extend_from_slice(&HID_CHANNEL);
+        packet.push(HID_TAG_APDU);
+        packet.extend_from_slice(&(sequence as u16).to_be_bytes());
+        packet.extend_from_slice(chunk);
+        packet.resize(HID_PACKET_SIZE + 1, 0);
+        write_packet(device, &packet, deadline)?;
+    }
+    Ok(())
+}
+
+fn read_response(device: &mut File, deadline: Instant) -> Result<Vec<u8>, ApiError> {
+    let mut response = Vec::new();
+    let mut expected = None;
+    let mut sequence: u16 = 0;
+    loop {
+        let packet = read_packet(device, deadline)?;
+        if packet[..2] != HID_CHANNEL || packet[2] != HID_TAG_APDU || packet[3..5] != sequence.to_be_bytes() {
+            return Err(ledger_error("Unexpected response framing from Ledger"));
+        }
+        let mut data = &packet[5..];
+        if expected.is_none() {
+            expected = Some(u16::from_be_bytes([data[0], data[1]]) as usize);
+            data = &data[2..];
+        }
+        response.extend_from_slice(data);
+        let length = expected.unwrap_or_default();
+        if response.len() >= length {
+            response.truncate(length);
+            break;
+        }
+        sequence = sequence.wrapping_add(1);
+    }
+
+    let Some(split) = response.len().checked_sub(2) else {
+        return Err(ledger_error("Truncated response from Ledger"));
+    };
+    let status = u16::from_be_bytes([response[split], response[split + 1]]);
+    if status != SW_OK {
+        return Err(status_error(status));
+    }
+    response.truncate(split);
+    Ok(response)
+}
+
+/// Sends APDUs in order and returns the data of the last response. The
+/// device is opened non-blocking and every wait ends at `deadline`, so an
+/// unanswered exchange gives the device up rather than holding it.
+fn exchange(device: Option<&str>, apdus: &[(u8, u8, u8, Vec<u8>)], deadline: Instant) -> Result<Vec<u8>, ApiError> {
+    let _guard = DEVICE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if Instant::now() >= deadline {
+        return Err(timed_out());
+    }
+    let path = match device {
+        Some(device) => verified_device(device)?,
+        None => find_device()?,
+    };
+    let mut device = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(&path)
+        .map_err(|e| ledger_error(format!("Failed to open {}: {e}", path.display())))?;
+
+    let mut response = Vec::new();
+    for (ins, p1, p2, data) in apdus {
+        let mut apdu = vec![CLA, *ins, *p1, *p2, data.len() as u8];
+        apdu.extend_from_slice(data);
+        write_apdu(&mut device, &apdu, deadline)?;
+        response = read_response(&mut device, deadline)?;
+    }
+    Ok(response)
+}
+
+async fn exchange_with_timeout(
+    device: Option<String>,
+    apdus: Vec<(u8, u8, u8, Vec<u8>)>,
+    timeout: Duration,
+) -> Result<Vec<u8>, ApiError> {
+    let deadline = Instant::now() + timeout;
+    tokio::task::spawn_blocking(move || exchange(device.as_deref(), &apdus, deadline))
+        .await
+        .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Ledger exchange was interrupted"))?
+}
+
+impl LedgerSigner {
+    pub fn new(device: Option<String>, derivation_path: Option<&str>) -> Result<Self, ApiError> {
+        let path = parse_derivation_path(derivation_path.unwrap_or(DEFAULT_LEDGER_PATH))?;
+        if let Some(device) = device.as_deref().filter(|device| hidraw_name(device).is_none()) {
+            return Err(invalid_device(device));
+        }
+        Ok(LedgerSigner { device, path })
+    }
+
+    /// Depth byte followed by each index, big-endian with the hardened bit set.
+    fn serialized_path(&self) -> Vec<u8> {
+        let indexes = self.path.path();
+        let mut bytes = vec![indexes.len() as u8];
+        for index in indexes {
+            bytes.extend_from_slice(&index.to_bits().to_be_bytes());
+        }
+        bytes
+    }
+
+    pub async fn pubkey(&self) -> Result<Pubkey, ApiError> {
+        let apdu = (INS_GET_PUBKEY, P1_NON_CONFIRM, 0, self.serialized_path());
+        let response = exchange_with_timeout(self.device.clone(), vec![apdu], PUBKEY_TIMEOUT).await?;
+        <[u8; 32]>::try_from(response.as_slice())
+            .map(Pubkey::new_from_array)
+            .map_err(|_| ledger_error("Ledger returned a malformed public key"))
+    }
+}
+
+impl RemoteSigner for LedgerSigner {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(async move {
+            // One signer, then its path, then as much of the message as fits.
+            let mut first = vec![1];
+            first.extend_from_slice(&self.serialized_path());
+            let (head, rest) = message.split_at(message.len().min(MAX_CHUNK_SIZE - first.len()));
+            first.extend_from_slice(head);
+
+            let ins = if message.starts_with(OffchainMessage::SIGNING_DOMAIN) {
+                INS_SIGN_OFFCHAIN_MESSAGE
+            } else {
+                INS_SIGN_MESSAGE
+            };
+            let mut apdus = vec![(ins, P1_CONFIRM, 0, first)];
+            let chunks = rest.chunks(MAX_CHUNK_SIZE);
+            apdus.extend(chunks.map(|chunk| (ins, P1_CONFIRM, P2_EXTEND, chunk.to_vec())));
+            // Every chunk but the last announces that more follow.
+            let last = apdus.len() - 1;
+            for (_, _, p2, _) in &mut apdus[..last] {
+                *p2 |= P2_MORE;
+            }
+
+            let response = exchange_with_timeout(self.device.clone(), apdus, CONFIRM_TIMEOUT).await?;
+            Signature::try_from(response.as_slice())
+                .map_err(|_| ledger_error("Ledger returned a malformed signature"))
+        })
+    }
+}
@@ -0,0 +1,83 @@
+use axum::Json;
+use solana_loader_v3_interface::{
+    get_program_data_address,
+    instruction::{close, close_any, set_upgrade_authority, set_upgrade_authority_checked, upgrade},
+};
+
+use crate::types::{ProgramCloseRequest, ProgramSetAuthorityRequest, ProgramUpgradeRequest};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub async fn set_program_authority(Json(payload): Json<ProgramSetAuthorityRequest>) -> ApiResult {
+    let ProgramSetAuthorityRequest { program, authority, new_authority, make_immutable, checked } = payload;
+
+    if program.is_empty() || authority.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: program or authority"));
+    }
+
+    let program_pubkey = parse_pubkey(&program, "program")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let new_authority_pubkey = parse_optional_pubkey(new_authority.as_deref(), "new authority")?;
+
+    // Dropping the authority is irreversible, so it has to be asked for explicitly.
+    let ix = match (new_authority_pubkey, make_immutable) {
+        (Some(_), true) => {
+            return Err(ApiError::bad_request("newAuthority cannot be combined with makeImmutable"));
+        }
+        (None, false) => {
+            return Err(ApiError::bad_request(
+                "Missing required fields: newAuthority (or makeImmutable to remove the authority)",
+            ));
+        }
+        (Some(new_authority), false) if checked => {
+            set_upgrade_authority_checked(&program_pubkey, &authority_pubkey, &new_authority)
+        }
+        (new_authority, _) => set_upgrade_authority(&program_pubkey, &authority_pubkey, new_authority.as_ref()),
+    };
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn upgrade_program(Json(payload): Json<ProgramUpgradeRequest>) -> ApiResult {
+    let ProgramUpgradeRequest { program, buffer, authority, spill } = payload;
+
+    if program.is_empty() || buffer.is_empty() || authority.is_empty() || spill.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: program, buffer, authority, or spill",
+        ));
+    }
+
+    let program_pubkey = parse_pubkey(&program, "program")?;
+    let buffer_pubkey = parse_pubkey(&buffer, "buffer")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let spill_pubkey = parse_pubkey(&spill, "spill")?;
+
+    let ix = upgrade(&program_pubkey, &buffer_pubkey, &authority_pubkey, &spill_pubkey);
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn close_program(Json(payload): Json<ProgramCloseRequest>) -> ApiResult {
+    let ProgramCloseRequest { program, buffer, recipient, authority } = payload;
+
+    if recipient.is_empty() || authority.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: recipient or authority"));
+    }
+
+    let program_pubkey = parse_optional_pubkey(program.as_deref(), "program")?;
+    let buffer_pubkey = parse_optional_pubkey(buffer.as_deref(), "buffer")?;
+    let recipient_pubkey = parse_pubkey(&recipient, "recipient")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+
+    let ix = match (program_pubkey, buffer_pubkey) {
+        (Some(program_pubkey), None) => close_any(
+            &get_program_data_address(&program_pubkey),
+            &recipient_pubkey,
+            Some(&authority_pubkey),
+            Some(&program_pubkey),
+        ),
+        (None, Some(buffer_pubkey)) => close(&buffer_pubkey, &recipient_pubkey, &authority_pubkey),
+        _ => return Err(ApiError::bad_request("Exactly one of program or buffer must be provided")),
+    };
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
@@ -0,0 +1,155 @@
+//! Structured logging and distributed tracing.
+//!
+//! Every request gets an `x-request-id` (a client-supplied one is kept) that is
+//! echoed in the response and attached to its log lines, along with the method,
+//! path, status and latency. `RUST_LOG` filters the output, e.g.
+//! `RUST_LOG=debug` or `RUST_LOG=info,tower_http=warn`.
+//!
+//! With an OTLP endpoint configured, request spans and the RPC calls made
+//! under them are also exported as OpenTelemetry traces over OTLP/HTTP. A W3C
+//! `traceparent` header on the request makes its span a child of the caller's.
+//! The standard `OTEL_*` variables (service name, headers, timeouts) apply.
+
+use axum::{
+    extract::MatchedPath,
+    http::{HeaderMap, Request, Response},
+};
+use opentelemetry::{global, propagation::Extractor, trace::TracerProvider};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tower_http::{
+    request_id::RequestId,
+    trace::{DefaultOnResponse, MakeSpan, OnResponse},
+    LatencyUnit,
+};
+use tracing::{field::Empty, Level, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use std::{io::IsTerminal, time::Duration};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(format: &str) -> Option<LogFormat> {
+        match format.to_ascii_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The tracer provider exporting to `endpoint`, a full OTLP/HTTP traces URL.
+fn tracer_provider(endpoint: &str) -> Result<SdkTracerProvider, String> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| format!("Invalid OTLP exporter configuration: {e}"))?;
+    let mut resource = Resource::builder();
+    if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+        resource = resource.with_service_name(env!("CARGO_PKG_NAME"));
+    }
+    Ok(SdkTracerProvider::builder().with_batch_exporter(exporter).with_resource(resource.build()).build())
+}
+
+/// Installs the global subscriber, exporting traces to `otlp_endpoint` when
+/// set. The returned provider must be shut down on exit to flush its spans.
+pub fn init(format: LogFormat, otlp_endpoint: Option<&str>) -> Result<Option<SdkTracerProvider>, String> {
+    let provider = otlp_endpoint.map(tracer_provider).transpose()?;
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt = tracing_subscriber::fmt::layer().with_ansi(std::io::stdout().is_terminal());
+    let fmt = match format {
+        LogFormat::Text => fmt.boxed(),
+        LogFormat::Json => fmt.json().flatten_event(true).with_span_list(false).boxed(),
+    };
+    let otel = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME"))));
+    tracing_subscriber::registry().with(fmt).with(otel).with(filter).init();
+    Ok(provider)
+}
+
+/// Flushes spans that are still buffered.
+pub fn shutdown(provider: Option<SdkTracerProvider>, timeout: Duration) {
+    if let Some(Err(err)) = provider.map(|provider| provider.shutdown_with_timeout(timeout)) {
+        tracing::warn!("Failed to flush traces: {err}");
+    }
+}
+
+/// Reads propagation headers for the OpenTelemetry propagator.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Request span carrying the fields every log line of the request shares,
+/// continuing the caller's trace when the request carries one.
+#[derive(Clone, Copy)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .and_then(|id| id.header_value().to_str().ok())
+            .unwrap_or_default();
+        // Span names use the route template so `/balance/{pubkey}` is one operation, not one per
+        // address; requests that match no route are named by their method alone.
+        let route = request.extensions().get::<MatchedPath>().map(MatchedPath::as_str);
+        let name = match route {
+            Some(route) => format!("{} {route}", request.method()),
+            None => request.method().to_string(),
+        };
+        let span = tracing::info_span!(
+            "request",
+            otel.name = %name,
+            otel.kind = "server",
+            otel.status_code = Empty,
+            method = %request.method(),
+            path = %request.uri().path(),
+            http.route = route,
+            request_id = %request_id,
+            http.response.status_code = Empty,
+        );
+        let headers = HeaderExtractor(request.headers());
+        let parent = global::get_text_map_propagator(|propagator| propagator.extract(&headers));
+        // Only fails when no OpenTelemetry layer is installed, and then there is nothing to link.
+        let _ = span.set_parent(parent);
+        span
+    }
+}
+
+/// Records the status on the request span, then logs the response.
+#[derive(Clone, Copy)]
+pub struct ResponseLog;
+
+impl<B> OnResponse<B> for ResponseLog {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        span.record("http.response.status_code", response.status().as_u16());
+        if response.status().is_server_error() {
+            span.record("otel.status_code", "ERROR");
+        }
+        DefaultOnResponse::new()
+            .level(Level::INFO)
+            .latency_unit(LatencyUnit::Millis)
+            .on_response(response, latency, span);
+    }
+}
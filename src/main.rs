@@ -1,68 +1,261 @@
+pub mod accounts;
+pub mod airdrop;
+pub mod amount;
+pub mod auth;
+pub mod bubblegum;
+pub mod candy_machine;
+pub mod challenge;
+pub mod cluster;
+pub mod compile;
+pub mod compute_budget;
+pub mod config;
+pub mod cursor;
+pub mod fee_payer;
+pub mod fees;
+pub mod health;
+pub mod holders;
+pub mod jito;
+pub mod keypair;
+pub mod keystore;
+pub mod ledger;
+pub mod loader;
+pub mod logging;
+pub mod memo;
+pub mod message;
+pub mod nft;
+pub mod precompiles;
+pub mod rate_limit;
+pub mod redact;
+pub mod reporting;
+pub mod rpc;
+pub mod shamir;
+pub mod shutdown;
+pub mod signer;
+pub mod siws;
+pub mod stake;
+pub mod stake_pool;
+pub mod state;
+pub mod token;
+pub mod token_swap;
+pub mod tracker;
+pub mod transaction;
+pub mod tx;
 pub mod types;
+pub mod utils;
+pub mod validate;
+pub mod vanity;
+pub mod vote;
 
 use axum::{
-    http::StatusCode, response::{IntoResponse}, routing::{get, post}, Json, Router
+    extract::State, http::StatusCode, middleware, response::{IntoResponse}, routing::{get, post}, Json, Router
+};
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use solana_sdk::{pubkey::Pubkey, system_instruction::transfer};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
 };
-use solana_keypair::keypair_from_seed;
-use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer, system_instruction::transfer};
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::instruction::{initialize_mint, mint_to, transfer as transfer_token};
 use spl_token::ID as TOKEN_PROGRAM_ID;
 
-use std::{net::SocketAddr, str::FromStr};
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
 use serde_json::{self, json};
 
-use crate::types::{AccountMetaResponse, CreateTokenRequest, SendSOLRequest, SendTokenRequest, SignMsgRequest, TokenAccount, TokenCreateErrorResponse, TokenCreateSuccessResponse, TokenData, TokenMintRequest, VerifyMsgRequest};
+use crate::config::Config;
+use crate::state::AppState;
+use crate::types::{AccountMetaResponse, CreateTokenRequest, SendSOLRequest, SendTokenRequest, SignMsgRequest, TokenCreateErrorResponse, TokenCreateSuccessResponse, TokenData, TokenMintRequest};
+use crate::utils::{encode_signature, instruction_to_data, message_signing_bytes};
 
 #[tokio::main]
 async fn main() {
+    let config = Arc::new(Config::load().unwrap_or_else(|err| panic!("Invalid configuration: {err}")));
+    let tracer_provider = logging::init(config.log_format, config.otlp_traces_endpoint.as_deref())
+        .unwrap_or_else(|err| panic!("Invalid configuration: {err}"));
+    redact::install_panic_hook();
+    let error_reporting = reporting::init(config.sentry_dsn.as_deref())
+        .unwrap_or_else(|err| panic!("Invalid configuration: {err}"));
+    let state = AppState::from_config(&config);
+    let fee_payers = fee_payer::load_fee_payers(&config)
+        .await
+        .unwrap_or_else(|err| panic!("Invalid fee payer configuration: {err}"));
+    let state = state.with_fee_payers(fee_payers);
+    keystore::load(config.keystore_file.as_ref(), state.pkcs11_tokens()).await;
+    let rate_limits = Arc::new(rate_limit::RateLimits::from_config(&config.rate_limit));
+    let api_keys = Arc::new(auth::ApiKeys::from_config(&config.auth));
+
     let app = Router::new()
         .route("/", get(root))
-        .route("/keypair", post(generate_keypair))
+        .route("/health", get(health::health))
+        .route("/ready", get(health::ready))
+        .route("/auth/key", get(auth::key_info))
+        .route("/keypair", post(keypair::generate_keypair))
+        .route("/keypair/derive", post(keypair::derive_keypair))
+        .route("/keypair/from-seed", post(keypair::keypair_from_seed_bytes))
+        .route("/keypair/vanity", post(vanity::start_vanity))
+        .route("/keypair/vanity/{job}", get(vanity::vanity_job))
+        .route("/derive/pda", post(keypair::derive_pda))
+        .route("/validate/pubkey", post(validate::validate_pubkey))
+        .route("/keys", post(keystore::create_key).get(keystore::list_keys))
+        .route("/keys/export", post(keystore::export_keys))
+        .route("/keys/import", post(keystore::import_keys))
+        .route("/keys/import-wallet", post(keystore::import_wallet_key))
+        .route("/keys/split", post(shamir::split_key))
+        .route("/keys/reconstruct", post(shamir::reconstruct_key))
+        .route("/keys/{id}", get(keystore::get_key).patch(keystore::update_key))
+        .route("/keys/{id}/export-wallet", post(keystore::export_wallet_key))
         .route("/token/create", post(token_create))
         .route("/token/mint", post(token_mint))
         .route("/message/sign", post(sign_msg))
-        .route("/message/verify", post(verify_msg))
+        .route("/message/sign/batch", post(message::sign_msg_batch))
+        .route("/message/verify", post(message::verify_msg))
+        .route("/message/challenge", get(challenge::issue_challenge))
+        .route("/siws/input", post(siws::siws_input))
+        .route("/siws/verify", post(siws::siws_verify))
         .route("/send/sol", post(send_sol))
-        .route("/send/token", post(send_token));
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    println!("Listening on http://{}", addr);
+        .route("/send/token", post(send_token))
+        .route("/tx/build", post(tx::tx_build))
+        .route("/fee-payers", get(fee_payer::list_fee_payers))
+        .route("/tx/sign", post(tx::tx_sign))
+        .route("/tx/signers", post(tx::tx_signers))
+        .route("/tx/decode", post(tx::tx_decode))
+        .route("/tx/submit", post(tx::tx_submit))
+        .route("/tx/tracked/{id}", get(tracker::tracked_transaction))
+        .route("/tx/fee", post(tx::tx_fee))
+        .route("/tx/estimate-cu", post(tx::tx_estimate_cu))
+        .route("/tx/status/{signature}", get(tx::tx_status))
+        .route("/tx/pack", post(tx::tx_pack))
+        .route("/tx/verify", post(tx::tx_verify))
+        .route("/blockhash", get(tx::latest_blockhash))
+        .route("/transaction/{signature}", get(transaction::get_transaction))
+        .route("/history/{pubkey}", get(transaction::address_history))
+        .route("/block/{slot}", get(transaction::get_block))
+        .route("/cluster/info", get(cluster::cluster_info))
+        .route("/validators", get(cluster::validators))
+        .route("/rewards/{pubkey}", get(stake::inflation_reward))
+        .route("/tx/bundle", post(jito::submit_bundle))
+        .route("/jito/tip", post(jito::tip))
+        .route("/fees/priority", get(fees::priority_fees))
+        .route("/memo", post(memo::memo))
+        .route("/compute-budget", post(compute_budget::compute_budget))
+        .route("/precompile/ed25519", post(precompiles::ed25519_instruction))
+        .route("/precompile/secp256k1", post(precompiles::secp256k1_instruction))
+        .route("/program/set-upgrade-authority", post(loader::set_program_authority))
+        .route("/program/upgrade", post(loader::upgrade_program))
+        .route("/program/close", post(loader::close_program))
+        .route("/stake/deactivate", post(stake::stake_deactivate))
+        .route("/stake/withdraw", post(stake::stake_withdraw))
+        .route("/stake/split", post(stake::stake_split))
+        .route("/stake/merge", post(stake::stake_merge))
+        .route("/stake/authorize", post(stake::stake_authorize))
+        .route("/stake/lockup", post(stake::stake_set_lockup))
+        .route("/stake/accounts/{pubkey}", get(stake::stake_accounts))
+        .route("/vote/withdraw", post(vote::vote_withdraw))
+        .route("/vote/authorize", post(vote::vote_authorize))
+        .route("/stake-pool/deposit-sol", post(stake_pool::deposit_sol))
+        .route("/stake-pool/withdraw-sol", post(stake_pool::withdraw_sol))
+        .route("/stake-pool/deposit-stake", post(stake_pool::deposit_stake))
+        .route("/stake-pool/withdraw-stake", post(stake_pool::withdraw_stake))
+        .route("/token-swap/initialize", post(token_swap::initialize))
+        .route("/token-swap/swap", post(token_swap::swap))
+        .route("/token-swap/deposit", post(token_swap::deposit))
+        .route("/token-swap/withdraw", post(token_swap::withdraw))
+        .route("/nft/mint", post(nft::nft_mint))
+        .route("/nft/transfer", post(nft::nft_transfer))
+        .route("/nft/burn", post(nft::nft_burn))
+        .route("/nft/metadata/create", post(nft::metadata_create))
+        .route("/nft/by-owner/{pubkey}", get(nft::nfts_by_owner))
+        .route("/candy-machine/mint", post(candy_machine::mint))
+        .route("/cnft/tree/create", post(bubblegum::create_tree))
+        .route("/cnft/mint", post(bubblegum::mint))
+        .route("/cnft/transfer", post(bubblegum::transfer))
+        .route("/accounts/batch", post(accounts::accounts_batch))
+        .route("/accounts/multiple", post(accounts::accounts_multiple))
+        .route("/program-accounts", post(accounts::program_accounts))
+        .route("/account/{pubkey}", get(accounts::account_info))
+        .route("/balance/{pubkey}", get(accounts::balance))
+        .route("/airdrop", post(airdrop::airdrop))
+        .route("/token/mint/{mint}/audit", get(token::mint_audit))
+        .route("/token/metadata/{mint}", get(token::token_metadata))
+        .route("/token/balance", get(token::token_balance))
+        .route("/token/accounts/{owner}", get(token::token_accounts))
+        .route("/token/delegated/{delegate}", get(token::token_accounts_by_delegate))
+        .route("/token/supply/{mint}", get(token::token_supply))
+        .route("/token/largest-accounts/{mint}", get(token::largest_accounts))
+        .route("/token/holders/{mint}", get(holders::token_holders))
+        .route("/mint/{pubkey}", get(token::mint_info))
+        .route("/token-account/{pubkey}", get(token::token_account_info))
+        .route("/token-account/{pubkey}/balance-at", get(transaction::token_balance_at))
+        .layer(middleware::from_fn(compute_budget::compute_budget_option))
+        .layer(middleware::from_fn_with_state(state.clone(), compile::compile_option))
+        .layer(middleware::from_fn_with_state(state.clone(), amount::amount_format))
+        .layer(middleware::from_fn(state::commitment_option))
+        .layer(middleware::from_fn_with_state(state.clone(), state::cluster_option))
+        .layer(middleware::from_fn(redact::redact_errors))
+        .layer(middleware::from_fn_with_state(config.clone(), config::feature_gate))
+        .layer(middleware::from_fn_with_state(config.clone(), config::request_timeout))
+        .layer(middleware::from_fn(reporting::report_errors))
+        .layer(middleware::from_fn_with_state(rate_limits, rate_limit::rate_limit))
+        .layer(middleware::from_fn_with_state(api_keys, auth::authenticate))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(logging::RequestSpan)
+                .on_request(())
+                .on_response(logging::ResponseLog),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(state);
+
+    let addr = config.bind;
+    let shutdown_timeout = config.shutdown_timeout;
+    if let Some(tls) = &config.tls {
+        // Only ring is compiled in, so this cannot clash with another provider.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let tls_config = RustlsConfig::from_pem_file(&tls.cert, &tls.key)
+            .await
+            .unwrap_or_else(|err| panic!("Invalid TLS configuration: {err}"));
+        let handle = Handle::new();
+        tokio::spawn({
+            let handle = handle.clone();
+            async move {
+                shutdown::signal().await;
+                handle.graceful_shutdown(Some(shutdown_timeout));
+            }
+        });
+        tracing::info!("Listening on https://{}", addr);
+        axum_server::bind_rustls(addr, tls_config)
+            .handle(handle)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap();
+    } else {
+        tracing::info!("Listening on http://{}", addr);
+
+        let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+        let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(shutdown::signal());
+        tokio::select! {
+            result = server => result.unwrap(),
+            _ = shutdown::drain_expired(shutdown_timeout) => tracing::warn!("Dropping requests still in flight"),
+        }
+    }
 
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let pending = tracker::drain(shutdown::deadline(shutdown_timeout)).await;
+    if !pending.is_empty() {
+        tracing::warn!(pending = %pending.join(", "), "Abandoning tracked transactions still pending");
+    }
+    keystore::save().await;
+    reporting::shutdown(error_reporting, shutdown_timeout);
+    logging::shutdown(tracer_provider, shutdown_timeout);
 }
 
 async fn root() -> &'static str {
     "gm Dharmin!"
 }
 
-async fn generate_keypair() -> impl IntoResponse {
-    let keypair = solana_sdk::signature::Keypair::new();
-    let pub_key = keypair.pubkey();
-    let secret_key = keypair.to_base58_string();
-
-    if secret_key.is_empty() {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "success": false,
-                "error": "Failed to generate keypair"
-            })),
-        );
-    } else {
-        return (StatusCode::OK, Json(serde_json::json!({
-            "success": true,
-            "data": {
-                "pubkey": pub_key.to_string(),
-                "secret": secret_key
-            }
-        })));
-    }
-}
-
 async fn token_create(Json(payload): Json<CreateTokenRequest>) -> impl IntoResponse {
-    if payload.mintAuthority.is_none() || payload.mint.is_none() {
+    if payload.mint_authority.is_none() || payload.mint.is_none() {
         let error_response = TokenCreateErrorResponse {
             success: false,
             error: "Missing required fields: mintAuthority or mint".to_string(),
@@ -70,9 +263,9 @@ async fn token_create(Json(payload): Json<CreateTokenRequest>) -> impl IntoRespo
         return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
     }
     
-    let CreateTokenRequest { mintAuthority, mint, decimals } = payload;
+    let CreateTokenRequest { mint_authority, mint, decimals } = payload;
 
-    let mintAuthority = mintAuthority.unwrap();
+    let mint_authority = mint_authority.unwrap();
     let mint = mint.unwrap();
 
     let mint_pubkey = match Pubkey::from_str(&mint) {
@@ -86,7 +279,7 @@ async fn token_create(Json(payload): Json<CreateTokenRequest>) -> impl IntoRespo
         }
     };
     
-    let mint_authority_pubkey = match Pubkey::from_str(&mintAuthority) {
+    let mint_authority_pubkey = match Pubkey::from_str(&mint_authority) {
         Ok(key) => key,
         Err(_) => {
             let error_response = TokenCreateErrorResponse {
@@ -124,14 +317,14 @@ async fn token_create(Json(payload): Json<CreateTokenRequest>) -> impl IntoRespo
                 },
             };
 
-            return (StatusCode::OK, Json(response)).into_response()
+            (StatusCode::OK, Json(response)).into_response()
         },
         Err(_) => {
             let error_response = TokenCreateErrorResponse {
                 success: false,
                 error: String::from("Failed to create mint instruction"),
             };
-            return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
+            (StatusCode::BAD_REQUEST, Json(error_response)).into_response()
         }
     }
     
@@ -217,7 +410,7 @@ async fn token_mint(Json(payload): Json<TokenMintRequest>) -> impl IntoResponse
                     instruction_data: bs58::encode(&ix.data).into_string(),
                 },
             };
-            return (StatusCode::OK, Json(response)).into_response();
+            (StatusCode::OK, Json(response)).into_response()
         }
         Err(_) => {
             let error_response = TokenCreateErrorResponse {
@@ -225,47 +418,42 @@ async fn token_mint(Json(payload): Json<TokenMintRequest>) -> impl IntoResponse
                 error: String::from("Failed to create mint instruction"),
             };
 
-            return (StatusCode::OK, Json(error_response)).into_response();
+            (StatusCode::OK, Json(error_response)).into_response()
         }
     }
 }
 
-async fn sign_msg(Json(payload): Json<SignMsgRequest>) -> impl IntoResponse {
-    let SignMsgRequest { message, secret } = payload;
+async fn sign_msg(State(state): State<AppState>, Json(payload): Json<SignMsgRequest>) -> impl IntoResponse {
+    let SignMsgRequest { message, encoding, standard, format, secret, key_id } = payload;
 
-    if message.is_empty() || secret.is_empty() {
+    let secret = secret.filter(|secret| !secret.is_empty());
+    let key_id = key_id.filter(|key_id| !key_id.is_empty());
+    if message.is_empty() || (secret.is_none() && key_id.is_none()) {
         return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
             "success": false,
             "error": "Missing required fields"
         }))).into_response();
     }
 
-    let secret_bytes = match bs58::decode(secret).into_vec() {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
-                "success": false,
-                "error": "Invalid secret key format"
-            }))).into_response();
-        }
+    let signer = match keystore::resolve_signer(&state, secret.as_ref(), key_id.as_deref()) {
+        Ok(signer) => signer,
+        Err(err) => return err.into_response(),
     };
-    let keypair = match keypair_from_seed(&secret_bytes) {
-        Ok(kp) => kp,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
-                "success": false,
-                "error": "Failed to create keypair from seed"
-            }))).into_response();
-        }
+    let message_bytes = match message_signing_bytes(&message, encoding, standard) {
+        Ok(bytes) => bytes,
+        Err(err) => return err.into_response(),
     };
 
-    let signature = keypair.sign_message(message.as_bytes());
+    let signature = match signer.sign(&message_bytes).await {
+        Ok(signature) => signature,
+        Err(err) => return err.into_response(),
+    };
 
     let response = serde_json::json!({
         "success": true,
         "data": {
-            "signature": signature.to_string(),
-            "pubkey": keypair.pubkey().to_string(),
+            "signature": encode_signature(&signature, format),
+            "pubkey": signer.pubkey().to_string(),
             "message": message
         }
     });
@@ -273,63 +461,8 @@ async fn sign_msg(Json(payload): Json<SignMsgRequest>) -> impl IntoResponse {
     (StatusCode::OK, Json(response)).into_response()
 }
 
-async fn verify_msg(Json(payload): Json<VerifyMsgRequest>) -> impl IntoResponse {
-    let VerifyMsgRequest { message, signature, pubkey } = payload;
-
-    if message.is_empty() || signature.is_empty() || pubkey.is_empty() {
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
-            "success": false,
-            "error": "Missing required fields"
-        }))).into_response();
-    }
-    let public_key = Pubkey::from_str(&pubkey).unwrap();
-
-    let signature_bytes = match bs58::decode(signature.as_str()).into_vec() {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
-                "success": false,
-                "error": "Invalid signature format"
-            }))).into_response();
-        }
-    };
-
-    if signature_bytes.len() != 64 {
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
-            "success": false,
-            "error": "Signature must be 64 bytes long"
-        }))).into_response();
-    }
-
-    let signature_array: [u8; 64] = signature_bytes.try_into().unwrap();
-
-    let signature = Signature::from(signature_array);
-
-    let is_valid_signature = signature.verify(&public_key.to_bytes(), message.as_bytes());
-
-    if !is_valid_signature {
-        let error_response = json!({
-            "success": false,
-            "error": "Invalid signature"
-        });
-        
-        return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
-    }
-    
-    let response = json!({
-        "success": true,
-        "data": {
-            "valid": is_valid_signature,
-            "pubkey": &pubkey,
-            "message": message
-        }
-    });
-
-    return (StatusCode::OK, Json(response)).into_response()
-}
-
 async fn send_sol(Json(payload): Json<SendSOLRequest>) -> impl IntoResponse {
-    let SendSOLRequest { from, to, lamports } = payload;
+    let SendSOLRequest { from, to, lamports, memo } = payload;
 
     if lamports == 0 {
         return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
@@ -364,18 +497,18 @@ async fn send_sol(Json(payload): Json<SendSOLRequest>) -> impl IntoResponse {
         lamports,
     );
 
-    let response = json!({
+    let mut response = json!({
         "success": true,
-        "data": {
-            "program_id": transfer_ix.program_id.to_string(),
-            "accounts": [
-                transfer_ix.accounts[0].pubkey.to_string(),
-                transfer_ix.accounts[1].pubkey.to_string()
-            ],
-            "instruction_data": bs58::encode(&transfer_ix.data).into_string(),
-        }
+        "data": instruction_to_data(&transfer_ix),
     });
 
+    if let Some(memo) = memo {
+        match memo::memo_instruction(&memo, &[&from_pubkey]) {
+            Ok(memo_ix) => response["data"]["memo_instruction"] = json!(instruction_to_data(&memo_ix)),
+            Err(err) => return err.into_response(),
+        }
+    }
+
     (StatusCode::OK, Json(response)).into_response()
 }
 
@@ -387,7 +520,7 @@ async fn send_token(Json(payload): Json<SendTokenRequest>) -> impl IntoResponse
         }))).into_response();
     }
 
-    let SendTokenRequest { destination, mint, owner, amount } = payload;
+    let SendTokenRequest { destination, mint, owner, amount, memo } = payload;
 
     let destination = destination.unwrap();
     let mint = mint.unwrap();
@@ -439,36 +572,25 @@ async fn send_token(Json(payload): Json<SendTokenRequest>) -> impl IntoResponse
     );
     match transfer_ix {
         Ok(ix) => {
-            let accounts = vec![
-                TokenAccount {
-                    pubkey: owner_pubkey.to_string(),
-                    isSigner: false,
-                },
-                TokenAccount {
-                    pubkey: destination_token_account.to_string(),
-                    isSigner: false,
-                },
-                TokenAccount {
-                    pubkey: owner_pubkey.to_string(),
-                    isSigner: false,
-                },
-           ];
-
-            let response = json!({
+            let mut response = json!({
                 "success": true,
-                "data": {
-                    "program_id": ix.program_id.to_string(),
-                    "accounts": accounts,
-                    "instruction_data": bs58::encode(&ix.data).into_string(),
-                }
+                "data": instruction_to_data(&ix),
             });
-            return (StatusCode::OK, Json(response)).into_response();
+
+            if let Some(memo) = memo {
+                match memo::memo_instruction(&memo, &[&owner_pubkey]) {
+                    Ok(memo_ix) => response["data"]["memo_instruction"] = json!(instruction_to_data(&memo_ix)),
+                    Err(err) => return err.into_response(),
+                }
+            }
+
+            (StatusCode::OK, Json(response)).into_response()
         },
         Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            (StatusCode::BAD_REQUEST, Json(serde_json::json!({
                 "success": false,
                 "error": String::from("Failed to create transfer instruction: ")
-            }))).into_response();
+            }))).into_response()
         }
-    };
+    }
 }
@@ -0,0 +1,34 @@
+use axum::Json;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::types::MemoRequest;
+use crate::utils::{instruction_to_data, parse_pubkey, success_response, ApiError, ApiResult};
+
+/// Largest memo that still fits in a single-signer transaction alongside a transfer.
+pub const MAX_MEMO_LEN: usize = 566;
+
+pub fn memo_instruction(memo: &str, signers: &[&Pubkey]) -> Result<Instruction, ApiError> {
+    if memo.is_empty() {
+        return Err(ApiError::bad_request("Memo must not be empty"));
+    }
+
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(ApiError::bad_request(format!("Memo must be at most {} bytes", MAX_MEMO_LEN)));
+    }
+
+    Ok(spl_memo::build_memo(memo.as_bytes(), signers))
+}
+
+pub async fn memo(Json(payload): Json<MemoRequest>) -> ApiResult {
+    let MemoRequest { memo, signers } = payload;
+
+    let signer_pubkeys = signers
+        .iter()
+        .map(|signer| parse_pubkey(signer, "signer"))
+        .collect::<Result<Vec<Pubkey>, ApiError>>()?;
+    let signer_refs: Vec<&Pubkey> = signer_pubkeys.iter().collect();
+
+    let ix = memo_instruction(&memo, &signer_refs)?;
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
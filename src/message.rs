@@ -0,0 +1,342 @@
+//! Message verification and bulk message signing.
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    Json,
+};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use std::str::FromStr;
+
+use crate::challenge::{challenge_nonce, consume_challenge};
+use crate::keystore::resolve_signer;
+use crate::state::AppState;
+use crate::types::{
+    BatchMessage, KeyedMessage, MessageEncoding, MessageStandard, SignMsgBatchData, SignMsgBatchRequest,
+    SignedMessageData, VerifyMode, VerifyMsgData, VerifyMsgRequest,
+};
+use crate::utils::{
+    decode_message, decode_signature, encode_signature, message_signing_bytes, success_response, ApiError, ApiResult,
+};
+
+const MAX_BATCH_MESSAGES: usize = 1000;
+
+/// Signs every message, each with its own key or the batch's. Any failure
+/// fails the whole batch and names the offending message's index.
+pub async fn sign_msg_batch(State(state): State<AppState>, Json(payload): Json<SignMsgBatchRequest>) -> ApiResult {
+    let SignMsgBatchRequest { messages, encoding, standard, format, secret, key_id } = payload;
+
+    if messages.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: messages"));
+    }
+    if messages.len() > MAX_BATCH_MESSAGES {
+        return Err(ApiError::bad_request(format!("At most {MAX_BATCH_MESSAGES} messages can be signed at once")));
+    }
+
+    let mut signatures = Vec::with_capacity(messages.len());
+    for (index, item) in messages.iter().enumerate() {
+        let (message, item_secret, item_key_id) = match item {
+            BatchMessage::Text(message) => (message, None, None),
+            BatchMessage::Keyed(KeyedMessage { message, secret, key_id }) => {
+                (message, secret.as_ref(), key_id.as_deref())
+            }
+        };
+        let at_index = |err: ApiError| {
+            let message = format!("messages[{index}]: {}", err.message);
+            ApiError { message, ..err }.with_details(json!({ "index": index }))
+        };
+        if message.is_empty() {
+            return Err(at_index(ApiError::bad_request("Missing required fields: message")));
+        }
+
+        // Resolving per message keeps keystore usage counts at one per signature.
+        let signer = if item_secret.is_some() || item_key_id.is_some() {
+            resolve_signer(&state, item_secret, item_key_id)
+        } else {
+            resolve_signer(&state, secret.as_ref(), key_id.as_deref())
+        }
+        .map_err(at_index)?;
+        let bytes = message_signing_bytes(message, encoding, standard).map_err(at_index)?;
+        let signature = signer.sign(&bytes).await.map_err(at_index)?;
+
+        signatures.push(SignedMessageData {
+            pubkey: signer.pubkey().to_string(),
+            signature: encode_signature(&signature, format),
+        });
+    }
+
+    Ok(success_response(SignMsgBatchData { signatures }))
+}
+
+/// The bytes a wallet may have signed for the message, with the name of each
+/// convention. Conventions the message cannot take are left out.
+fn wallet_candidates(message: &str, encoding: MessageEncoding) -> Result<Vec<(&'static str, Vec<u8>)>, ApiError> {
+    let raw = decode_message(message, encoding)?;
+    let mut candidates = Vec::new();
+    if let Ok(offchain) = message_signing_bytes(message, encoding, MessageStandard::Offchain) {
+        candidates.push(("offchain", offchain));
+    }
+    // Frontends that pass `JSON.stringify(message)` to the encoder sign the quoted text.
+    if let Ok(text) = std::str::from_utf8(&raw) {
+        candidates.push(("json", serde_json::to_string(text).unwrap_or_default().into_bytes()));
+    }
+    candidates.insert(0, ("raw", raw));
+    Ok(candidates)
+}
+
+/// The convention under which `signature` is `pubkey`'s signature over the
+/// message, if any. Only input that cannot be checked at all is an error.
+fn verify_message(request: &VerifyMsgRequest) -> Result<Option<&'static str>, ApiError> {
+    let VerifyMsgRequest { message, encoding, standard, mode, signature, pubkey, require_challenge } = request;
+
+    if message.is_empty() || signature.is_empty() || pubkey.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: message, signature or pubkey"));
+    }
+    let pubkey = Pubkey::from_str(pubkey.trim())
+        .map_err(|_| ApiError::bad_request("Invalid public key format").with_code("INVALID_PUBKEY"))?;
+    let signature = decode_signature(signature)?;
+    let text = decode_message(message, *encoding)?;
+    let nonce = if *require_challenge { Some(challenge_nonce(&text)?) } else { None };
+
+    let candidates = match mode {
+        VerifyMode::Exact => {
+            let name = match standard {
+                MessageStandard::Raw => "raw",
+                MessageStandard::Offchain => "offchain",
+            };
+            vec![(name, message_signing_bytes(message, *encoding, *standard)?)]
+        }
+        VerifyMode::Wallet => wallet_candidates(message, *encoding)?,
+    };
+    let matched = candidates
+        .into_iter()
+        .find(|(_, bytes)| signature.verify(pubkey.as_ref(), bytes))
+        .map(|(name, _)| name);
+
+    // Only a valid signature uses the challenge up, so a forged one cannot burn it.
+    if let (Some(nonce), Some(_)) = (nonce, matched) {
+        consume_challenge(nonce, &pubkey.to_string())?;
+    }
+    Ok(matched)
+}
+
+/// Malformed input is a 400; a signature that simply does not match is a 200
+/// with `valid: false`.
+pub async fn verify_msg(payload: Result<Json<VerifyMsgRequest>, JsonRejection>) -> ApiResult {
+    let Json(payload) =
+        payload.map_err(|rejection| ApiError::bad_request(format!("Invalid request body: {}", rejection.body_text())))?;
+
+    let matched = verify_message(&payload)?;
+    let VerifyMsgRequest { message, pubkey, .. } = payload;
+    Ok(success_response(VerifyMsgData {
+        valid: matched.is_some(),
+        pubkey,
+        message,
+        matched: matched.map(str::to_string),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::to_bytes, extract::Query, http::StatusCode};
+    use serde_json::Value;
+    use solana_keypair::Keypair;
+    use solana_sdk::signer::Signer;
+
+    use crate::challenge::issue_challenge;
+    use crate::types::{ChallengeQuery, SignatureFormat};
+
+    fn request(message: &str, signature: &str, pubkey: &str) -> VerifyMsgRequest {
+        VerifyMsgRequest {
+            message: message.to_string(),
+            encoding: MessageEncoding::Utf8,
+            standard: MessageStandard::Raw,
+            mode: VerifyMode::Exact,
+            signature: signature.to_string(),
+            pubkey: pubkey.to_string(),
+            require_challenge: false,
+        }
+    }
+
+    fn signed(message: &[u8]) -> (String, String) {
+        let keypair = Keypair::new();
+        (keypair.sign_message(message).to_string(), keypair.pubkey().to_string())
+    }
+
+    async fn verify(payload: VerifyMsgRequest) -> (StatusCode, Value) {
+        let response = match verify_msg(Ok(Json(payload))).await {
+            Ok(response) => response,
+            Err(err) => return (err.status, json!({ "error": err.message, "code": err.code })),
+        };
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn valid_signature_verifies() {
+        let (signature, pubkey) = signed(b"hello");
+        let (status, body) = verify(request("hello", &signature, &pubkey)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["valid"], true);
+        assert_eq!(body["data"]["pubkey"], pubkey);
+    }
+
+    #[tokio::test]
+    async fn mismatched_signature_is_ok_but_invalid() {
+        let (signature, pubkey) = signed(b"hello");
+        let (status, body) = verify(request("goodbye", &signature, &pubkey)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["valid"], false);
+
+        let (_, other) = signed(b"hello");
+        let (status, body) = verify(request("hello", &signature, &other)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["valid"], false);
+    }
+
+    #[tokio::test]
+    async fn missing_fields_are_rejected() {
+        let (signature, pubkey) = signed(b"hello");
+        let payloads = [
+            request("", &signature, &pubkey),
+            request("hello", "", &pubkey),
+            request("hello", &signature, ""),
+        ];
+        for payload in payloads {
+            let (status, body) = verify(payload).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body["error"].as_str().unwrap().starts_with("Missing required fields"));
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_pubkey_is_rejected() {
+        let (signature, _) = signed(b"hello");
+        for pubkey in ["not-a-key", "0OIl", "1111", "11111111111111111111111111111111111111111111111"] {
+            let (status, body) = verify(request("hello", &signature, pubkey)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{pubkey}");
+            assert_eq!(body["code"], "INVALID_PUBKEY");
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_signature_is_rejected() {
+        let (signature, pubkey) = signed(b"hello");
+        let short = bs58::encode([7u8; 63]).into_string();
+        let long = bs58::encode([7u8; 65]).into_string();
+        for signature in ["0OIl", short.as_str(), long.as_str(), &signature[..signature.len() - 4]] {
+            let (status, body) = verify(request("hello", signature, &pubkey)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{signature}");
+            assert_eq!(body["code"], "INVALID_SIGNATURE_FORMAT");
+        }
+    }
+
+    #[tokio::test]
+    async fn signatures_verify_in_every_format() {
+        let keypair = Keypair::new();
+        let signature = keypair.sign_message(b"hello");
+        let pubkey = keypair.pubkey().to_string();
+        for format in [SignatureFormat::Base58, SignatureFormat::Base64, SignatureFormat::Hex] {
+            let encoded = encode_signature(&signature, format);
+            assert_eq!(verify(request("hello", &encoded, &pubkey)).await.1["data"]["valid"], true, "{encoded}");
+        }
+        let prefixed = format!("0x{}", encode_signature(&signature, SignatureFormat::Hex));
+        assert_eq!(verify(request("hello", &prefixed, &pubkey)).await.1["data"]["valid"], true);
+    }
+
+    #[tokio::test]
+    async fn wallet_mode_reports_the_matching_convention() {
+        let offchain = solana_offchain_message::OffchainMessage::new(0, b"hello").unwrap().serialize().unwrap();
+        let cases: [(&[u8], &str); 3] = [(b"hello", "raw"), (&offchain, "offchain"), (b"\"hello\"", "json")];
+        for (signed_bytes, convention) in cases {
+            let (signature, pubkey) = signed(signed_bytes);
+            let mut payload = request("hello", &signature, &pubkey);
+            payload.mode = VerifyMode::Wallet;
+            let (status, body) = verify(payload).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["data"]["valid"], true, "{convention}");
+            assert_eq!(body["data"]["matched"], convention);
+        }
+
+        let (signature, pubkey) = signed(b"\"hello\"");
+        let (_, body) = verify(request("hello", &signature, &pubkey)).await;
+        assert_eq!(body["data"]["valid"], false);
+        assert!(body["data"].get("matched").is_none());
+
+        let (signature, pubkey) = signed(b"goodbye");
+        let mut payload = request("hello", &signature, &pubkey);
+        payload.mode = VerifyMode::Wallet;
+        assert_eq!(verify(payload).await.1["data"]["valid"], false);
+    }
+
+    async fn challenge_message(pubkey: &str) -> String {
+        let query = ChallengeQuery { pubkey: Some(pubkey.to_string()), ttl_seconds: None };
+        let response = issue_challenge(Query(query)).await.map_err(|err| err.message).unwrap();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        body["data"]["message"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn challenges_are_single_use_and_bound_to_their_pubkey() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey().to_string();
+        let challenged = |message: &str| {
+            let mut payload = request(message, &keypair.sign_message(message.as_bytes()).to_string(), &pubkey);
+            payload.require_challenge = true;
+            payload
+        };
+
+        let message = challenge_message(&pubkey).await;
+        let (status, body) = verify(challenged(&message)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["valid"], true);
+        let (status, body) = verify(challenged(&message)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "CHALLENGE_INVALID");
+
+        let (_, other) = signed(b"");
+        let message = challenge_message(&other).await;
+        assert_eq!(verify(challenged(&message)).await.1["code"], "CHALLENGE_INVALID");
+
+        let (status, body) = verify(challenged("no nonce here")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "CHALLENGE_MISSING");
+
+        // A signature that does not match leaves the challenge usable.
+        let message = challenge_message(&pubkey).await;
+        let mut forged = challenged(&message);
+        forged.signature = Keypair::new().sign_message(message.as_bytes()).to_string();
+        assert_eq!(verify(forged).await.1["data"]["valid"], false);
+        assert_eq!(verify(challenged(&message)).await.1["data"]["valid"], true);
+    }
+
+    #[tokio::test]
+    async fn malformed_message_encoding_is_rejected() {
+        let (signature, pubkey) = signed(b"hello");
+        let mut payload = request("not hex", &signature, &pubkey);
+        payload.encoding = MessageEncoding::Hex;
+        let (status, _) = verify(payload).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn binary_and_offchain_messages_verify() {
+        let (signature, pubkey) = signed(&[0xde, 0xad, 0xbe, 0xef]);
+        let mut payload = request("3q2+7w==", &signature, &pubkey);
+        payload.encoding = MessageEncoding::Base64;
+        assert_eq!(verify(payload).await.1["data"]["valid"], true);
+
+        let offchain = solana_offchain_message::OffchainMessage::new(0, b"hello").unwrap().serialize().unwrap();
+        let (signature, pubkey) = signed(&offchain);
+        let mut payload = request("hello", &signature, &pubkey);
+        payload.standard = MessageStandard::Offchain;
+        assert_eq!(verify(payload).await.1["data"]["valid"], true);
+        payload = request("hello", &signature, &pubkey);
+        assert_eq!(verify(payload).await.1["data"]["valid"], false);
+    }
+}
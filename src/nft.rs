@@ -0,0 +1,686 @@
+//! Metaplex Token Metadata instruction builders and NFT read endpoints.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use mpl_token_metadata::{
+    accounts::{Edition, EditionMarker, MasterEdition, Metadata, TokenRecord},
+    instructions::{BurnV1Builder, CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder, TransferV1Builder},
+    types::{Creator, DataV2, ProgrammableConfig, TokenStandard},
+    MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+};
+use serde_json::Value;
+use solana_account_decoder_client_types::UiAccountData;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::TokenAccountsFilter};
+use solana_sdk::{program_pack::Pack, pubkey, pubkey::Pubkey, rent::Rent, system_instruction::create_account};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::{
+    instruction::{initialize_mint2, mint_to},
+    state::Mint,
+    ID as TOKEN_PROGRAM_ID,
+};
+
+use crate::accounts::MULTIPLE_ACCOUNTS_CHUNK;
+use crate::state::AppState;
+use crate::token::is_token_program;
+use crate::types::{
+    CollectionData, CreatorData, CreatorRequest, MetadataCreateData, MetadataCreateRequest, NftBurnRequest,
+    NftInstructionsData, NftMintData, NftMintRequest, NftTransferRequest, NftsByOwnerData, NftsByOwnerQuery,
+    OwnedNftData, TokenMetadataData,
+};
+use crate::utils::{
+    instruction_to_data, page_offset, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult,
+};
+
+pub const TOKEN_AUTH_RULES_PROGRAM_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+/// Metaplex metadata of a mint, if any, along with the token program that owns the mint.
+pub struct NftAccounts {
+    pub metadata: Option<Metadata>,
+    pub metadata_address: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl NftAccounts {
+    pub fn token_standard(&self) -> Option<&TokenStandard> {
+        self.metadata.as_ref().and_then(|metadata| metadata.token_standard.as_ref())
+    }
+
+    pub fn is_programmable(&self) -> bool {
+        matches!(
+            self.token_standard(),
+            Some(TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition)
+        )
+    }
+
+    pub fn rule_set(&self) -> Option<Pubkey> {
+        match self.metadata.as_ref().and_then(|metadata| metadata.programmable_config.as_ref()) {
+            Some(ProgrammableConfig::V1 { rule_set }) => *rule_set,
+            None => None,
+        }
+    }
+}
+
+pub fn token_standard_name(standard: Option<&TokenStandard>) -> &'static str {
+    match standard {
+        Some(TokenStandard::NonFungible) | None => "nonFungible",
+        Some(TokenStandard::FungibleAsset) => "fungibleAsset",
+        Some(TokenStandard::Fungible) => "fungible",
+        Some(TokenStandard::NonFungibleEdition) => "nonFungibleEdition",
+        Some(TokenStandard::ProgrammableNonFungible) => "programmableNonFungible",
+        Some(TokenStandard::ProgrammableNonFungibleEdition) => "programmableNonFungibleEdition",
+    }
+}
+
+pub async fn fetch_nft(client: &RpcClient, mint: &Pubkey) -> Result<NftAccounts, ApiError> {
+    let (metadata_address, _) = Metadata::find_pda(mint);
+    let accounts = client.get_multiple_accounts(&[*mint, metadata_address]).await?;
+
+    let token_program = match &accounts[0] {
+        Some(account) if is_token_program(&account.owner) => account.owner,
+        Some(_) => return Err(ApiError::bad_request("Account is not a token mint")),
+        None => return Err(ApiError::new(StatusCode::NOT_FOUND, "Mint account not found")),
+    };
+    let metadata = accounts[1].as_ref().and_then(|account| Metadata::safe_deserialize(&account.data).ok());
+
+    Ok(NftAccounts { metadata, metadata_address, token_program })
+}
+
+/// Off-chain metadata larger than this is not worth inlining in a response.
+const MAX_OFF_CHAIN_METADATA_BYTES: usize = 1024 * 1024;
+const OFF_CHAIN_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Each hop is checked like the first, up to this many.
+const MAX_OFF_CHAIN_REDIRECTS: usize = 5;
+/// Hosts that answer with cloud credentials, whatever they resolve to.
+const METADATA_SERVICE_HOSTS: [&str; 2] = ["metadata.google.internal", "metadata"];
+
+/// Metaplex pads strings with NULs up to their maximum length.
+fn trim_padding(value: &str) -> String {
+    value.trim_end_matches('\0').to_string()
+}
+
+pub fn metaplex_metadata_data(mint: &Pubkey, address: &Pubkey, metadata: &Metadata) -> TokenMetadataData {
+    TokenMetadataData {
+        mint: mint.to_string(),
+        source: "metaplex",
+        metadata_address: address.to_string(),
+        update_authority: Some(metadata.update_authority.to_string()),
+        name: trim_padding(&metadata.name),
+        symbol: trim_padding(&metadata.symbol),
+        uri: trim_padding(&metadata.uri),
+        seller_fee_basis_points: Some(metadata.seller_fee_basis_points),
+        creators: metadata
+            .creators
+            .iter()
+            .flatten()
+            .map(|creator| CreatorData {
+                address: creator.address.to_string(),
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect(),
+        primary_sale_happened: Some(metadata.primary_sale_happened),
+        is_mutable: metadata.is_mutable,
+        token_standard: metadata.token_standard.as_ref().map(|standard| token_standard_name(Some(standard))),
+        collection: metadata.collection.as_ref().map(|collection| CollectionData {
+            key: collection.key.to_string(),
+            verified: collection.verified,
+        }),
+        additional_metadata: Vec::new(),
+        off_chain: None,
+        off_chain_error: None,
+    }
+}
+
+/// Whether `ip` is reachable from the public internet, as opposed to this
+/// host, its private network or a cloud metadata service.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Shared address space (carrier-grade NAT) and "this network".
+                || (a == 100 && (64..128).contains(&b))
+                || a == 0)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ip(IpAddr::V4(mapped)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local and link-local.
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves `url`'s host, refusing it unless every address is public.
+async fn public_addresses(url: &reqwest::Url) -> Result<Vec<SocketAddr>, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Unsupported metadata uri scheme: {}", url.scheme()));
+    }
+    let host = url.host_str().ok_or_else(|| "Metadata uri has no host".to_string())?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if METADATA_SERVICE_HOSTS.contains(&host.trim_end_matches('.').to_ascii_lowercase().as_str()) {
+        return Err("Metadata uri points at a private address".to_string());
+    }
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| format!("Failed to resolve metadata host {host}"))?
+        .collect();
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public_ip(address.ip())) {
+        return Err("Metadata uri points at a private address".to_string());
+    }
+    Ok(addresses)
+}
+
+/// Fetches the JSON document a metadata `uri` points at. Only public
+/// addresses are contacted: the host is resolved and checked before every
+/// request, redirects included, and the connection is pinned to the checked
+/// addresses.
+pub async fn fetch_off_chain(uri: &str) -> Result<Value, String> {
+    let mut url = reqwest::Url::parse(uri).map_err(|_| "Metadata uri is not a valid URL".to_string())?;
+    let mut redirects = 0;
+    let mut response = loop {
+        let addresses = public_addresses(&url).await?;
+        let mut client = reqwest::Client::builder()
+            .timeout(OFF_CHAIN_FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy();
+        if let Some(host) = url.host_str() {
+            client = client.resolve_to_addrs(host, &addresses);
+        }
+        let client = client.build().map_err(|e| e.to_string())?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch off-chain metadata: {e}"))?;
+        if !response.status().is_redirection() {
+            break response.error_for_status().map_err(|e| format!("Failed to fetch off-chain metadata: {e}"))?;
+        }
+        redirects += 1;
+        if redirects > MAX_OFF_CHAIN_REDIRECTS {
+            return Err("Off-chain metadata redirected too many times".to_string());
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| "Off-chain metadata redirect has no location".to_string())?;
+        url = url.join(location).map_err(|_| "Off-chain metadata redirect is not a valid URL".to_string())?;
+    };
+
+    if response.content_length().is_some_and(|len| len as usize > MAX_OFF_CHAIN_METADATA_BYTES) {
+        return Err("Off-chain metadata is too large".to_string());
+    }
+    // Read in chunks so that a body without a length stops at the limit.
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to fetch off-chain metadata: {e}"))? {
+        if body.len() + chunk.len() > MAX_OFF_CHAIN_METADATA_BYTES {
+            return Err("Off-chain metadata is too large".to_string());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    serde_json::from_slice(&body).map_err(|_| "Off-chain metadata is not valid JSON".to_string())
+}
+
+/// Validates the on-chain metadata fields and builds the `DataV2` payload.
+///
+/// Only the update authority can be marked verified up front, since it signs
+/// the creation instruction; other creators verify themselves afterwards.
+pub fn metadata_data(
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<CreatorRequest>>,
+    update_authority: &Pubkey,
+) -> Result<DataV2, ApiError> {
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(ApiError::bad_request(format!("Name must be at most {MAX_NAME_LENGTH} bytes")));
+    }
+    if symbol.len() > MAX_SYMBOL_LENGTH {
+        return Err(ApiError::bad_request(format!("Symbol must be at most {MAX_SYMBOL_LENGTH} bytes")));
+    }
+    if uri.len() > MAX_URI_LENGTH {
+        return Err(ApiError::bad_request(format!("URI must be at most {MAX_URI_LENGTH} bytes")));
+    }
+    if seller_fee_basis_points > 10_000 {
+        return Err(ApiError::bad_request("sellerFeeBasisPoints must be at most 10000"));
+    }
+
+    let creators = match creators {
+        Some(creators) if !creators.is_empty() => {
+            if creators.len() > MAX_CREATOR_LIMIT {
+                return Err(ApiError::bad_request(format!("At most {MAX_CREATOR_LIMIT} creators are allowed")));
+            }
+
+            let creators = creators
+                .into_iter()
+                .map(|creator| {
+                    let address = parse_pubkey(&creator.address, "creator")?;
+                    if creator.verified && address != *update_authority {
+                        return Err(ApiError::bad_request(
+                            "Only the update authority can be marked as a verified creator",
+                        ));
+                    }
+                    Ok(Creator { address, verified: creator.verified, share: creator.share })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for (i, creator) in creators.iter().enumerate() {
+                if creators[..i].iter().any(|other| other.address == creator.address) {
+                    return Err(ApiError::bad_request("Creators must be unique"));
+                }
+            }
+            if creators.iter().map(|creator| creator.share as u32).sum::<u32>() != 100 {
+                return Err(ApiError::bad_request("Creator shares must add up to 100"));
+            }
+
+            Some(creators)
+        }
+        _ => None,
+    };
+
+    Ok(DataV2 { name, symbol, uri, seller_fee_basis_points, creators, collection: None, uses: None })
+}
+
+pub async fn metadata_create(Json(payload): Json<MetadataCreateRequest>) -> ApiResult {
+    let MetadataCreateRequest {
+        mint,
+        mint_authority,
+        payer,
+        update_authority,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+        is_mutable,
+    } = payload;
+
+    if mint.is_empty() || mint_authority.is_empty() || name.is_empty() || uri.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: mint, mintAuthority, name, or uri"));
+    }
+
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+    let mint_authority_pubkey = parse_pubkey(&mint_authority, "mint authority")?;
+    let payer_pubkey = parse_optional_pubkey(payer.as_deref(), "payer")?.unwrap_or(mint_authority_pubkey);
+    let update_authority_pubkey =
+        parse_optional_pubkey(update_authority.as_deref(), "update authority")?.unwrap_or(mint_authority_pubkey);
+
+    let data = metadata_data(name, symbol, uri, seller_fee_basis_points, creators, &update_authority_pubkey)?;
+
+    let (metadata_pda, _) = Metadata::find_pda(&mint_pubkey);
+    let ix = CreateMetadataAccountV3Builder::new()
+        .metadata(metadata_pda)
+        .mint(mint_pubkey)
+        .mint_authority(mint_authority_pubkey)
+        .payer(payer_pubkey)
+        .update_authority(update_authority_pubkey, true)
+        .data(data)
+        .is_mutable(is_mutable.unwrap_or(true))
+        .instruction();
+
+    Ok(success_response(MetadataCreateData {
+        metadata: metadata_pda.to_string(),
+        instructions: vec![instruction_to_data(&ix)],
+    }))
+}
+
+pub async fn nft_mint(Json(payload): Json<NftMintRequest>) -> ApiResult {
+    let NftMintRequest {
+        mint,
+        payer,
+        owner,
+        update_authority,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+        is_mutable,
+        max_supply,
+    } = payload;
+
+    if mint.is_empty() || payer.is_empty() || name.is_empty() || uri.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: mint, payer, name, or uri"));
+    }
+
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+    let payer_pubkey = parse_pubkey(&payer, "payer")?;
+    let owner_pubkey = parse_optional_pubkey(owner.as_deref(), "owner")?.unwrap_or(payer_pubkey);
+    let update_authority_pubkey =
+        parse_optional_pubkey(update_authority.as_deref(), "update authority")?.unwrap_or(payer_pubkey);
+
+    if mint_pubkey == payer_pubkey {
+        return Err(ApiError::bad_request("Mint must be a fresh keypair, not the payer"));
+    }
+
+    let data = metadata_data(name, symbol, uri, seller_fee_basis_points, creators, &update_authority_pubkey)?;
+
+    let (metadata_pda, _) = Metadata::find_pda(&mint_pubkey);
+    let (edition_pda, _) = MasterEdition::find_pda(&mint_pubkey);
+    let token_account = get_associated_token_address(&owner_pubkey, &mint_pubkey);
+
+    // The payer acts as mint and freeze authority until the master edition
+    // instruction hands both over to the edition PDA.
+    let instructions = [
+        create_account(
+            &payer_pubkey,
+            &mint_pubkey,
+            Rent::default().minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &TOKEN_PROGRAM_ID,
+        ),
+        initialize_mint2(&TOKEN_PROGRAM_ID, &mint_pubkey, &payer_pubkey, Some(&payer_pubkey), 0)
+            .map_err(|e| ApiError::bad_request(format!("Failed to create initialize mint instruction: {e}")))?,
+        create_associated_token_account_idempotent(&payer_pubkey, &owner_pubkey, &mint_pubkey, &TOKEN_PROGRAM_ID),
+        mint_to(&TOKEN_PROGRAM_ID, &mint_pubkey, &token_account, &payer_pubkey, &[], 1)
+            .map_err(|e| ApiError::bad_request(format!("Failed to create mint instruction: {e}")))?,
+        CreateMetadataAccountV3Builder::new()
+            .metadata(metadata_pda)
+            .mint(mint_pubkey)
+            .mint_authority(payer_pubkey)
+            .payer(payer_pubkey)
+            .update_authority(update_authority_pubkey, true)
+            .data(data)
+            .is_mutable(is_mutable.unwrap_or(true))
+            .instruction(),
+        CreateMasterEditionV3Builder::new()
+            .edition(edition_pda)
+            .mint(mint_pubkey)
+            .update_authority(update_authority_pubkey)
+            .mint_authority(payer_pubkey)
+            .payer(payer_pubkey)
+            .metadata(metadata_pda)
+            .max_supply(max_supply.unwrap_or(0))
+            .instruction(),
+    ];
+
+    Ok(success_response(NftMintData {
+        mint: mint_pubkey.to_string(),
+        token_account: token_account.to_string(),
+        metadata: metadata_pda.to_string(),
+        master_edition: edition_pda.to_string(),
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn nft_transfer(State(state): State<AppState>, Json(payload): Json<NftTransferRequest>) -> ApiResult {
+    let NftTransferRequest { mint, owner, destination, authority, payer } = payload;
+
+    if mint.is_empty() || owner.is_empty() || destination.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: mint, owner, or destination"));
+    }
+
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+    let destination_pubkey = parse_pubkey(&destination, "destination")?;
+    let authority_pubkey = parse_optional_pubkey(authority.as_deref(), "authority")?.unwrap_or(owner_pubkey);
+    let payer_pubkey = parse_optional_pubkey(payer.as_deref(), "payer")?.unwrap_or(authority_pubkey);
+
+    if owner_pubkey == destination_pubkey {
+        return Err(ApiError::bad_request("Source and destination owners must differ"));
+    }
+
+    let client = state.rpc()?;
+    let nft = fetch_nft(client, &mint_pubkey).await?;
+
+    let source = get_associated_token_address_with_program_id(&owner_pubkey, &mint_pubkey, &nft.token_program);
+    let destination_token =
+        get_associated_token_address_with_program_id(&destination_pubkey, &mint_pubkey, &nft.token_program);
+
+    // Programmable NFTs stay frozen between transfers, so they have to go
+    // through Token Metadata, which also enforces the rule set.
+    let instructions = if nft.is_programmable() {
+        let rule_set = nft.rule_set();
+        let mut builder = TransferV1Builder::new();
+        builder
+            .token(source)
+            .token_owner(owner_pubkey)
+            .destination_token(destination_token)
+            .destination_owner(destination_pubkey)
+            .mint(mint_pubkey)
+            .metadata(nft.metadata_address)
+            .edition(Some(MasterEdition::find_pda(&mint_pubkey).0))
+            .token_record(Some(TokenRecord::find_pda(&mint_pubkey, &source).0))
+            .destination_token_record(Some(TokenRecord::find_pda(&mint_pubkey, &destination_token).0))
+            .authority(authority_pubkey)
+            .payer(payer_pubkey)
+            .spl_token_program(nft.token_program)
+            .amount(1);
+        if rule_set.is_some() {
+            builder.authorization_rules_program(Some(TOKEN_AUTH_RULES_PROGRAM_ID)).authorization_rules(rule_set);
+        }
+        vec![builder.instruction()]
+    } else {
+        vec![
+            create_associated_token_account_idempotent(
+                &payer_pubkey,
+                &destination_pubkey,
+                &mint_pubkey,
+                &nft.token_program,
+            ),
+            spl_token_2022::instruction::transfer_checked(
+                &nft.token_program,
+                &source,
+                &mint_pubkey,
+                &destination_token,
+                &authority_pubkey,
+                &[],
+                1,
+                0,
+            )
+            .map_err(|e| ApiError::bad_request(format!("Failed to create transfer instruction: {e}")))?,
+        ]
+    };
+
+    Ok(success_response(NftInstructionsData {
+        token_standard: token_standard_name(nft.token_standard()),
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+/// Prints record their burn in an edition marker covering 248 edition numbers.
+const EDITION_MARKER_BIT_SIZE: u64 = 248;
+
+pub async fn nft_burn(State(state): State<AppState>, Json(payload): Json<NftBurnRequest>) -> ApiResult {
+    let NftBurnRequest { mint, owner, authority, token_account, master_edition_mint } = payload;
+
+    if mint.is_empty() || owner.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: mint or owner"));
+    }
+
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+    let authority_pubkey = parse_optional_pubkey(authority.as_deref(), "authority")?.unwrap_or(owner_pubkey);
+    let token_account_pubkey = parse_optional_pubkey(token_account.as_deref(), "token account")?;
+    let master_mint_pubkey = parse_optional_pubkey(master_edition_mint.as_deref(), "master edition mint")?;
+
+    let client = state.rpc()?;
+    let nft = fetch_nft(client, &mint_pubkey).await?;
+
+    let token = token_account_pubkey.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(&owner_pubkey, &mint_pubkey, &nft.token_program)
+    });
+
+    // Without metadata there is nothing for Token Metadata to clean up, so
+    // burn the token and reclaim the account rent directly.
+    let Some(metadata) = nft.metadata.as_ref() else {
+        let instructions = [
+            spl_token_2022::instruction::burn_checked(
+                &nft.token_program,
+                &token,
+                &mint_pubkey,
+                &authority_pubkey,
+                &[],
+                1,
+                0,
+            )
+            .map_err(|e| ApiError::bad_request(format!("Failed to create burn instruction: {e}")))?,
+            spl_token_2022::instruction::close_account(
+                &nft.token_program,
+                &token,
+                &owner_pubkey,
+                &owner_pubkey,
+                &[],
+            )
+            .map_err(|e| ApiError::bad_request(format!("Failed to create close account instruction: {e}")))?,
+        ];
+        return Ok(success_response(NftInstructionsData {
+            token_standard: token_standard_name(None),
+            instructions: instructions.iter().map(instruction_to_data).collect(),
+        }));
+    };
+
+    let (edition_pda, _) = MasterEdition::find_pda(&mint_pubkey);
+    let mut builder = BurnV1Builder::new();
+    builder
+        .authority(authority_pubkey)
+        .metadata(nft.metadata_address)
+        .mint(mint_pubkey)
+        .token(token)
+        .spl_token_program(nft.token_program)
+        .amount(1);
+
+    // Verified members of a sized collection decrement its size on burn.
+    if let Some(collection) = metadata.collection.as_ref().filter(|collection| collection.verified) {
+        builder.collection_metadata(Some(Metadata::find_pda(&collection.key).0));
+    }
+    if nft.is_programmable() {
+        builder.token_record(Some(TokenRecord::find_pda(&mint_pubkey, &token).0));
+    }
+
+    match nft.token_standard() {
+        Some(TokenStandard::NonFungibleEdition | TokenStandard::ProgrammableNonFungibleEdition) => {
+            let master_mint = master_mint_pubkey
+                .ok_or_else(|| ApiError::bad_request("masterEditionMint is required to burn a print edition"))?;
+            let edition_data = client.get_account_data(&edition_pda).await?;
+            let edition = Edition::from_bytes(&edition_data)
+                .map_err(|_| ApiError::bad_request("Edition account is not a valid print edition"))?;
+            let (master_edition, _) = MasterEdition::find_pda(&master_mint);
+            if edition.parent != master_edition {
+                return Err(ApiError::bad_request("masterEditionMint is not the parent of this edition"));
+            }
+            let master_token = client
+                .get_token_largest_accounts(&master_mint)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| ApiError::bad_request("Master edition has no token account"))?;
+            let master_token = parse_pubkey(&master_token.address, "master edition token")?;
+            let marker = (edition.edition / EDITION_MARKER_BIT_SIZE).to_string();
+
+            builder
+                .edition(Some(edition_pda))
+                .master_edition(Some(master_edition))
+                .master_edition_mint(Some(master_mint))
+                .master_edition_token(Some(master_token))
+                .edition_marker(Some(EditionMarker::find_pda(&master_mint, &marker).0));
+        }
+        Some(TokenStandard::Fungible | TokenStandard::FungibleAsset) => {
+            return Err(ApiError::bad_request("Mint is not a non-fungible token"));
+        }
+        _ => {
+            builder.edition(Some(edition_pda));
+        }
+    }
+
+    Ok(success_response(NftInstructionsData {
+        token_standard: token_standard_name(nft.token_standard()),
+        instructions: vec![instruction_to_data(&builder.instruction())],
+    }))
+}
+
+const DEFAULT_NFT_PAGE_SIZE: usize = 50;
+const MAX_NFT_PAGE_SIZE: usize = 100;
+
+pub async fn nfts_by_owner(
+    State(state): State<AppState>,
+    Path(owner): Path<String>,
+    Query(query): Query<NftsByOwnerQuery>,
+) -> ApiResult {
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_NFT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_NFT_PAGE_SIZE {
+        return Err(ApiError::bad_request(format!("limit must be between 1 and {MAX_NFT_PAGE_SIZE}")));
+    }
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::bad_request("page must be at least 1"));
+    }
+
+    let client = state.rpc()?;
+
+    // Anything holding exactly one unit of a zero-decimal mint is NFT-shaped;
+    // metadata decides the rest.
+    let mut holdings = Vec::new();
+    for program_id in [spl_token::ID, spl_token_2022::ID] {
+        let accounts = client
+            .get_token_accounts_by_owner(&owner_pubkey, TokenAccountsFilter::ProgramId(program_id))
+            .await?;
+        for keyed in accounts {
+            let UiAccountData::Json(parsed) = keyed.account.data else {
+                continue;
+            };
+            let info = &parsed.parsed["info"];
+            let amount = &info["tokenAmount"];
+            if amount["amount"].as_str() != Some("1") || amount["decimals"].as_u64() != Some(0) {
+                continue;
+            }
+            let Some(mint) = info["mint"].as_str().and_then(|mint| Pubkey::try_from(mint).ok()) else {
+                continue;
+            };
+            holdings.push((mint, keyed.pubkey));
+        }
+    }
+    holdings.sort();
+
+    let total = holdings.len();
+    let page_holdings: Vec<_> = holdings.into_iter().skip(page_offset(page, limit)).take(limit).collect();
+
+    let metadata_addresses: Vec<Pubkey> = page_holdings.iter().map(|(mint, _)| Metadata::find_pda(mint).0).collect();
+    let mut metadata_accounts = Vec::with_capacity(metadata_addresses.len());
+    for chunk in metadata_addresses.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+        metadata_accounts.extend(client.get_multiple_accounts(chunk).await?);
+    }
+
+    let items = page_holdings
+        .into_iter()
+        .zip(metadata_addresses.iter().zip(metadata_accounts))
+        .map(|((mint, token_account), (address, account))| OwnedNftData {
+            mint: mint.to_string(),
+            token_account,
+            metadata: account
+                .and_then(|account| Metadata::safe_deserialize(&account.data).ok())
+                .map(|metadata| metaplex_metadata_data(&mint, address, &metadata)),
+        })
+        .collect();
+
+    Ok(success_response(NftsByOwnerData { owner: owner_pubkey.to_string(), total, page, limit, items }))
+}
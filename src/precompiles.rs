@@ -0,0 +1,86 @@
+use axum::Json;
+use sha3::{Digest, Keccak256};
+use solana_sdk::{
+    ed25519_instruction::new_ed25519_instruction_with_signature,
+    secp256k1_instruction::{
+        eth_address_from_pubkey, new_secp256k1_instruction_with_signature, HASHED_PUBKEY_SERIALIZED_SIZE,
+        SIGNATURE_SERIALIZED_SIZE,
+    },
+    secp256k1_recover::secp256k1_recover,
+    signature::Signature,
+};
+
+use std::str::FromStr;
+
+use crate::types::{Ed25519InstructionRequest, Secp256k1InstructionRequest};
+use crate::utils::{instruction_to_data, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub async fn ed25519_instruction(Json(payload): Json<Ed25519InstructionRequest>) -> ApiResult {
+    let Ed25519InstructionRequest { pubkey, message, signature } = payload;
+
+    if pubkey.is_empty() || message.is_empty() || signature.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pubkey, message, or signature"));
+    }
+
+    let public_key = parse_pubkey(&pubkey, "signer")?;
+    let signature = Signature::from_str(&signature).map_err(|_| ApiError::bad_request("Invalid signature format"))?;
+
+    // The precompile fails the whole transaction on a bad signature, so reject it up front.
+    if !signature.verify(public_key.as_ref(), message.as_bytes()) {
+        return Err(ApiError::bad_request("Signature does not verify against pubkey and message"));
+    }
+
+    let ix = new_ed25519_instruction_with_signature(message.as_bytes(), signature.as_array(), &public_key.to_bytes());
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+/// Parses a hex string, tolerating an Ethereum-style `0x` prefix.
+fn parse_hex(value: &str, field: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|_| ApiError::bad_request(format!("Invalid {} hex format", field)))
+}
+
+pub async fn secp256k1_instruction(Json(payload): Json<Secp256k1InstructionRequest>) -> ApiResult {
+    let Secp256k1InstructionRequest { eth_address, message, signature, recovery_id } = payload;
+
+    if eth_address.is_empty() || message.is_empty() || signature.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: ethAddress, message, or signature"));
+    }
+
+    let eth_address: [u8; HASHED_PUBKEY_SERIALIZED_SIZE] = parse_hex(&eth_address, "eth address")?
+        .try_into()
+        .map_err(|_| ApiError::bad_request("Eth address must be 20 bytes long"))?;
+
+    // Accept 64-byte r||s with a separate recovery id, or 65-byte r||s||v as produced by Ethereum wallets.
+    let mut signature_bytes = parse_hex(&signature, "signature")?;
+    let recovery_id = match (signature_bytes.len(), recovery_id) {
+        (SIGNATURE_SERIALIZED_SIZE, Some(recovery_id)) => recovery_id,
+        (65, None) => signature_bytes.pop().unwrap_or_default(),
+        (65, Some(_)) => {
+            return Err(ApiError::bad_request(
+                "65-byte signatures already embed the recovery id; omit recoveryId or send 64 bytes",
+            ));
+        }
+        (SIGNATURE_SERIALIZED_SIZE, None) => {
+            return Err(ApiError::bad_request("recoveryId is required for 64-byte signatures"));
+        }
+        _ => return Err(ApiError::bad_request("Signature must be 64 or 65 bytes long")),
+    };
+    // Ethereum encodes v as 27/28.
+    let recovery_id = if recovery_id >= 27 { recovery_id - 27 } else { recovery_id };
+    let signature: [u8; SIGNATURE_SERIALIZED_SIZE] = signature_bytes
+        .try_into()
+        .map_err(|_| ApiError::bad_request("Signature must be 64 or 65 bytes long"))?;
+
+    let message_hash = Keccak256::digest(message.as_bytes());
+    let recovered = secp256k1_recover(&message_hash, recovery_id, &signature)
+        .map_err(|_| ApiError::bad_request("Signature does not verify against eth address and message"))?;
+    if eth_address_from_pubkey(&recovered.to_bytes()) != eth_address {
+        return Err(ApiError::bad_request("Signature does not verify against eth address and message"));
+    }
+
+    let ix = new_secp256k1_instruction_with_signature(message.as_bytes(), &signature, recovery_id, &eth_address);
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
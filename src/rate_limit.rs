@@ -0,0 +1,170 @@
+//! Per-client rate limiting.
+//!
+//! Each client draws from a token bucket holding ten seconds' worth of its
+//! per-minute allowance. Clients are told apart by the API key they
+//! authenticated with, or else by address; keys listed under
+//! `rate_limit.api_keys` have an allowance of their own. `/keypair` routes, vanity grinding above all, also
+//! draw from a second, smaller bucket. Limited responses carry
+//! `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` for the
+//! tightest bucket; rejected requests get a 429 with `Retry-After`.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{
+    clock::{Clock, DefaultClock},
+    middleware::{StateInformationMiddleware, StateSnapshot},
+    DefaultDirectRateLimiter, DefaultKeyedRateLimiter, NotUntil, Quota, RateLimiter,
+};
+use serde_json::json;
+
+use std::{cmp::Reverse, collections::HashMap, net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+
+use crate::auth::Caller;
+use crate::config::RateLimitConfig;
+use crate::health::PROBE_ROUTES;
+use crate::utils::ApiError;
+
+const KEYPAIR_ROUTES: &str = "/keypair";
+/// Clients whose buckets have refilled are forgotten once this many are tracked.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+type ClientLimiter = DefaultKeyedRateLimiter<String, StateInformationMiddleware>;
+type KeyLimiter = DefaultDirectRateLimiter<StateInformationMiddleware>;
+type Outcome = Result<StateSnapshot, NotUntil<<DefaultClock as Clock>::Instant>>;
+
+/// Ten seconds' worth of `per_minute`, and at least one request, at once.
+fn quota(per_minute: NonZeroU32) -> Quota {
+    Quota::per_minute(per_minute).allow_burst(NonZeroU32::new(per_minute.get() / 6).unwrap_or(NonZeroU32::MIN))
+}
+
+pub struct RateLimits {
+    /// Per key or address, for clients without an allowance of their own.
+    clients: Option<ClientLimiter>,
+    /// Per address or key, for /keypair routes.
+    keypair: Option<ClientLimiter>,
+    /// By key name; `None` for keys exempt from the general limit.
+    api_keys: HashMap<String, Option<KeyLimiter>>,
+}
+
+impl RateLimits {
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let keyed = |limit: Option<NonZeroU32>| {
+            limit.map(|limit| RateLimiter::keyed(quota(limit)).with_middleware::<StateInformationMiddleware>())
+        };
+        let direct = |limit: Option<NonZeroU32>| {
+            limit.map(|limit| RateLimiter::direct(quota(limit)).with_middleware::<StateInformationMiddleware>())
+        };
+        RateLimits {
+            clients: keyed(config.requests_per_minute),
+            keypair: keyed(config.keypair_requests_per_minute),
+            api_keys: config.api_keys.iter().map(|(key, limit)| (key.clone(), direct(*limit))).collect(),
+        }
+    }
+
+    fn forget_idle_clients(&self) {
+        for limiter in [&self.clients, &self.keypair].into_iter().flatten() {
+            if limiter.len() > MAX_TRACKED_CLIENTS {
+                limiter.retain_recent();
+                limiter.shrink_to_fit();
+            }
+        }
+    }
+}
+
+/// Where a client stands with one bucket.
+struct Standing {
+    limit: u32,
+    remaining: u32,
+    /// Until the bucket is full again, or until the next request is allowed
+    /// after a rejection.
+    reset: Duration,
+    /// Set when the request was rejected.
+    retry_after: Option<Duration>,
+}
+
+impl Standing {
+    fn new(outcome: Outcome, clock: &DefaultClock) -> Standing {
+        match outcome {
+            Ok(snapshot) => {
+                let quota = snapshot.quota();
+                let remaining = snapshot.remaining_burst_capacity();
+                let used = quota.burst_size().get().saturating_sub(remaining);
+                Standing {
+                    limit: quota.burst_size().get(),
+                    remaining,
+                    reset: quota.replenish_interval() * used,
+                    retry_after: None,
+                }
+            }
+            Err(not_until) => {
+                let wait = not_until.wait_time_from(clock.now());
+                Standing {
+                    limit: not_until.quota().burst_size().get(),
+                    remaining: 0,
+                    reset: wait,
+                    retry_after: Some(wait),
+                }
+            }
+        }
+    }
+
+    fn write_headers(&self, headers: &mut HeaderMap) {
+        // Whole seconds, rounded up like Retry-After.
+        let reset = self.reset.as_secs() + u64::from(self.reset.subsec_nanos() > 0);
+        headers.insert("ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert("ratelimit-reset", HeaderValue::from(reset));
+    }
+}
+
+/// Rejects requests from clients that have used up their allowance.
+pub async fn rate_limit(State(limits): State<Arc<RateLimits>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if PROBE_ROUTES.contains(&path) {
+        return next.run(request).await;
+    }
+    let keypair_route = path.starts_with(KEYPAIR_ROUTES);
+    let caller = request.extensions().get::<Caller>().map(|Caller(key)| key.name.as_str());
+    let address = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+    let client = match (caller, address) {
+        (Some(name), _) => format!("key:{name}"),
+        (None, Some(address)) => format!("ip:{address}"),
+        (None, None) => "ip:unknown".to_string(),
+    };
+
+    let mut standings = Vec::new();
+    match caller.and_then(|name| limits.api_keys.get(name)) {
+        Some(limiter) => {
+            standings.extend(limiter.as_ref().map(|limiter| Standing::new(limiter.check(), limiter.clock())));
+        }
+        None => {
+            let limiter = limits.clients.as_ref();
+            standings.extend(limiter.map(|limiter| Standing::new(limiter.check_key(&client), limiter.clock())));
+        }
+    }
+    if keypair_route {
+        let limiter = limits.keypair.as_ref();
+        standings.extend(limiter.map(|limiter| Standing::new(limiter.check_key(&client), limiter.clock())));
+    }
+    limits.forget_idle_clients();
+
+    // A rejection, the longest wait first, or else the bucket closest to empty.
+    let tightest = standings.into_iter().max_by_key(|standing| (standing.retry_after, Reverse(standing.remaining)));
+    let Some(standing) = tightest else {
+        return next.run(request).await;
+    };
+    let mut response = match standing.retry_after {
+        Some(retry_after) => ApiError::new(StatusCode::TOO_MANY_REQUESTS, "Too many requests; slow down")
+            .with_code("RATE_LIMITED")
+            .with_details(json!({ "retry_after_ms": retry_after.as_millis() as u64 }))
+            .with_retry_after(retry_after)
+            .into_response(),
+        None => next.run(request).await,
+    };
+    standing.write_headers(response.headers_mut());
+    response
+}
@@ -0,0 +1,170 @@
+//! Keeps secret material out of error responses, logs and panics.
+//!
+//! Handlers never put secrets in their own messages, but rejections from
+//! deserialization quote the offending value, and that value can be a secret
+//! key or mnemonic. The middleware collects the values of secret-bearing
+//! fields from each request body and scrubs them from error responses. Panic
+//! messages are not tied to a request, so key-shaped text is masked instead.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+
+use crate::utils::{buffer_request, buffer_response};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Request fields, at any depth, whose values are secret.
+const SECRET_FIELDS: [&str; 9] =
+    ["secret", "secretKey", "secretKeys", "mnemonic", "passphrase", "seed", "shares", "archive", "pin"];
+/// Shorter values are too common to be worth scrubbing, and scrubbing them
+/// would mangle unrelated text.
+const MIN_SECRET_LEN: usize = 6;
+/// Base58 or hex text this long may be a 64-byte secret key (or a signature,
+/// which is masked too rather than risk missing a key).
+const MIN_ENCODED_KEY_LEN: usize = 80;
+/// JSON byte arrays this long may be a seed or secret key.
+const MIN_BYTE_ARRAY_LEN: usize = 32;
+
+/// Every string under a secret field of `value`, longest first.
+pub fn secret_values(value: &Value) -> Vec<String> {
+    fn strings(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::String(text) => out.push(text.clone()),
+            Value::Array(items) => items.iter().for_each(|item| strings(item, out)),
+            Value::Object(fields) => fields.values().for_each(|field| strings(field, out)),
+            _ => {}
+        }
+    }
+    fn collect(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Object(fields) => {
+                for (name, field) in fields {
+                    if SECRET_FIELDS.contains(&name.as_str()) {
+                        strings(field, out);
+                    } else {
+                        collect(field, out);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect(item, out)),
+            _ => {}
+        }
+    }
+
+    let mut values = Vec::new();
+    collect(value, &mut values);
+    // Each value may also appear with JSON escapes, as serde quotes it.
+    let escaped: Vec<String> = values
+        .iter()
+        .filter_map(|value| serde_json::to_string(value).ok())
+        .map(|quoted| quoted[1..quoted.len() - 1].to_string())
+        .collect();
+    values.extend(escaped);
+    values.retain(|value| value.trim().len() >= MIN_SECRET_LEN);
+    values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+    values.dedup();
+    values
+}
+
+/// Replaces the value of every secret field of `value`, at any depth.
+pub fn redact_fields(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.as_str()) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_fields),
+        _ => {}
+    }
+}
+
+/// `text` with every occurrence of `secrets` replaced.
+pub fn redact_values(text: &str, secrets: &[String]) -> String {
+    secrets.iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+/// `text` with anything shaped like a secret key masked: long base58 or hex
+/// runs and long arrays of byte values.
+pub fn redact_keys(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c == '[' || c.is_ascii_alphanumeric()) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let (len, secret) = if rest.starts_with('[') {
+            byte_array(rest)
+        } else {
+            let len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+            (len, len >= MIN_ENCODED_KEY_LEN)
+        };
+        out.push_str(if secret { REDACTED } else { &rest[..len] });
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// For `text` starting with `[`: how much of it to consume, and whether that
+/// is a byte array long enough to mask.
+fn byte_array(text: &str) -> (usize, bool) {
+    let Some(end) = text.find(']') else {
+        return (1, false);
+    };
+    let items: Vec<&str> = text[1..end].split(',').map(str::trim).collect();
+    if items.len() >= MIN_BYTE_ARRAY_LEN && items.iter().all(|item| item.parse::<u8>().is_ok()) {
+        (end + 1, true)
+    } else {
+        (1, false)
+    }
+}
+
+/// Scrubs the secrets a request carried from its error response.
+pub async fn redact_errors(request: Request, next: Next) -> Response {
+    let (parts, bytes) = match buffer_request(request).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let secrets = serde_json::from_slice::<Value>(&bytes).map(|json| secret_values(&json)).unwrap_or_default();
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    if secrets.is_empty() || response.status().is_success() {
+        return response;
+    }
+
+    let (mut parts, bytes) = match buffer_response(response).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let redacted = redact_values(&text, &secrets);
+    if redacted == text {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(redacted.len()));
+    Response::from_parts(parts, Body::from(redacted))
+}
+
+/// Logs panics through tracing with key-shaped text masked, in place of the
+/// default hook that prints the message as is.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let location = info.location().map(|location| location.to_string()).unwrap_or_default();
+        tracing::error!(location = %location, "panicked: {}", redact_keys(&message));
+    }));
+}
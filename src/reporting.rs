@@ -0,0 +1,152 @@
+//! Optional error reporting to Sentry.
+//!
+//! With a DSN configured, handler panics and 5xx responses are reported with
+//! the request behind them: method, route, query, headers and JSON body.
+//! Secret fields of the body and credential headers are masked before the
+//! event leaves the process, as are the request's secret values and anything
+//! key-shaped in messages. `SENTRY_ENVIRONMENT` and `SENTRY_RELEASE` apply.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use sentry::{
+    protocol::{Event, Level, Map, Request as RequestContext},
+    ClientInitGuard, ClientOptions, Hub, SentryFutureExt,
+};
+use serde_json::Value;
+use tower_http::request_id::RequestId;
+
+use std::{sync::Arc, time::Duration};
+
+use crate::health::PROBE_ROUTES;
+use crate::redact::{redact_fields, redact_keys, redact_values, secret_values, REDACTED};
+use crate::utils::{buffer_request, buffer_response};
+
+/// Headers that carry credentials. Headers naming a key, token or secret are
+/// masked as well.
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
+
+/// Starts reporting to `dsn` when set. The returned guard must be kept alive,
+/// and flushed on exit.
+pub fn init(dsn: Option<&str>) -> Result<Option<ClientInitGuard>, String> {
+    let Some(dsn) = dsn else {
+        return Ok(None);
+    };
+    let mut options = ClientOptions {
+        dsn: Some(dsn.parse().map_err(|e| format!("Invalid Sentry DSN: {e}"))?),
+        before_send: Some(Arc::new(|event| Some(scrub(event, &[])))),
+        ..Default::default()
+    };
+    if std::env::var_os("SENTRY_RELEASE").is_none() {
+        options.release = sentry::release_name!();
+    }
+    Ok(Some(sentry::init(options)))
+}
+
+/// Sends events that are still queued.
+pub fn shutdown(guard: Option<ClientInitGuard>, timeout: Duration) {
+    if guard.is_some_and(|guard| !guard.flush(Some(timeout))) {
+        tracing::warn!("Failed to flush error reports");
+    }
+}
+
+/// `event` with `secrets` and key-shaped text masked from its messages.
+fn scrub(mut event: Event<'static>, secrets: &[String]) -> Event<'static> {
+    let clean = |text: &str| redact_keys(&redact_values(text, secrets));
+    event.message = event.message.as_deref().map(clean);
+    if let Some(entry) = &mut event.logentry {
+        entry.message = clean(&entry.message);
+    }
+    for exception in &mut event.exception.values {
+        exception.value = exception.value.as_deref().map(clean);
+    }
+    for value in event.extra.values_mut() {
+        if let Value::String(text) = value {
+            *text = clean(text);
+        }
+    }
+    event
+}
+
+/// `headers` minus the values of any that may carry credentials.
+fn header_context(headers: &HeaderMap) -> Map<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str();
+            let sensitive = CREDENTIAL_HEADERS.contains(&name)
+                || ["key", "token", "secret"].iter().any(|word| name.contains(word));
+            let value = if sensitive { REDACTED.to_string() } else { String::from_utf8_lossy(value.as_bytes()).into() };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Reports panics and 5xx responses along with the request that caused them.
+pub async fn report_errors(request: Request, next: Next) -> Response {
+    // Probe failures already reach the orchestrator polling them, every few seconds.
+    let probe = PROBE_ROUTES.contains(&request.uri().path());
+    if probe || !Hub::current().client().is_some_and(|client| client.is_enabled()) {
+        return next.run(request).await;
+    }
+    let (parts, bytes) = match buffer_request(request).await {
+        Ok(buffered) => buffered,
+        Err(response) => return response,
+    };
+    let json = serde_json::from_slice::<Value>(&bytes).ok();
+    let secrets = json.as_ref().map(secret_values).unwrap_or_default();
+    let route = parts.extensions.get::<MatchedPath>().map_or(parts.uri.path(), |path| path.as_str());
+    let label = format!("{} {route}", parts.method);
+    let request_id = parts.extensions.get::<RequestId>().and_then(|id| id.header_value().to_str().ok());
+    let context = RequestContext {
+        method: Some(parts.method.to_string()),
+        query_string: parts.uri.query().map(|query| redact_keys(&redact_values(query, &secrets))),
+        headers: header_context(&parts.headers),
+        data: json.map(|mut json| {
+            redact_fields(&mut json);
+            json.to_string()
+        }),
+        ..Default::default()
+    };
+
+    // Events captured while the handler runs, panics included, go through
+    // this hub and so carry the request.
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    hub.configure_scope(|scope| {
+        scope.set_transaction(Some(&label));
+        if let Some(request_id) = request_id {
+            scope.set_tag("request_id", request_id);
+        }
+        let secrets = secrets.clone();
+        scope.add_event_processor(move |mut event| {
+            event.request.get_or_insert_with(|| context.clone());
+            Some(scrub(event, &secrets))
+        });
+    });
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).bind_hub(hub.clone()).await;
+    if !response.status().is_server_error() {
+        return response;
+    }
+
+    let status = response.status();
+    let mut event = Event {
+        level: Level::Error,
+        message: Some(format!("{label} responded {status}")),
+        ..Default::default()
+    };
+    event.tags.insert("status".to_string(), status.as_u16().to_string());
+    // A response too large to buffer is reported without its body.
+    let response = match buffer_response(response).await {
+        Ok((parts, bytes)) => {
+            event.extra.insert("response".to_string(), Value::String(String::from_utf8_lossy(&bytes).into()));
+            Response::from_parts(parts, Body::from(bytes))
+        }
+        Err(response) => response,
+    };
+    hub.capture_event(event);
+    response
+}
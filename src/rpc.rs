@@ -0,0 +1,242 @@
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use solana_client::{
+    client_error::{self, ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcRequest},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{field::Empty, Instrument};
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::types::Commitment;
+use crate::utils::ApiError;
+
+pub const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+
+/// JSON-RPC error code some providers return instead of HTTP 429.
+const RPC_RATE_LIMIT_CODE: i64 = -32005;
+const BASE_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(30);
+/// Rate limits further apart than this are treated as unrelated incidents.
+const RATE_LIMIT_RESET_WINDOW: Duration = Duration::from_secs(60);
+
+struct RateLimitState {
+    hits: u32,
+    last_hit: Instant,
+    limited_until: Instant,
+}
+
+/// Per-provider cooldowns, so a rate-limited endpoint is not hammered while it recovers.
+fn rate_limits() -> &'static Mutex<HashMap<String, RateLimitState>> {
+    static RATE_LIMITS: OnceLock<Mutex<HashMap<String, RateLimitState>>> = OnceLock::new();
+    RATE_LIMITS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Provider of the default endpoint, for RPC errors that do not name theirs.
+static DEFAULT_PROVIDER: OnceLock<String> = OnceLock::new();
+
+pub fn set_default_provider(url: &str) {
+    let _ = DEFAULT_PROVIDER.set(provider_name(url));
+}
+
+/// A rate limit as `TracedSender` reports it: JSON-RPC errors do not say
+/// which endpoint sent them, so the sender names its provider, having already
+/// started its cooldown.
+#[derive(Debug)]
+struct ProviderRateLimited {
+    provider: String,
+    retry_after: Duration,
+    source: ClientError,
+}
+
+impl fmt::Display for ProviderRateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RPC provider {} is rate limiting requests: {}", self.provider, self.source)
+    }
+}
+
+impl std::error::Error for ProviderRateLimited {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Canonical name of a well-known cluster, accepting the common aliases.
+pub fn canonical_cluster(cluster: &str) -> Option<&'static str> {
+    match cluster.to_ascii_lowercase().as_str() {
+        "devnet" => Some("devnet"),
+        "testnet" => Some("testnet"),
+        "mainnet" | "mainnet-beta" => Some("mainnet"),
+        "localnet" | "localhost" => Some("localnet"),
+        _ => None,
+    }
+}
+
+/// RPC URL for a well-known cluster name.
+pub fn cluster_url(cluster: &str) -> Option<&'static str> {
+    match canonical_cluster(cluster)? {
+        "devnet" => Some("https://api.devnet.solana.com"),
+        "testnet" => Some("https://api.testnet.solana.com"),
+        "mainnet" => Some("https://api.mainnet-beta.solana.com"),
+        _ => Some("http://127.0.0.1:8899"),
+    }
+}
+
+pub fn parse_commitment(commitment: &str) -> Option<Commitment> {
+    match commitment.to_ascii_lowercase().as_str() {
+        "processed" => Some(Commitment::Processed),
+        "confirmed" => Some(Commitment::Confirmed),
+        "finalized" => Some(Commitment::Finalized),
+        _ => None,
+    }
+}
+
+/// Host name of an RPC URL, used to identify the provider in errors.
+pub fn provider_name(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme.split(['/', '?']).next().unwrap_or(without_scheme).to_string()
+}
+
+fn rate_limited_error(provider: &str, retry_after: Duration) -> ApiError {
+    ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        format!("RPC provider {} is rate limiting requests", provider),
+    )
+    .with_code("RPC_RATE_LIMITED")
+    .with_details(json!({
+        "provider": provider,
+        "retry_after_ms": retry_after.as_millis() as u64,
+    }))
+    .with_retry_after(retry_after)
+}
+
+/// Records a rate-limit hit and returns how long clients should back off.
+fn record_rate_limit(provider: &str) -> Duration {
+    let now = Instant::now();
+    let mut limits = rate_limits().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let state = limits.entry(provider.to_string()).or_insert(RateLimitState {
+        hits: 0,
+        last_hit: now,
+        limited_until: now,
+    });
+
+    if now.duration_since(state.last_hit) > RATE_LIMIT_RESET_WINDOW {
+        state.hits = 0;
+    }
+    state.hits = state.hits.saturating_add(1);
+    state.last_hit = now;
+
+    let backoff = BASE_RATE_LIMIT_BACKOFF
+        .saturating_mul(2u32.saturating_pow(state.hits - 1))
+        .min(MAX_RATE_LIMIT_BACKOFF);
+    state.limited_until = now + backoff;
+    backoff
+}
+
+/// Fails fast while the provider is cooling down from a rate limit.
+pub fn check_rate_limit(provider: &str) -> Result<(), ApiError> {
+    let limits = rate_limits().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match limits.get(provider) {
+        Some(state) if state.limited_until > Instant::now() => {
+            Err(rate_limited_error(provider, state.limited_until - Instant::now()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_rate_limited(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Reqwest(err) => err.status().is_some_and(|status| status.as_u16() == 429),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == RPC_RATE_LIMIT_CODE,
+        _ => false,
+    }
+}
+
+/// Sends RPC requests over HTTP inside a span per call, so traces show each
+/// downstream request and how long it took.
+pub struct TracedSender {
+    inner: HttpSender,
+    provider: String,
+}
+
+impl TracedSender {
+    pub fn new(url: &str, timeout: Duration) -> Self {
+        TracedSender { inner: HttpSender::new_with_timeout(url, timeout), provider: provider_name(url) }
+    }
+}
+
+#[async_trait]
+impl RpcSender for TracedSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> client_error::Result<Value> {
+        let span = tracing::info_span!(
+            "rpc",
+            otel.name = %request,
+            otel.kind = "client",
+            otel.status_code = Empty,
+            rpc.system = "jsonrpc",
+            rpc.method = %request,
+            server.address = %self.provider,
+        );
+        let result = self.inner.send(request, params).instrument(span.clone()).await;
+        if result.is_err() {
+            span.record("otel.status_code", "ERROR");
+        }
+        result.map_err(|err| {
+            if !is_rate_limited(&err) {
+                return err;
+            }
+            let retry_after = record_rate_limit(&self.provider);
+            let limited = ProviderRateLimited { provider: self.provider.clone(), retry_after, source: err };
+            ClientErrorKind::Middleware(anyhow::Error::new(limited)).into()
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+pub fn commitment_config(commitment: Commitment) -> CommitmentConfig {
+    match commitment {
+        Commitment::Processed => CommitmentConfig::processed(),
+        Commitment::Confirmed => CommitmentConfig::confirmed(),
+        Commitment::Finalized => CommitmentConfig::finalized(),
+    }
+}
+
+impl From<ClientError> for ApiError {
+    fn from(err: ClientError) -> Self {
+        let limited = match err.kind() {
+            ClientErrorKind::Middleware(err) => err.downcast_ref::<ProviderRateLimited>(),
+            _ => None,
+        };
+        if let Some(limited) = limited {
+            return rate_limited_error(&limited.provider, limited.retry_after);
+        }
+        // Clients built on another sender report rate limits as they come.
+        if is_rate_limited(&err) {
+            let provider = match err.kind() {
+                ClientErrorKind::Reqwest(err) => err.url().map(|url| provider_name(url.as_str())),
+                _ => None,
+            }
+            .or_else(|| DEFAULT_PROVIDER.get().cloned())
+            .unwrap_or_default();
+            let retry_after = record_rate_limit(&provider);
+            return rate_limited_error(&provider, retry_after);
+        }
+        ApiError::new(StatusCode::BAD_GATEWAY, format!("RPC request failed: {}", err))
+    }
+}
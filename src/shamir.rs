@@ -0,0 +1,152 @@
+//! Shamir secret sharing for secret keys.
+//!
+//! The 32-byte ed25519 seed is split byte by byte over GF(2^8): each byte is
+//! the constant term of a random polynomial of degree `threshold - 1`, and a
+//! share holds that polynomial's value at its own x coordinate. Any
+//! `threshold` shares recover the seed by Lagrange interpolation at zero;
+//! fewer reveal nothing about it.
+//!
+//! A share is base58 of `[version, threshold, x, y_0 .. y_31]`.
+
+use axum::Json;
+use solana_keypair::keypair_from_seed;
+use solana_sdk::signer::Signer;
+
+use crate::types::{ReconstructKeyData, ReconstructKeyRequest, SplitKeyData, SplitKeyRequest};
+use crate::utils::{parse_secret_key, success_response, ApiError, ApiResult};
+
+const SHARE_VERSION: u8 = 1;
+const SHARE_HEADER_LEN: usize = 3;
+const SEED_LEN: usize = 32;
+
+/// Multiplication in GF(2^8) modulo the AES polynomial x^8 + x^4 + x^3 + x + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse; every non-zero element satisfies a^255 = 1.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    for _ in 0..254 {
+        result = gf_mul(result, a);
+    }
+    result
+}
+
+/// Evaluates the polynomial with `coefficients` (constant term first) at `x`.
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients.iter().rev().fold(0, |acc, coefficient| gf_mul(acc, x) ^ coefficient)
+}
+
+fn split_secret(secret: &[u8], shares: u8, threshold: u8) -> Vec<Vec<u8>> {
+    let mut outputs: Vec<Vec<u8>> = (1..=shares).map(|x| vec![SHARE_VERSION, threshold, x]).collect();
+    for byte in secret {
+        let mut coefficients = vec![*byte];
+        coefficients.extend((1..threshold).map(|_| rand::random::<u8>()));
+        for output in &mut outputs {
+            let x = output[2];
+            output.push(evaluate(&coefficients, x));
+        }
+    }
+    outputs
+}
+
+/// Interpolates each byte at x = 0 from `(x, y)` points with distinct, non-zero x.
+fn combine(points: &[(u8, &[u8])]) -> Vec<u8> {
+    let len = points[0].1.len();
+    (0..len)
+        .map(|i| {
+            points.iter().fold(0, |secret, (xj, yj)| {
+                // Lagrange basis polynomial for xj, evaluated at zero; subtraction is xor.
+                let basis = points
+                    .iter()
+                    .filter(|(xm, _)| xm != xj)
+                    .fold(1, |basis, (xm, _)| gf_mul(basis, gf_mul(*xm, gf_inv(xm ^ xj))));
+                secret ^ gf_mul(yj[i], basis)
+            })
+        })
+        .collect()
+}
+
+pub async fn split_key(Json(payload): Json<SplitKeyRequest>) -> ApiResult {
+    let SplitKeyRequest { secret, shares, threshold } = payload;
+
+    if secret.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: secret"));
+    }
+    if threshold < 2 || threshold > shares {
+        return Err(ApiError::bad_request("threshold must be at least 2 and at most shares"));
+    }
+
+    let keypair = parse_secret_key(&secret)?;
+    let shares = split_secret(keypair.secret_bytes(), shares, threshold)
+        .into_iter()
+        .map(|share| bs58::encode(share).into_string())
+        .collect();
+
+    Ok(success_response(SplitKeyData { pubkey: keypair.pubkey().to_string(), threshold, shares }))
+}
+
+pub async fn reconstruct_key(Json(payload): Json<ReconstructKeyRequest>) -> ApiResult {
+    let ReconstructKeyRequest { shares } = payload;
+
+    let decoded = shares
+        .iter()
+        .map(|share| bs58::decode(share).into_vec())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::bad_request("Invalid share format"))?;
+    if decoded.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: shares"));
+    }
+    if decoded
+        .iter()
+        .any(|share| share.len() != SHARE_HEADER_LEN + SEED_LEN || share[0] != SHARE_VERSION || share[2] == 0)
+    {
+        return Err(ApiError::bad_request("Invalid share format"));
+    }
+
+    let threshold = decoded[0][1];
+    // `split_key` never makes shares below this, and zero would interpolate nothing.
+    if threshold < 2 {
+        return Err(ApiError::bad_request("Invalid share format"));
+    }
+    if decoded.iter().any(|share| share[1] != threshold) {
+        return Err(ApiError::bad_request("Shares come from different splits"));
+    }
+    let mut points: Vec<(u8, &[u8])> = Vec::new();
+    for share in &decoded {
+        let (x, y) = (share[2], &share[SHARE_HEADER_LEN..]);
+        match points.iter().find(|(known, _)| *known == x) {
+            Some((_, known)) if *known != y => return Err(ApiError::bad_request("Shares come from different splits")),
+            Some(_) => {}
+            None => points.push((x, y)),
+        }
+    }
+    if points.len() < threshold as usize {
+        return Err(ApiError::bad_request(format!(
+            "{threshold} distinct shares are needed, got {}",
+            points.len()
+        )));
+    }
+
+    // Any `threshold` shares determine the polynomial; extra ones add nothing.
+    let seed = combine(&points[..threshold as usize]);
+    let keypair = keypair_from_seed(&seed).map_err(|_| ApiError::bad_request("Failed to create keypair from seed"))?;
+
+    Ok(success_response(ReconstructKeyData {
+        pubkey: keypair.pubkey().to_string(),
+        secret: keypair.to_base58_string(),
+    }))
+}
@@ -0,0 +1,55 @@
+//! Graceful shutdown.
+//!
+//! On SIGTERM or Ctrl-C the server stops accepting connections, lets
+//! in-flight requests finish and then waits for tracked submissions to land,
+//! all within one shutdown timeout measured from the signal.
+
+use std::{sync::OnceLock, time::Duration};
+
+use tokio::time::Instant;
+
+/// When the shutdown signal arrived.
+static SIGNALLED: OnceLock<Instant> = OnceLock::new();
+
+/// Resolves once SIGTERM or Ctrl-C is received.
+pub async fn signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    SIGNALLED.get_or_init(|| {
+        tracing::info!("Shutting down; draining in-flight work");
+        Instant::now()
+    });
+}
+
+/// Whether the shutdown signal has arrived.
+pub fn is_shutting_down() -> bool {
+    SIGNALLED.get().is_some()
+}
+
+/// When draining must stop: `timeout` after the signal, or from now if none arrived yet.
+pub fn deadline(timeout: Duration) -> Instant {
+    SIGNALLED.get().copied().unwrap_or_else(Instant::now) + timeout
+}
+
+/// Resolves `timeout` after the shutdown signal.
+pub async fn drain_expired(timeout: Duration) {
+    signal().await;
+    tokio::time::sleep_until(deadline(timeout)).await;
+}
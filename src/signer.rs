@@ -0,0 +1,460 @@
+//! Signing backends for keystore keys.
+//!
+//! A key either lives in this process or behind a `RemoteSigner` that only
+//! ever returns signatures: a Google Cloud KMS or AWS KMS ed25519 key, a
+//! PKCS#11 token driven through OpenSC's `pkcs11-tool`, or a Ledger (see
+//! `ledger`). Remote keys other than Ledgers are checked with a probe
+//! signature when registered, so a misconfigured backend is caught before
+//! anything is signed with it.
+//!
+//! Credentials come from the environment: `GCP_ACCESS_TOKEN` (otherwise the
+//! GCE metadata server), `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` /
+//! `AWS_SESSION_TOKEN`, and for PKCS#11 the variable named by the token's
+//! `pin_env`. PKCS#11 tokens are server configuration: requests name one and
+//! never choose the module that gets loaded.
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use solana_keypair::Keypair;
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
+use tokio::io::AsyncWriteExt;
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    process::Stdio,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::Pkcs11Config;
+use crate::ledger::LedgerSigner;
+use crate::types::KeyBackendConfig;
+use crate::utils::{format_utc_timestamp, ApiError};
+
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+/// DER prefix of an ed25519 SubjectPublicKeyInfo; the 32 key bytes follow it.
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+const PROBE_MESSAGE: &[u8] = b"keystore signer probe";
+/// Variable the PIN reaches `pkcs11-tool` through, so it never shows on the
+/// command line.
+const PKCS11_PIN_VAR: &str = "PKCS11_TOOL_PIN";
+const GCP_KMS_ENDPOINT: &str = "https://cloudkms.googleapis.com/v1/";
+const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<Signature, ApiError>> + Send + 'a>>;
+
+/// A signing service holding a key this process never sees.
+pub trait RemoteSigner: Send + Sync {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a>;
+}
+
+/// A key requests may sign with.
+#[derive(Clone)]
+pub enum KeySigner {
+    Local(Arc<Keypair>),
+    Remote { pubkey: Pubkey, signer: Arc<dyn RemoteSigner> },
+}
+
+impl KeySigner {
+    pub fn pubkey(&self) -> Pubkey {
+        match self {
+            KeySigner::Local(keypair) => keypair.pubkey(),
+            KeySigner::Remote { pubkey, .. } => *pubkey,
+        }
+    }
+
+    pub async fn sign(&self, message: &[u8]) -> Result<Signature, ApiError> {
+        match self {
+            KeySigner::Local(keypair) => Ok(keypair.sign_message(message)),
+            KeySigner::Remote { pubkey, signer } => {
+                let signature = signer.sign(message).await?;
+                // A backend that answers with another key's signature must not go unnoticed.
+                if !signature.verify(pubkey.as_ref(), message) {
+                    return Err(remote_error(format!("Remote signer returned an invalid signature for {pubkey}")));
+                }
+                Ok(signature)
+            }
+        }
+    }
+}
+
+impl From<Keypair> for KeySigner {
+    fn from(keypair: Keypair) -> Self {
+        KeySigner::Local(Arc::new(keypair))
+    }
+}
+
+fn remote_error(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::BAD_GATEWAY, message).with_code("REMOTE_SIGNER_FAILED")
+}
+
+fn http_client() -> Result<reqwest::Client, ApiError> {
+    reqwest::Client::builder()
+        .timeout(REMOTE_SIGNER_TIMEOUT)
+        .build()
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn pubkey_from_spki(der: &[u8]) -> Option<Pubkey> {
+    let key = der.strip_prefix(ED25519_SPKI_PREFIX.as_slice())?;
+    <[u8; 32]>::try_from(key).ok().map(Pubkey::new_from_array)
+}
+
+fn signature_from_bytes(bytes: &[u8]) -> Result<Signature, ApiError> {
+    Signature::try_from(bytes).map_err(|_| remote_error(format!("Remote signer returned {} bytes", bytes.len())))
+}
+
+fn decode_base64_field(response: &Value, field: &str) -> Result<Vec<u8>, ApiError> {
+    response[field]
+        .as_str()
+        .and_then(|value| STANDARD.decode(value).ok())
+        .ok_or_else(|| remote_error(format!("Remote signer response has no valid {field}")))
+}
+
+/// An ed25519 key version in Google Cloud KMS (`EC_SIGN_ED25519`).
+struct GcpKmsSigner {
+    key_version: String,
+}
+
+/// Whether `key_version` is a full
+/// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/N` resource name. Ids are
+/// limited to the characters Cloud KMS allows, which keeps `?`, `#`, `%` and dot segments out of the URL.
+fn is_gcp_key_version(key_version: &str) -> bool {
+    const COLLECTIONS: [&str; 5] = ["projects", "locations", "keyRings", "cryptoKeys", "cryptoKeyVersions"];
+    let is_id = |id: &str| {
+        !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+    };
+    let segments: Vec<&str> = key_version.split('/').collect();
+    segments.len() == 2 * COLLECTIONS.len()
+        && segments.chunks(2).zip(COLLECTIONS).all(|(pair, collection)| pair[0] == collection && is_id(pair[1]))
+        && segments[segments.len() - 1].bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Whether `region` looks like an AWS region such as `us-east-1` or `ap-southeast-2`.
+fn is_aws_region(region: &str) -> bool {
+    let parts: Vec<&str> = region.split('-').collect();
+    let lowercase = |part: &&str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_lowercase());
+    let [area, names @ .., number] = parts.as_slice() else {
+        return false;
+    };
+    area.len() == 2
+        && lowercase(area)
+        && !names.is_empty()
+        && names.iter().all(lowercase)
+        && !number.is_empty()
+        && number.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Access token for Cloud KMS, cached until shortly before it expires.
+async fn gcp_access_token(client: &reqwest::Client) -> Result<String, ApiError> {
+    if let Ok(token) = std::env::var("GCP_ACCESS_TOKEN") {
+        return Ok(token);
+    }
+
+    static TOKEN: OnceLock<Mutex<Option<(String, Instant)>>> = OnceLock::new();
+    let cache = TOKEN.get_or_init(|| Mutex::new(None));
+    let cached = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    if let Some((token, _)) = cached.filter(|(_, expires)| Instant::now() < *expires) {
+        return Ok(token);
+    }
+
+    let response: Value = client
+        .get(GCP_METADATA_TOKEN_URL)
+        .header("Metadata-Flavor", "Google")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| remote_error(format!("No GCP credentials: set GCP_ACCESS_TOKEN or run on GCP ({e})")))?
+        .json()
+        .await
+        .map_err(|e| remote_error(format!("Invalid GCP metadata token response: {e}")))?;
+    let token = response["access_token"]
+        .as_str()
+        .ok_or_else(|| remote_error("GCP metadata server returned no access token"))?
+        .to_string();
+    let lifetime = response["expires_in"].as_u64().unwrap_or(0).saturating_sub(60);
+    *cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+        Some((token.clone(), Instant::now() + Duration::from_secs(lifetime)));
+    Ok(token)
+}
+
+impl GcpKmsSigner {
+    fn new(key_version: &str) -> Result<Self, ApiError> {
+        if !is_gcp_key_version(key_version) {
+            return Err(ApiError::bad_request(
+                "keyVersion must be projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/N",
+            )
+            .with_code("INVALID_BACKEND"));
+        }
+        Ok(Self { key_version: key_version.to_string() })
+    }
+
+    async fn request(&self, method: &str, body: Option<Value>) -> Result<Value, ApiError> {
+        let client = http_client()?;
+        let token = gcp_access_token(&client).await?;
+        let url = reqwest::Url::parse(GCP_KMS_ENDPOINT)
+            .and_then(|endpoint| endpoint.join(&format!("{}{method}", self.key_version)))
+            .map_err(|e| remote_error(format!("Invalid Cloud KMS URL: {e}")))?;
+        let request = match body {
+            Some(body) => client.post(url).json(&body),
+            None => client.get(url),
+        };
+        let response = request
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| remote_error(format!("Cloud KMS request failed: {e}")))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| remote_error(format!("Invalid Cloud KMS response: {e}")))?;
+        if !status.is_success() {
+            let message = body["error"]["message"].as_str().unwrap_or("unknown error");
+            return Err(remote_error(format!("Cloud KMS rejected the request: {message}")));
+        }
+        Ok(body)
+    }
+
+    async fn pubkey(&self) -> Result<Pubkey, ApiError> {
+        let response = self.request("/publicKey", None).await?;
+        let pem = response["pem"].as_str().unwrap_or_default();
+        let der: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+        STANDARD
+            .decode(der)
+            .ok()
+            .and_then(|der| pubkey_from_spki(&der))
+            .ok_or_else(|| remote_error("Cloud KMS key is not an ed25519 key"))
+    }
+}
+
+impl RemoteSigner for GcpKmsSigner {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(async move {
+            // Ed25519 keys sign the message itself rather than a digest.
+            let response = self.request(":asymmetricSign", Some(json!({ "data": STANDARD.encode(message) }))).await?;
+            signature_from_bytes(&decode_base64_field(&response, "signature")?)
+        })
+    }
+}
+
+/// An ed25519 key in AWS KMS (`ECC_NIST_EDWARDS25519`), called with SigV4-signed requests.
+struct AwsKmsSigner {
+    key_id: String,
+    region: String,
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl AwsKmsSigner {
+    fn new(key_id: &str, region: &str) -> Result<Self, ApiError> {
+        // The region becomes part of the host the signed request, credentials included, is sent to.
+        if !is_aws_region(region) {
+            return Err(ApiError::bad_request(format!("Invalid AWS region {region}")).with_code("INVALID_BACKEND"));
+        }
+        Ok(Self { key_id: key_id.to_string(), region: region.to_string() })
+    }
+
+    async fn request(&self, target: &str, body: Value) -> Result<Value, ApiError> {
+        let (Ok(access_key), Ok(secret_key)) =
+            (std::env::var("AWS_ACCESS_KEY_ID"), std::env::var("AWS_SECRET_ACCESS_KEY"))
+        else {
+            return Err(remote_error("AWS credentials are not configured"));
+        };
+        let session_token = std::env::var("AWS_SESSION_TOKEN").ok();
+
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        // SigV4 wants the basic ISO 8601 form, 20240131T120000Z.
+        let amz_date = format_utc_timestamp(seconds).replace(['-', ':'], "");
+        let date = amz_date[..8].to_string();
+
+        let host = format!("kms.{}.amazonaws.com", self.region);
+        let target = format!("TrentService.{target}");
+        let payload = serde_json::to_vec(&body).unwrap_or_default();
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", target));
+
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{name}:{value}\n")).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(&payload))
+        );
+        let scope = format!("{date}/{}/kms/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_bytes(), b"kms", b"aws4_request"]
+            .iter()
+            .fold(hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date.as_bytes()), |key, part| {
+                hmac_sha256(&key, part)
+            });
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={signed_headers}, Signature={signature}"
+        );
+
+        let mut url = reqwest::Url::parse("https://amazonaws.com/").expect("static URL is valid");
+        url.set_host(Some(&host)).map_err(|e| remote_error(format!("Invalid AWS KMS host {host}: {e}")))?;
+        let mut request = http_client()?.post(url).header("authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(payload)
+            .send()
+            .await
+            .map_err(|e| remote_error(format!("AWS KMS request failed: {e}")))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| remote_error(format!("Invalid AWS KMS response: {e}")))?;
+        if !status.is_success() {
+            let kind = body["__type"].as_str().unwrap_or("error");
+            let message = body["message"].as_str().or(body["Message"].as_str()).unwrap_or("unknown error");
+            return Err(remote_error(format!("AWS KMS rejected the request: {kind}: {message}")));
+        }
+        Ok(body)
+    }
+
+    async fn pubkey(&self) -> Result<Pubkey, ApiError> {
+        let response = self.request("GetPublicKey", json!({ "KeyId": self.key_id })).await?;
+        pubkey_from_spki(&decode_base64_field(&response, "PublicKey")?)
+            .ok_or_else(|| remote_error("AWS KMS key is not an ed25519 key"))
+    }
+}
+
+impl RemoteSigner for AwsKmsSigner {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(async move {
+            let body = json!({
+                "KeyId": self.key_id,
+                "Message": STANDARD.encode(message),
+                "MessageType": "RAW",
+                "SigningAlgorithm": "ED25519_SHA_512",
+            });
+            let response = self.request("Sign", body).await?;
+            signature_from_bytes(&decode_base64_field(&response, "Signature")?)
+        })
+    }
+}
+
+/// An ed25519 key on a PKCS#11 token, signed with through the configured
+/// `pkcs11-tool` binary.
+struct Pkcs11Signer {
+    token: Pkcs11Config,
+    key_id: String,
+}
+
+impl RemoteSigner for Pkcs11Signer {
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(async move {
+            let mut command = tokio::process::Command::new(&self.token.tool);
+            command
+                .arg("--module")
+                .arg(&self.token.module)
+                .args(["--id", &self.key_id])
+                .args(["--sign", "--mechanism", "EDDSA", "--input-file", "/dev/stdin", "--output-file", "/dev/stdout"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            if let Some(slot) = self.token.slot {
+                command.args(["--slot", &slot.to_string()]);
+            }
+            if let Some(pin_env) = &self.token.pin_env {
+                let pin = std::env::var(pin_env)
+                    .map_err(|_| remote_error(format!("PKCS#11 PIN variable {pin_env} is not set")))?;
+                command.env(PKCS11_PIN_VAR, pin).args(["--login", "--pin", &format!("env:{PKCS11_PIN_VAR}")]);
+            }
+
+            let mut child = command.spawn().map_err(|e| remote_error(format!("Failed to run pkcs11-tool: {e}")))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(message).await.map_err(|e| remote_error(format!("pkcs11-tool failed: {e}")))?;
+            }
+            let output = tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, child.wait_with_output())
+                .await
+                .map_err(|_| remote_error("pkcs11-tool timed out"))?
+                .map_err(|e| remote_error(format!("pkcs11-tool failed: {e}")))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(remote_error(format!("pkcs11-tool failed: {}", stderr.trim())));
+            }
+            signature_from_bytes(&output.stdout)
+        })
+    }
+}
+
+fn pkcs11_token<'a>(tokens: &'a HashMap<String, Pkcs11Config>, name: &str) -> Result<&'a Pkcs11Config, ApiError> {
+    tokens.get(name).ok_or_else(|| {
+        ApiError::bad_request(format!("No PKCS#11 backend named {name} is configured")).with_code("UNKNOWN_BACKEND")
+    })
+}
+
+fn remote_signer(
+    config: &KeyBackendConfig,
+    pkcs11_tokens: &HashMap<String, Pkcs11Config>,
+) -> Result<Arc<dyn RemoteSigner>, ApiError> {
+    Ok(match config {
+        KeyBackendConfig::GcpKms { key_version } => Arc::new(GcpKmsSigner::new(key_version)?),
+        KeyBackendConfig::AwsKms { key_id, region } => Arc::new(AwsKmsSigner::new(key_id, region)?),
+        KeyBackendConfig::Pkcs11 { backend, key_id, .. } => Arc::new(Pkcs11Signer {
+            token: pkcs11_token(pkcs11_tokens, backend)?.clone(),
+            key_id: key_id.clone(),
+        }),
+        KeyBackendConfig::Ledger { derivation_path, device } => {
+            Arc::new(LedgerSigner::new(device.clone(), derivation_path.as_deref())?)
+        }
+    })
+}
+
+/// Rebuilds a remote key whose address is already known, without contacting the backend.
+pub fn restore_remote(
+    config: &KeyBackendConfig,
+    pubkey: Pubkey,
+    pkcs11_tokens: &HashMap<String, Pkcs11Config>,
+) -> Result<KeySigner, ApiError> {
+    Ok(KeySigner::Remote { pubkey, signer: remote_signer(config, pkcs11_tokens)? })
+}
+
+/// Connects to a remote key, looks up its address, and proves it can sign.
+pub async fn connect_remote(
+    config: &KeyBackendConfig,
+    pkcs11_tokens: &HashMap<String, Pkcs11Config>,
+) -> Result<KeySigner, ApiError> {
+    let pubkey = match config {
+        KeyBackendConfig::GcpKms { key_version } => GcpKmsSigner::new(key_version)?.pubkey().await?,
+        KeyBackendConfig::AwsKms { key_id, region } => AwsKmsSigner::new(key_id, region)?.pubkey().await?,
+        // pkcs11-tool has no convenient raw public key output, so the caller names it.
+        KeyBackendConfig::Pkcs11 { pubkey, .. } => pubkey
+            .parse()
+            .map_err(|_| ApiError::bad_request("Invalid pkcs11 public key format"))?,
+        // A probe would need confirming on the device, and the Solana app only signs transactions anyway.
+        KeyBackendConfig::Ledger { derivation_path, device } => {
+            let ledger = LedgerSigner::new(device.clone(), derivation_path.as_deref())?;
+            return Ok(KeySigner::Remote { pubkey: ledger.pubkey().await?, signer: Arc::new(ledger) });
+        }
+    };
+
+    let signer = restore_remote(config, pubkey, pkcs11_tokens)?;
+    signer.sign(PROBE_MESSAGE).await?;
+    Ok(signer)
+}
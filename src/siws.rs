@@ -0,0 +1,253 @@
+//! Sign-In With Solana.
+//!
+//! `/siws/input` issues a sign-in input with a fresh nonce, which the client
+//! passes to the wallet's `signIn` (or signs as text with `signMessage`).
+//! `/siws/verify` then parses the message the wallet signed, checks every
+//! field against the input issued for its nonce, verifies the signature and
+//! consumes the nonce, so each sign-in can be used exactly once.
+
+use axum::{http::StatusCode, Json};
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::types::{SiwsInput, SiwsInputData, SiwsInputRequest, SiwsVerifyData, SiwsVerifyRequest};
+use crate::utils::{
+    decode_message, decode_signature, format_utc_timestamp, parse_pubkey, success_response, ApiError, ApiResult,
+};
+
+const SIWS_VERSION: &str = "1";
+const DEFAULT_SIGN_IN_TTL: Duration = Duration::from_secs(600);
+const MAX_SIGN_IN_TTL: Duration = Duration::from_secs(3600);
+const HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+/// Outstanding inputs in total and for one requested address, as with challenges.
+const MAX_PENDING_SIGN_INS: usize = 10_000;
+const MAX_SIGN_INS_PER_ADDRESS: usize = 16;
+
+struct PendingSignIn {
+    input: SiwsInput,
+    expires: Instant,
+}
+
+/// Issued inputs by nonce, until they are used or expire.
+fn pending() -> &'static Mutex<HashMap<String, PendingSignIn>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, PendingSignIn>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn sign_in_error(status: StatusCode, message: impl Into<String>, code: &'static str) -> ApiError {
+    ApiError::new(status, message).with_code(code)
+}
+
+/// The message text for `input`, laid out as wallets render it.
+fn message_text(input: &SiwsInput, address: &str) -> String {
+    let mut message = format!("{}{HEADER_SUFFIX}\n{address}", input.domain);
+    if let Some(statement) = &input.statement {
+        message.push_str(&format!("\n\n{statement}"));
+    }
+
+    let mut fields = Vec::new();
+    if let Some(uri) = &input.uri {
+        fields.push(format!("URI: {uri}"));
+    }
+    fields.push(format!("Version: {}", input.version));
+    if let Some(chain_id) = &input.chain_id {
+        fields.push(format!("Chain ID: {chain_id}"));
+    }
+    fields.push(format!("Nonce: {}", input.nonce));
+    fields.push(format!("Issued At: {}", input.issued_at));
+    fields.push(format!("Expiration Time: {}", input.expiration_time));
+    if !input.resources.is_empty() {
+        fields.push("Resources:".to_string());
+        fields.extend(input.resources.iter().map(|resource| format!("- {resource}")));
+    }
+    message.push_str(&format!("\n\n{}", fields.join("\n")));
+    message
+}
+
+/// A sign-in message broken into its parts.
+#[derive(Default)]
+struct ParsedMessage {
+    domain: String,
+    address: String,
+    statement: Option<String>,
+    fields: HashMap<String, String>,
+    resources: Vec<String>,
+}
+
+const FIELD_NAMES: [&str; 8] =
+    ["URI", "Version", "Chain ID", "Nonce", "Issued At", "Expiration Time", "Not Before", "Request ID"];
+
+fn parse_message(text: &str) -> Option<ParsedMessage> {
+    let mut lines = text.split('\n');
+    let mut parsed = ParsedMessage {
+        domain: lines.next()?.strip_suffix(HEADER_SUFFIX)?.to_string(),
+        address: lines.next()?.to_string(),
+        ..ParsedMessage::default()
+    };
+
+    let mut in_resources = false;
+    for line in lines.filter(|line| !line.is_empty()) {
+        if in_resources {
+            parsed.resources.push(line.strip_prefix("- ")?.to_string());
+        } else if line == "Resources:" {
+            in_resources = true;
+        } else if let Some((name, value)) = line.split_once(": ").filter(|(name, _)| FIELD_NAMES.contains(name)) {
+            if parsed.fields.insert(name.to_string(), value.to_string()).is_some() {
+                return None;
+            }
+        } else if parsed.statement.is_none() && parsed.fields.is_empty() {
+            parsed.statement = Some(line.to_string());
+        } else {
+            return None;
+        }
+    }
+    Some(parsed)
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+pub async fn siws_input(Json(payload): Json<SiwsInputRequest>) -> ApiResult {
+    let SiwsInputRequest { domain, address, statement, uri, chain_id, resources, ttl_seconds } = payload;
+
+    if domain.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: domain"));
+    }
+    if domain.contains(char::is_whitespace) {
+        return Err(ApiError::bad_request("domain must not contain whitespace"));
+    }
+    if let Some(address) = &address {
+        parse_pubkey(address, "address")?;
+    }
+    // Every field is one line of the message, so a line break would let it forge another.
+    let single_line = [statement.as_deref(), uri.as_deref(), chain_id.as_deref()]
+        .into_iter()
+        .flatten()
+        .chain(resources.iter().map(String::as_str))
+        .all(|value| !value.contains(['\n', '\r']));
+    if !single_line {
+        return Err(ApiError::bad_request("statement, uri, chainId and resources must be single lines"));
+    }
+
+    let ttl = ttl_seconds.map_or(DEFAULT_SIGN_IN_TTL, Duration::from_secs);
+    if ttl.is_zero() || ttl > MAX_SIGN_IN_TTL {
+        return Err(ApiError::bad_request(format!(
+            "ttlSeconds must be between 1 and {}",
+            MAX_SIGN_IN_TTL.as_secs()
+        )));
+    }
+
+    let issued_at = unix_timestamp();
+    let input = SiwsInput {
+        domain,
+        address,
+        statement,
+        uri,
+        version: SIWS_VERSION.to_string(),
+        chain_id,
+        nonce: hex::encode(rand::random::<[u8; 16]>()),
+        issued_at: format_utc_timestamp(issued_at),
+        expiration_time: format_utc_timestamp(issued_at + ttl.as_secs()),
+        resources,
+    };
+    // Without a requested address the wallet fills in its own; show a placeholder.
+    let message = message_text(&input, input.address.as_deref().unwrap_or("<address>"));
+
+    {
+        let mut pending = pending().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        pending.retain(|_, sign_in| sign_in.expires > Instant::now());
+        let soonest = |sign_ins: Vec<&PendingSignIn>| {
+            let expires = sign_ins.iter().map(|sign_in| sign_in.expires).min();
+            expires.map_or(Duration::ZERO, |expires| expires.saturating_duration_since(Instant::now()))
+        };
+        let for_address: Vec<&PendingSignIn> = match &input.address {
+            Some(address) => {
+                pending.values().filter(|sign_in| sign_in.input.address.as_ref() == Some(address)).collect()
+            }
+            None => Vec::new(),
+        };
+        if for_address.len() >= MAX_SIGN_INS_PER_ADDRESS {
+            let message = format!("At most {MAX_SIGN_INS_PER_ADDRESS} sign-ins can be outstanding for one address");
+            return Err(sign_in_error(StatusCode::TOO_MANY_REQUESTS, message, "TOO_MANY_SIGN_INS")
+                .with_retry_after(soonest(for_address)));
+        }
+        if pending.len() >= MAX_PENDING_SIGN_INS {
+            let message = "Too many sign-ins are outstanding; try again later";
+            return Err(sign_in_error(StatusCode::TOO_MANY_REQUESTS, message, "TOO_MANY_SIGN_INS")
+                .with_retry_after(soonest(pending.values().collect())));
+        }
+        pending.insert(input.nonce.clone(), PendingSignIn { input: input.clone(), expires: Instant::now() + ttl });
+    }
+
+    Ok(success_response(SiwsInputData { input, message }))
+}
+
+pub async fn siws_verify(Json(payload): Json<SiwsVerifyRequest>) -> ApiResult {
+    let SiwsVerifyRequest { signed_message, encoding, signature, address } = payload;
+
+    if signed_message.is_empty() || signature.is_empty() || address.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: signedMessage, signature or address"));
+    }
+    let pubkey = parse_pubkey(&address, "address")?;
+    let signature = decode_signature(&signature)?;
+    let message_bytes = decode_message(&signed_message, encoding)?;
+    let parsed = std::str::from_utf8(&message_bytes)
+        .ok()
+        .and_then(parse_message)
+        .ok_or_else(|| ApiError::bad_request("Signed message is not a Sign-In With Solana message"))?;
+
+    let nonce = parsed.fields.get("Nonce").cloned().unwrap_or_default();
+    let input = {
+        let mut pending = pending().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        pending.retain(|_, sign_in| sign_in.expires > Instant::now());
+        pending.get(&nonce).map(|sign_in| sign_in.input.clone())
+    }
+    .ok_or_else(|| {
+        sign_in_error(StatusCode::UNAUTHORIZED, "Nonce is unknown, expired or already used", "NONCE_INVALID")
+    })?;
+
+    if parsed.address != address || input.address.as_ref().is_some_and(|expected| *expected != address) {
+        return Err(sign_in_error(StatusCode::UNAUTHORIZED, "Signed message is for another address", "SIWS_MISMATCH"));
+    }
+    // Any difference from the issued input, including fields the wallet added, fails the sign-in.
+    let expected = parse_message(&message_text(&input, &address)).unwrap_or_default();
+    let mismatch = if parsed.domain != expected.domain {
+        Some("domain")
+    } else if parsed.statement != expected.statement {
+        Some("statement")
+    } else if parsed.resources != expected.resources {
+        Some("resources")
+    } else {
+        FIELD_NAMES.into_iter().find(|name| parsed.fields.get(*name) != expected.fields.get(*name))
+    };
+    if let Some(field) = mismatch {
+        return Err(sign_in_error(
+            StatusCode::UNAUTHORIZED,
+            format!("Signed message {field} does not match the issued sign-in input"),
+            "SIWS_MISMATCH",
+        ));
+    }
+
+    if !signature.verify(pubkey.as_ref(), &message_bytes) {
+        return Err(sign_in_error(StatusCode::UNAUTHORIZED, "Invalid signature", "INVALID_SIGNATURE"));
+    }
+
+    // Only one verification may consume the nonce, however many race for it.
+    let consumed = pending().lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(&nonce);
+    if consumed.is_none() {
+        return Err(sign_in_error(StatusCode::UNAUTHORIZED, "Nonce was already used", "NONCE_INVALID"));
+    }
+
+    Ok(success_response(SiwsVerifyData {
+        address,
+        domain: input.domain,
+        nonce,
+        issued_at: input.issued_at,
+        expiration_time: input.expiration_time,
+    }))
+}
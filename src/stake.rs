@@ -0,0 +1,292 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{pubkey::Pubkey, rent::Rent, system_instruction::transfer};
+use solana_stake_interface::{
+    instruction::{authorize, deactivate_stake, merge, set_lockup, split_with_seed, withdraw, LockupArgs},
+    program::ID as STAKE_PROGRAM_ID,
+    state::{StakeAuthorize, StakeStateV2},
+};
+
+use crate::state::AppState;
+use crate::types::{
+    InflationRewardData, InflationRewardQuery, InstructionsData, StakeAccountData, StakeAccountsData,
+    StakeAuthorizeRequest, StakeAuthorizeType, StakeDeactivateRequest, StakeDelegationData, StakeMergeRequest,
+    StakeSetLockupRequest, StakeSplitData, StakeSplitRequest, StakeWithdrawRequest,
+};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub async fn stake_deactivate(Json(payload): Json<StakeDeactivateRequest>) -> ApiResult {
+    let StakeDeactivateRequest { stake_account, authority } = payload;
+
+    if stake_account.is_empty() || authority.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: stakeAccount or authority"));
+    }
+
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+
+    let ix = deactivate_stake(&stake_pubkey, &authority_pubkey);
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn stake_withdraw(Json(payload): Json<StakeWithdrawRequest>) -> ApiResult {
+    let StakeWithdrawRequest { stake_account, withdraw_authority, recipient, lamports, custodian } = payload;
+
+    if stake_account.is_empty() || withdraw_authority.is_empty() || recipient.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: stakeAccount, withdrawAuthority, or recipient",
+        ));
+    }
+
+    if lamports == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let withdraw_authority_pubkey = parse_pubkey(&withdraw_authority, "withdraw authority")?;
+    let recipient_pubkey = parse_pubkey(&recipient, "recipient")?;
+    let custodian_pubkey = parse_optional_pubkey(custodian.as_deref(), "custodian")?;
+
+    let ix = withdraw(
+        &stake_pubkey,
+        &withdraw_authority_pubkey,
+        &recipient_pubkey,
+        lamports,
+        custodian_pubkey.as_ref(),
+    );
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn stake_split(Json(payload): Json<StakeSplitRequest>) -> ApiResult {
+    let StakeSplitRequest { stake_account, authority, lamports, base, seed, payer } = payload;
+
+    if stake_account.is_empty() || authority.is_empty() || base.is_empty() || seed.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: stakeAccount, authority, base, or seed",
+        ));
+    }
+
+    if lamports == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let base_pubkey = parse_pubkey(&base, "base")?;
+    let payer_pubkey = parse_optional_pubkey(payer.as_deref(), "payer")?;
+
+    let split_stake_pubkey = Pubkey::create_with_seed(&base_pubkey, &seed, &STAKE_PROGRAM_ID)
+        .map_err(|_| ApiError::bad_request("Invalid seed for split stake account"))?;
+
+    let mut instructions = Vec::new();
+    if let Some(payer_pubkey) = payer_pubkey {
+        let rent_exempt_reserve = Rent::default().minimum_balance(StakeStateV2::size_of());
+        instructions.push(transfer(&payer_pubkey, &split_stake_pubkey, rent_exempt_reserve));
+    }
+    instructions.extend(split_with_seed(
+        &stake_pubkey,
+        &authority_pubkey,
+        lamports,
+        &split_stake_pubkey,
+        &base_pubkey,
+        &seed,
+    ));
+
+    Ok(success_response(StakeSplitData {
+        split_stake_account: split_stake_pubkey.to_string(),
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn stake_merge(Json(payload): Json<StakeMergeRequest>) -> ApiResult {
+    let StakeMergeRequest { destination_stake_account, source_stake_account, authority } = payload;
+
+    if destination_stake_account.is_empty() || source_stake_account.is_empty() || authority.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: destinationStakeAccount, sourceStakeAccount, or authority",
+        ));
+    }
+
+    let destination_pubkey = parse_pubkey(&destination_stake_account, "destination stake account")?;
+    let source_pubkey = parse_pubkey(&source_stake_account, "source stake account")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+
+    if destination_pubkey == source_pubkey {
+        return Err(ApiError::bad_request("Cannot merge a stake account into itself"));
+    }
+
+    let instructions = merge(&destination_pubkey, &source_pubkey, &authority_pubkey);
+
+    Ok(success_response(InstructionsData {
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn stake_authorize(Json(payload): Json<StakeAuthorizeRequest>) -> ApiResult {
+    let StakeAuthorizeRequest { stake_account, authority, new_authority, authorize_type, custodian } = payload;
+
+    if stake_account.is_empty() || authority.is_empty() || new_authority.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: stakeAccount, authority, or newAuthority",
+        ));
+    }
+
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let new_authority_pubkey = parse_pubkey(&new_authority, "new authority")?;
+    let custodian_pubkey = parse_optional_pubkey(custodian.as_deref(), "custodian")?;
+
+    let stake_authorize = match authorize_type {
+        StakeAuthorizeType::Staker => StakeAuthorize::Staker,
+        StakeAuthorizeType::Withdrawer => StakeAuthorize::Withdrawer,
+    };
+
+    let ix = authorize(
+        &stake_pubkey,
+        &authority_pubkey,
+        &new_authority_pubkey,
+        stake_authorize,
+        custodian_pubkey.as_ref(),
+    );
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn stake_set_lockup(Json(payload): Json<StakeSetLockupRequest>) -> ApiResult {
+    let StakeSetLockupRequest { stake_account, custodian, unix_timestamp, epoch, new_custodian } = payload;
+
+    if stake_account.is_empty() || custodian.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: stakeAccount or custodian"));
+    }
+
+    if unix_timestamp.is_none() && epoch.is_none() && new_custodian.is_none() {
+        return Err(ApiError::bad_request(
+            "At least one of unixTimestamp, epoch, or newCustodian must be provided",
+        ));
+    }
+
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let custodian_pubkey = parse_pubkey(&custodian, "custodian")?;
+    let new_custodian_pubkey = parse_optional_pubkey(new_custodian.as_deref(), "new custodian")?;
+
+    let lockup = LockupArgs {
+        unix_timestamp,
+        epoch,
+        custodian: new_custodian_pubkey,
+    };
+
+    let ix = set_lockup(&stake_pubkey, &lockup, &custodian_pubkey);
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+/// Offset of `Meta.authorized.withdrawer` in a serialized `StakeStateV2`:
+/// 4-byte enum tag, 8-byte rent exempt reserve, 32-byte staker.
+const WITHDRAWER_OFFSET: usize = 4 + 8 + 32;
+
+/// Decodes a stake account, classifying its activation against `epoch`.
+///
+/// The status ignores the warmup/cooldown rate, so large stakes may report
+/// `active`/`inactive` an epoch or two before the cluster fully agrees.
+pub fn decode_stake_account(pubkey: &Pubkey, lamports: u64, data: &[u8], epoch: u64) -> Option<StakeAccountData> {
+    let (meta, stake) = match bincode::deserialize::<StakeStateV2>(data).ok()? {
+        StakeStateV2::Initialized(meta) => (meta, None),
+        StakeStateV2::Stake(meta, stake, _) => (meta, Some(stake)),
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => return None,
+    };
+
+    let delegation = stake.map(|stake| stake.delegation);
+    let status = match &delegation {
+        None => "initialized",
+        Some(delegation) if delegation.deactivation_epoch != u64::MAX => {
+            if delegation.deactivation_epoch >= epoch { "deactivating" } else { "inactive" }
+        }
+        // Bootstrap stakes carry u64::MAX as their activation epoch and are always active.
+        Some(delegation) if delegation.activation_epoch >= epoch && delegation.activation_epoch != u64::MAX => {
+            "activating"
+        }
+        Some(_) => "active",
+    };
+
+    Some(StakeAccountData {
+        pubkey: pubkey.to_string(),
+        lamports,
+        status: status.to_string(),
+        staker: meta.authorized.staker.to_string(),
+        withdrawer: meta.authorized.withdrawer.to_string(),
+        rent_exempt_reserve: meta.rent_exempt_reserve,
+        lockup_unix_timestamp: meta.lockup.unix_timestamp,
+        lockup_epoch: meta.lockup.epoch,
+        lockup_custodian: meta.lockup.custodian.to_string(),
+        delegation: delegation.map(|delegation| StakeDelegationData {
+            voter: delegation.voter_pubkey.to_string(),
+            stake: delegation.stake,
+            activation_epoch: delegation.activation_epoch,
+            deactivation_epoch: (delegation.deactivation_epoch != u64::MAX)
+                .then_some(delegation.deactivation_epoch),
+        }),
+    })
+}
+
+pub async fn stake_accounts(State(state): State<AppState>, Path(pubkey): Path<String>) -> ApiResult {
+    let withdrawer = parse_pubkey(&pubkey, "withdraw authority")?;
+
+    let client = state.rpc()?;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(StakeStateV2::size_of() as u64),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(WITHDRAWER_OFFSET, withdrawer.to_bytes().to_vec())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let epoch = client.get_epoch_info().await?.epoch;
+    let program_accounts = client.get_program_accounts_with_config(&STAKE_PROGRAM_ID, config).await?;
+
+    let accounts = program_accounts
+        .iter()
+        .filter_map(|(pubkey, account)| decode_stake_account(pubkey, account.lamports, &account.data, epoch))
+        .collect();
+
+    Ok(success_response(StakeAccountsData { epoch, accounts }))
+}
+
+pub async fn inflation_reward(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Query(query): Query<InflationRewardQuery>,
+) -> ApiResult {
+    let address = parse_pubkey(&pubkey, "stake or vote account")?;
+
+    let client = state.rpc()?;
+    // Without an epoch the RPC node reports the most recently completed one.
+    let reward = client
+        .get_inflation_reward(&[address], query.epoch)
+        .await?
+        .into_iter()
+        .next()
+        .flatten();
+
+    Ok(success_response(InflationRewardData {
+        pubkey: address.to_string(),
+        epoch: reward.as_ref().map(|reward| reward.epoch).or(query.epoch),
+        found: reward.is_some(),
+        amount: reward.as_ref().map_or(0, |reward| reward.amount),
+        post_balance: reward.as_ref().map(|reward| reward.post_balance),
+        effective_slot: reward.as_ref().map(|reward| reward.effective_slot),
+        commission: reward.and_then(|reward| reward.commission),
+    }))
+}
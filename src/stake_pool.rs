@@ -0,0 +1,402 @@
+//! Instruction builders for the SPL stake pool program.
+//!
+//! The program's instruction and account layouts are encoded by hand here;
+//! only the pool fields needed to resolve derived accounts are decoded.
+
+use axum::{extract::State, http::StatusCode, Json};
+use borsh::BorshDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_instruction::create_account,
+    sysvar::{clock, stake_history},
+};
+use solana_sdk_ids::system_program;
+use solana_stake_interface::{
+    instruction::authorize,
+    program::ID as STAKE_PROGRAM_ID,
+    state::{StakeAuthorize, StakeStateV2},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::state::AppState;
+use crate::types::{
+    InstructionsData, StakePoolDepositSolRequest, StakePoolDepositStakeRequest, StakePoolWithdrawSolRequest,
+    StakePoolWithdrawStakeRequest,
+};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+const DEPOSIT_STAKE_TAG: u8 = 9;
+const WITHDRAW_STAKE_TAG: u8 = 10;
+const DEPOSIT_SOL_TAG: u8 = 14;
+const WITHDRAW_SOL_TAG: u8 = 16;
+
+#[derive(BorshDeserialize)]
+struct Fee {
+    _denominator: u64,
+    _numerator: u64,
+}
+
+// Decoded only to advance past it.
+#[allow(dead_code)]
+#[derive(BorshDeserialize)]
+enum FutureEpochFee {
+    None,
+    One(Fee),
+    Two(Fee),
+}
+
+/// Leading fields of the on-chain `StakePool` account, through the SOL withdraw authority.
+#[derive(BorshDeserialize)]
+struct StakePool {
+    account_type: u8,
+    _manager: [u8; 32],
+    _staker: [u8; 32],
+    stake_deposit_authority: [u8; 32],
+    _stake_withdraw_bump_seed: u8,
+    validator_list: [u8; 32],
+    reserve_stake: [u8; 32],
+    pool_mint: [u8; 32],
+    manager_fee_account: [u8; 32],
+    token_program_id: [u8; 32],
+    _total_lamports: u64,
+    _pool_token_supply: u64,
+    _last_update_epoch: u64,
+    _lockup: (i64, u64, [u8; 32]),
+    _epoch_fee: Fee,
+    _next_epoch_fee: FutureEpochFee,
+    _preferred_deposit_validator: Option<[u8; 32]>,
+    _preferred_withdraw_validator: Option<[u8; 32]>,
+    _stake_deposit_fee: Fee,
+    _stake_withdrawal_fee: Fee,
+    _next_stake_withdrawal_fee: FutureEpochFee,
+    _stake_referral_fee: u8,
+    sol_deposit_authority: Option<[u8; 32]>,
+    _sol_deposit_fee: Fee,
+    _sol_referral_fee: u8,
+    sol_withdraw_authority: Option<[u8; 32]>,
+}
+
+/// Resolved accounts of a stake pool.
+struct PoolAccounts {
+    pool: Pubkey,
+    withdraw_authority: Pubkey,
+    stake_deposit_authority: Pubkey,
+    validator_list: Pubkey,
+    reserve_stake: Pubkey,
+    pool_mint: Pubkey,
+    manager_fee_account: Pubkey,
+    token_program_id: Pubkey,
+    sol_deposit_authority: Option<Pubkey>,
+    sol_withdraw_authority: Option<Pubkey>,
+}
+
+/// Size of one `ValidatorStakeInfo` entry in the validator list.
+const VALIDATOR_STAKE_INFO_LEN: usize = 73;
+/// Account type byte plus `max_validators`, followed by the vec length prefix.
+const VALIDATOR_LIST_HEADER_LEN: usize = 1 + 4 + 4;
+
+async fn fetch_pool(client: &RpcClient, pool: &Pubkey) -> Result<PoolAccounts, ApiError> {
+    let account = client
+        .get_account_with_commitment(pool, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Stake pool account not found"))?;
+
+    if account.owner != STAKE_POOL_PROGRAM_ID {
+        return Err(ApiError::bad_request("Account is not owned by the stake pool program"));
+    }
+
+    let state = StakePool::deserialize(&mut account.data.as_slice())
+        .map_err(|_| ApiError::bad_request("Account is not a valid stake pool"))?;
+    // AccountType::StakePool
+    if state.account_type != 1 {
+        return Err(ApiError::bad_request("Account is not a valid stake pool"));
+    }
+
+    let (withdraw_authority, _) =
+        Pubkey::find_program_address(&[pool.as_ref(), b"withdraw"], &STAKE_POOL_PROGRAM_ID);
+
+    Ok(PoolAccounts {
+        pool: *pool,
+        withdraw_authority,
+        stake_deposit_authority: Pubkey::new_from_array(state.stake_deposit_authority),
+        validator_list: Pubkey::new_from_array(state.validator_list),
+        reserve_stake: Pubkey::new_from_array(state.reserve_stake),
+        pool_mint: Pubkey::new_from_array(state.pool_mint),
+        manager_fee_account: Pubkey::new_from_array(state.manager_fee_account),
+        token_program_id: Pubkey::new_from_array(state.token_program_id),
+        sol_deposit_authority: state.sol_deposit_authority.map(Pubkey::new_from_array),
+        sol_withdraw_authority: state.sol_withdraw_authority.map(Pubkey::new_from_array),
+    })
+}
+
+/// Finds the pool's stake account for `vote_account` by reading its seed from the validator list.
+async fn validator_stake_account(
+    client: &RpcClient,
+    pool: &PoolAccounts,
+    vote_account: &Pubkey,
+) -> Result<Pubkey, ApiError> {
+    let data = client.get_account_data(&pool.validator_list).await?;
+    let entries = data.get(VALIDATOR_LIST_HEADER_LEN..).unwrap_or_default();
+
+    let seed = entries
+        .chunks_exact(VALIDATOR_STAKE_INFO_LEN)
+        .find(|entry| entry[41..73] == vote_account.to_bytes())
+        .map(|entry| u32::from_le_bytes([entry[36], entry[37], entry[38], entry[39]]))
+        .ok_or_else(|| ApiError::bad_request("Validator is not part of this stake pool"))?;
+
+    let seed_bytes = seed.to_le_bytes();
+    let mut seeds: Vec<&[u8]> = vec![vote_account.as_ref(), pool.pool.as_ref()];
+    if seed != 0 {
+        seeds.push(&seed_bytes);
+    }
+
+    Ok(Pubkey::find_program_address(&seeds, &STAKE_POOL_PROGRAM_ID).0)
+}
+
+fn pool_instruction(tag: u8, amount: Option<u64>, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = vec![tag];
+    if let Some(amount) = amount {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    Instruction { program_id: STAKE_POOL_PROGRAM_ID, accounts, data }
+}
+
+fn user_pool_token_account(
+    pool: &PoolAccounts,
+    owner: &Pubkey,
+    pool_token_account: Option<&str>,
+) -> Result<Pubkey, ApiError> {
+    Ok(parse_optional_pubkey(pool_token_account, "pool token account")?.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(owner, &pool.pool_mint, &pool.token_program_id)
+    }))
+}
+
+fn instructions_response(instructions: &[Instruction]) -> ApiResult {
+    Ok(success_response(InstructionsData {
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn deposit_sol(State(state): State<AppState>, Json(payload): Json<StakePoolDepositSolRequest>) -> ApiResult {
+    let StakePoolDepositSolRequest { pool, funder, lamports, pool_token_account, referrer_token_account } = payload;
+
+    if pool.is_empty() || funder.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pool or funder"));
+    }
+    if lamports == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let pool_pubkey = parse_pubkey(&pool, "pool")?;
+    let funder_pubkey = parse_pubkey(&funder, "funder")?;
+
+    let client = state.rpc()?;
+    let pool = fetch_pool(client, &pool_pubkey).await?;
+
+    let destination = user_pool_token_account(&pool, &funder_pubkey, pool_token_account.as_deref())?;
+    let referrer = parse_optional_pubkey(referrer_token_account.as_deref(), "referrer token account")?
+        .unwrap_or(destination);
+
+    let mut accounts = vec![
+        AccountMeta::new(pool.pool, false),
+        AccountMeta::new_readonly(pool.withdraw_authority, false),
+        AccountMeta::new(pool.reserve_stake, false),
+        AccountMeta::new(funder_pubkey, true),
+        AccountMeta::new(destination, false),
+        AccountMeta::new(pool.manager_fee_account, false),
+        AccountMeta::new(referrer, false),
+        AccountMeta::new(pool.pool_mint, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(pool.token_program_id, false),
+    ];
+    if let Some(authority) = pool.sol_deposit_authority {
+        accounts.push(AccountMeta::new_readonly(authority, true));
+    }
+
+    instructions_response(&[pool_instruction(DEPOSIT_SOL_TAG, Some(lamports), accounts)])
+}
+
+pub async fn withdraw_sol(
+    State(state): State<AppState>,
+    Json(payload): Json<StakePoolWithdrawSolRequest>,
+) -> ApiResult {
+    let StakePoolWithdrawSolRequest { pool, authority, destination, pool_tokens, pool_token_account } = payload;
+
+    if pool.is_empty() || authority.is_empty() || destination.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pool, authority, or destination"));
+    }
+    if pool_tokens == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let pool_pubkey = parse_pubkey(&pool, "pool")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let destination_pubkey = parse_pubkey(&destination, "destination")?;
+
+    let client = state.rpc()?;
+    let pool = fetch_pool(client, &pool_pubkey).await?;
+
+    let source = user_pool_token_account(&pool, &authority_pubkey, pool_token_account.as_deref())?;
+
+    let mut accounts = vec![
+        AccountMeta::new(pool.pool, false),
+        AccountMeta::new_readonly(pool.withdraw_authority, false),
+        AccountMeta::new_readonly(authority_pubkey, true),
+        AccountMeta::new(source, false),
+        AccountMeta::new(pool.reserve_stake, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(pool.manager_fee_account, false),
+        AccountMeta::new(pool.pool_mint, false),
+        AccountMeta::new_readonly(clock::ID, false),
+        AccountMeta::new_readonly(stake_history::ID, false),
+        AccountMeta::new_readonly(STAKE_PROGRAM_ID, false),
+        AccountMeta::new_readonly(pool.token_program_id, false),
+    ];
+    if let Some(authority) = pool.sol_withdraw_authority {
+        accounts.push(AccountMeta::new_readonly(authority, true));
+    }
+
+    instructions_response(&[pool_instruction(WITHDRAW_SOL_TAG, Some(pool_tokens), accounts)])
+}
+
+pub async fn deposit_stake(
+    State(state): State<AppState>,
+    Json(payload): Json<StakePoolDepositStakeRequest>,
+) -> ApiResult {
+    let StakePoolDepositStakeRequest { pool, stake_account, owner, pool_token_account, referrer_token_account } =
+        payload;
+
+    if pool.is_empty() || stake_account.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pool or stakeAccount"));
+    }
+
+    let pool_pubkey = parse_pubkey(&pool, "pool")?;
+    let stake_pubkey = parse_pubkey(&stake_account, "stake account")?;
+    let owner_pubkey = parse_optional_pubkey(owner.as_deref(), "owner")?;
+
+    let client = state.rpc()?;
+    let pool = fetch_pool(client, &pool_pubkey).await?;
+
+    let stake_data = client.get_account_data(&stake_pubkey).await?;
+    let (meta, stake) = match bincode::deserialize::<StakeStateV2>(&stake_data) {
+        Ok(StakeStateV2::Stake(meta, stake, _)) => (meta, stake),
+        _ => return Err(ApiError::bad_request("Stake account is not delegated")),
+    };
+
+    let vote_account = stake.delegation.voter_pubkey;
+    let validator_stake = validator_stake_account(client, &pool, &vote_account).await?;
+
+    let owner_pubkey = owner_pubkey.unwrap_or(meta.authorized.withdrawer);
+    let destination = user_pool_token_account(&pool, &owner_pubkey, pool_token_account.as_deref())?;
+    let referrer = parse_optional_pubkey(referrer_token_account.as_deref(), "referrer token account")?
+        .unwrap_or(destination);
+
+    // The pool's default deposit authority is a PDA; a custom one must co-sign.
+    let (default_deposit_authority, _) =
+        Pubkey::find_program_address(&[pool.pool.as_ref(), b"deposit"], &STAKE_POOL_PROGRAM_ID);
+    let custom_deposit_authority = pool.stake_deposit_authority != default_deposit_authority;
+
+    let instructions = vec![
+        authorize(
+            &stake_pubkey,
+            &meta.authorized.staker,
+            &pool.stake_deposit_authority,
+            StakeAuthorize::Staker,
+            None,
+        ),
+        authorize(
+            &stake_pubkey,
+            &meta.authorized.withdrawer,
+            &pool.stake_deposit_authority,
+            StakeAuthorize::Withdrawer,
+            None,
+        ),
+        pool_instruction(DEPOSIT_STAKE_TAG, None, vec![
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(pool.validator_list, false),
+            AccountMeta::new_readonly(pool.stake_deposit_authority, custom_deposit_authority),
+            AccountMeta::new_readonly(pool.withdraw_authority, false),
+            AccountMeta::new(stake_pubkey, false),
+            AccountMeta::new(validator_stake, false),
+            AccountMeta::new(pool.reserve_stake, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new(pool.manager_fee_account, false),
+            AccountMeta::new(referrer, false),
+            AccountMeta::new(pool.pool_mint, false),
+            AccountMeta::new_readonly(clock::ID, false),
+            AccountMeta::new_readonly(stake_history::ID, false),
+            AccountMeta::new_readonly(pool.token_program_id, false),
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false),
+        ]),
+    ];
+
+    instructions_response(&instructions)
+}
+
+pub async fn withdraw_stake(
+    State(state): State<AppState>,
+    Json(payload): Json<StakePoolWithdrawStakeRequest>,
+) -> ApiResult {
+    let StakePoolWithdrawStakeRequest {
+        pool,
+        authority,
+        stake_receiver,
+        pool_tokens,
+        vote_account,
+        new_stake_authority,
+        pool_token_account,
+    } = payload;
+
+    if pool.is_empty() || authority.is_empty() || stake_receiver.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pool, authority, or stakeReceiver"));
+    }
+    if pool_tokens == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let pool_pubkey = parse_pubkey(&pool, "pool")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let receiver_pubkey = parse_pubkey(&stake_receiver, "stake receiver")?;
+    let vote_pubkey = parse_optional_pubkey(vote_account.as_deref(), "vote account")?;
+    let new_authority_pubkey =
+        parse_optional_pubkey(new_stake_authority.as_deref(), "new stake authority")?.unwrap_or(authority_pubkey);
+
+    let client = state.rpc()?;
+    let pool = fetch_pool(client, &pool_pubkey).await?;
+
+    let stake_to_split = match vote_pubkey {
+        Some(vote_pubkey) => validator_stake_account(client, &pool, &vote_pubkey).await?,
+        None => pool.reserve_stake,
+    };
+    let source = user_pool_token_account(&pool, &authority_pubkey, pool_token_account.as_deref())?;
+
+    let stake_size = StakeStateV2::size_of();
+    let rent = client.get_minimum_balance_for_rent_exemption(stake_size).await?;
+
+    let instructions = vec![
+        create_account(&authority_pubkey, &receiver_pubkey, rent, stake_size as u64, &STAKE_PROGRAM_ID),
+        pool_instruction(WITHDRAW_STAKE_TAG, Some(pool_tokens), vec![
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(pool.validator_list, false),
+            AccountMeta::new_readonly(pool.withdraw_authority, false),
+            AccountMeta::new(stake_to_split, false),
+            AccountMeta::new(receiver_pubkey, false),
+            AccountMeta::new_readonly(new_authority_pubkey, false),
+            AccountMeta::new_readonly(authority_pubkey, true),
+            AccountMeta::new(source, false),
+            AccountMeta::new(pool.manager_fee_account, false),
+            AccountMeta::new(pool.pool_mint, false),
+            AccountMeta::new_readonly(clock::ID, false),
+            AccountMeta::new_readonly(pool.token_program_id, false),
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false),
+        ]),
+    ];
+
+    instructions_response(&instructions)
+}
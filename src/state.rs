@@ -0,0 +1,248 @@
+//! Shared application state handed to handlers through axum's `State`.
+//!
+//! Every RPC-backed endpoint accepts a `commitment` query parameter, and a
+//! cluster through the `X-Solana-Cluster` header or `cluster` query parameter.
+//! Middleware scopes both to the request, and `rpc()` then hands out the
+//! matching client; otherwise the configured defaults apply.
+
+use axum::{
+    extract::{Query, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::RpcClientConfig};
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::amount::AmountFormat;
+use crate::config::{Config, CursorConfig, JitoConfig, Pkcs11Config};
+use crate::rpc::{
+    canonical_cluster, check_rate_limit, commitment_config, provider_name, set_default_provider, TracedSender,
+};
+use crate::signer::KeySigner;
+use crate::types::{ClusterQuery, Commitment, CommitmentQuery};
+use crate::utils::ApiError;
+
+pub const CLUSTER_HEADER: &str = "x-solana-cluster";
+
+tokio::task_local! {
+    static REQUEST_COMMITMENT: Commitment;
+    static REQUEST_CLUSTER: &'static str;
+}
+
+/// A reusable RPC client per commitment level and the URL they talk to.
+pub struct RpcEndpoint {
+    pub url: String,
+    /// Client at the configured default commitment.
+    pub client: Arc<RpcClient>,
+    processed: Arc<RpcClient>,
+    confirmed: Arc<RpcClient>,
+    finalized: Arc<RpcClient>,
+}
+
+impl RpcEndpoint {
+    fn new(url: String, default: Commitment, timeout: Duration) -> Self {
+        let client = |commitment| {
+            let config = RpcClientConfig::with_commitment(commitment_config(commitment));
+            Arc::new(RpcClient::new_sender(TracedSender::new(&url, timeout), config))
+        };
+        let processed = client(Commitment::Processed);
+        let confirmed = client(Commitment::Confirmed);
+        let finalized = client(Commitment::Finalized);
+        let mut endpoint = RpcEndpoint { url, client: finalized.clone(), processed, confirmed, finalized };
+        endpoint.client = endpoint.client_for(default).clone();
+        endpoint
+    }
+
+    fn client_for(&self, commitment: Commitment) -> &Arc<RpcClient> {
+        match commitment {
+            Commitment::Processed => &self.processed,
+            Commitment::Confirmed => &self.confirmed,
+            Commitment::Finalized => &self.finalized,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AppState {
+    rpc: Arc<RpcEndpoint>,
+    /// Name of the default cluster, when it was chosen by name rather than URL.
+    cluster: Option<&'static str>,
+    /// Other clusters requests may select, keyed by canonical name.
+    clusters: Arc<HashMap<&'static str, RpcEndpoint>>,
+    broadcast: Arc<Vec<RpcEndpoint>>,
+    commitment: Commitment,
+    /// Whether signing endpoints accept secret keys in requests, or only keystore ids.
+    allow_raw_secrets: bool,
+    keystore_enabled: bool,
+    /// Whether metadata lookups may fetch the documents their uris point at.
+    off_chain_metadata: bool,
+    amount_format: AmountFormat,
+    cursor: Arc<CursorConfig>,
+    jito: Arc<JitoConfig>,
+    /// PKCS#11 tokens keystore keys may use, by backend name.
+    pkcs11: Arc<HashMap<String, Pkcs11Config>>,
+    /// Server-held fee payers requests refer to by name.
+    fee_payers: Arc<HashMap<String, KeySigner>>,
+}
+
+impl AppState {
+    /// Builds the state from the server configuration.
+    pub fn from_config(config: &Config) -> Self {
+        let endpoint = |url: &str| RpcEndpoint::new(url.to_string(), config.commitment, config.rpc_timeout);
+        set_default_provider(&config.rpc_url);
+        AppState {
+            rpc: Arc::new(endpoint(&config.rpc_url)),
+            cluster: config.cluster,
+            clusters: Arc::new(config.clusters.iter().map(|(name, url)| (*name, endpoint(url))).collect()),
+            broadcast: Arc::new(config.broadcast_rpc_urls.iter().map(|url| endpoint(url)).collect()),
+            commitment: config.commitment,
+            allow_raw_secrets: config.allow_raw_secrets,
+            keystore_enabled: config.feature_enabled("keystore"),
+            off_chain_metadata: config.feature_enabled("off_chain_metadata"),
+            amount_format: config.amount_format,
+            cursor: Arc::new(config.cursor.clone()),
+            jito: Arc::new(config.jito.clone()),
+            pkcs11: Arc::new(config.pkcs11.clone()),
+            fee_payers: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Adds the fee payers loaded by `fee_payer::load_fee_payers`.
+    pub fn with_fee_payers(mut self, fee_payers: HashMap<String, KeySigner>) -> Self {
+        self.fee_payers = Arc::new(fee_payers);
+        self
+    }
+
+    pub fn fee_payers(&self) -> &HashMap<String, KeySigner> {
+        &self.fee_payers
+    }
+
+    pub fn fee_payer(&self, name: &str) -> Result<&KeySigner, ApiError> {
+        self.fee_payers.get(name).ok_or_else(|| {
+            ApiError::new(StatusCode::NOT_FOUND, format!("No fee payer named {name}")).with_code("UNKNOWN_FEE_PAYER")
+        })
+    }
+
+    /// The endpoint of the cluster the current request selected, or the default one.
+    fn endpoint(&self) -> &RpcEndpoint {
+        REQUEST_CLUSTER
+            .try_with(|cluster| self.clusters.get(cluster))
+            .ok()
+            .flatten()
+            .unwrap_or(&self.rpc)
+    }
+
+    /// Whether requests may select `cluster`.
+    fn serves_cluster(&self, cluster: &str) -> bool {
+        self.cluster == Some(cluster) || self.clusters.contains_key(cluster)
+    }
+
+    pub fn allow_raw_secrets(&self) -> bool {
+        self.allow_raw_secrets
+    }
+
+    pub fn keystore_enabled(&self) -> bool {
+        self.keystore_enabled
+    }
+
+    pub fn off_chain_metadata(&self) -> bool {
+        self.off_chain_metadata
+    }
+
+    pub fn amount_format(&self) -> AmountFormat {
+        self.amount_format
+    }
+
+    pub fn cursor(&self) -> &CursorConfig {
+        &self.cursor
+    }
+
+    pub fn jito(&self) -> &JitoConfig {
+        &self.jito
+    }
+
+    pub fn pkcs11_tokens(&self) -> &HashMap<String, Pkcs11Config> {
+        &self.pkcs11
+    }
+
+    /// Every endpoint requests can reach, the default one first, each named
+    /// by its cluster.
+    pub fn rpc_endpoints(&self) -> Vec<(&'static str, &RpcEndpoint)> {
+        let default = (self.cluster.unwrap_or("default"), self.rpc.as_ref());
+        std::iter::once(default).chain(self.clusters.iter().map(|(name, endpoint)| (*name, endpoint))).collect()
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.endpoint().url
+    }
+
+    /// Commitment the current request asked for, or the configured default.
+    pub fn commitment(&self) -> Commitment {
+        REQUEST_COMMITMENT.try_with(|commitment| *commitment).unwrap_or(self.commitment)
+    }
+
+    /// The shared client at the request's commitment, unless its provider is
+    /// cooling down from a rate limit.
+    pub fn rpc(&self) -> Result<&RpcClient, ApiError> {
+        let endpoint = self.endpoint();
+        check_rate_limit(&provider_name(&endpoint.url))?;
+        Ok(endpoint.client_for(self.commitment()))
+    }
+
+    /// An owned handle for work that outlives the request, like background rebroadcasts.
+    pub fn rpc_handle(&self) -> Arc<RpcClient> {
+        self.endpoint().client_for(self.commitment()).clone()
+    }
+
+    /// Additional endpoints fanned-out submissions are sent to, besides the primary one.
+    /// They belong to the default cluster, so requests for another cluster get none.
+    pub fn broadcast_endpoints(&self) -> &[RpcEndpoint] {
+        if std::ptr::eq(self.endpoint(), self.rpc.as_ref()) {
+            &self.broadcast
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Scopes the `commitment` query parameter to the request it came with.
+pub async fn commitment_option(request: Request, next: Next) -> Response {
+    match Query::<CommitmentQuery>::try_from_uri(request.uri()) {
+        Ok(Query(CommitmentQuery { commitment: Some(commitment) })) => {
+            REQUEST_COMMITMENT.scope(commitment, next.run(request)).await
+        }
+        Ok(_) => next.run(request).await,
+        Err(_) => ApiError::bad_request("Invalid commitment; use processed, confirmed or finalized").into_response(),
+    }
+}
+
+/// Scopes the cluster selected by the `X-Solana-Cluster` header, or failing
+/// that the `cluster` query parameter, to the request it came with.
+pub async fn cluster_option(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let from_header = request
+        .headers()
+        .get(CLUSTER_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().to_string());
+    let from_query = match Query::<ClusterQuery>::try_from_uri(request.uri()) {
+        Ok(Query(query)) => query.cluster,
+        Err(_) => None,
+    };
+    let Some(requested) = from_header.or(from_query) else {
+        return next.run(request).await;
+    };
+
+    match canonical_cluster(&requested).filter(|cluster| state.serves_cluster(cluster)) {
+        Some(cluster) => REQUEST_CLUSTER.scope(cluster, next.run(request)).await,
+        None => {
+            let mut available: Vec<&str> = state.clusters.keys().copied().chain(state.cluster).collect();
+            available.sort_unstable();
+            ApiError::bad_request(format!("Cluster {requested:?} is not configured on this server"))
+                .with_code("UNKNOWN_CLUSTER")
+                .with_details(json!({ "clusters": available }))
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use mpl_token_metadata::accounts::Metadata;
+use solana_account_decoder_client_types::UiAccountData;
+use solana_client::{rpc_request::TokenAccountsFilter, rpc_response::RpcKeyedAccount};
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{
+        permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::accounts::extension_names;
+use crate::nft::{fetch_off_chain, metaplex_metadata_data};
+use crate::state::AppState;
+use crate::types::{
+    DelegatedTokenAccountsData, HolderConcentrationData, LargestAccountData, LargestAccountsData, MintAuditData,
+    MintInfoData, TokenAccountData, TokenAccountInfoData, TokenAccountsData, TokenBalanceData, TokenBalanceQuery,
+    TokenMetadataData, TokenMetadataQuery, TokenSupplyData, TransferFeeData,
+};
+use crate::utils::{parse_pubkey, success_response, ApiError, ApiResult};
+
+/// Share of supply above which the ten largest holders are flagged.
+const TOP_10_CONCENTRATION_THRESHOLD: f64 = 50.0;
+
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::ID || *program_id == spl_token_2022::ID
+}
+
+fn percent_of(amount: u64, supply: u64) -> f64 {
+    amount as f64 * 100.0 / supply as f64
+}
+
+pub async fn mint_audit(State(state): State<AppState>, Path(mint): Path<String>) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+
+    let client = state.rpc()?;
+    let mint_account = client
+        .get_account_with_commitment(&mint_pubkey, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+
+    if !is_token_program(&mint_account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+
+    let state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?;
+    let base = state.base;
+
+    let mint_authority: Option<Pubkey> = base.mint_authority.into();
+    let freeze_authority: Option<Pubkey> = base.freeze_authority.into();
+
+    let extensions = extension_names(&state);
+
+    let transfer_fee = state.get_extension::<TransferFeeConfig>().ok().map(|config| {
+        let fee = config.newer_transfer_fee;
+        let config_authority: Option<Pubkey> = config.transfer_fee_config_authority.into();
+        TransferFeeData {
+            basis_points: u16::from(fee.transfer_fee_basis_points),
+            maximum_fee: u64::from(fee.maximum_fee),
+            config_authority: config_authority.map(|key| key.to_string()),
+        }
+    });
+
+    let permanent_delegate = state
+        .get_extension::<PermanentDelegate>()
+        .ok()
+        .and_then(|extension| Option::<Pubkey>::from(extension.delegate));
+
+    // Token-2022 mints may carry their metadata inline; otherwise fall back to Metaplex.
+    let (metadata_mutable, metadata_update_authority) = match state.get_variable_len_extension::<TokenMetadata>() {
+        Ok(metadata) => {
+            let update_authority: Option<Pubkey> = metadata.update_authority.into();
+            (Some(update_authority.is_some()), update_authority)
+        }
+        Err(_) => {
+            let (metadata_pda, _) = Metadata::find_pda(&mint_pubkey);
+            let metadata_account = client
+                .get_account_with_commitment(&metadata_pda, client.commitment())
+                .await?
+                .value;
+            match metadata_account.and_then(|account| Metadata::safe_deserialize(&account.data).ok()) {
+                Some(metadata) => (Some(metadata.is_mutable), Some(metadata.update_authority)),
+                None => (None, None),
+            }
+        }
+    };
+
+    let concentration = if base.supply > 0 {
+        let largest = client.get_token_largest_accounts(&mint_pubkey).await?;
+        let amounts: Vec<u64> = largest
+            .iter()
+            .map(|balance| balance.amount.amount.parse().unwrap_or_default())
+            .collect();
+        Some(HolderConcentrationData {
+            top_1_percent: percent_of(amounts.first().copied().unwrap_or_default(), base.supply),
+            top_10_percent: percent_of(amounts.iter().take(10).sum(), base.supply),
+            largest_accounts: largest.into_iter().take(10).map(|balance| balance.address).collect(),
+        })
+    } else {
+        None
+    };
+
+    let mut risks = Vec::new();
+    if mint_authority.is_some() {
+        risks.push("mint_authority_active");
+    }
+    if freeze_authority.is_some() {
+        risks.push("freeze_authority_active");
+    }
+    if metadata_mutable == Some(true) {
+        risks.push("metadata_mutable");
+    }
+    if transfer_fee.as_ref().is_some_and(|fee| fee.basis_points > 0) {
+        risks.push("transfer_fee");
+    }
+    if permanent_delegate.is_some() {
+        risks.push("permanent_delegate");
+    }
+    if concentration.as_ref().is_some_and(|c| c.top_10_percent > TOP_10_CONCENTRATION_THRESHOLD) {
+        risks.push("high_holder_concentration");
+    }
+
+    Ok(success_response(MintAuditData {
+        mint: mint_pubkey.to_string(),
+        program_id: mint_account.owner.to_string(),
+        supply: base.supply,
+        decimals: base.decimals,
+        mint_authority: mint_authority.map(|key| key.to_string()),
+        freeze_authority: freeze_authority.map(|key| key.to_string()),
+        metadata_mutable,
+        metadata_update_authority: metadata_update_authority.map(|key| key.to_string()),
+        extensions,
+        transfer_fee,
+        permanent_delegate: permanent_delegate.map(|key| key.to_string()),
+        concentration,
+        risks: risks.into_iter().map(String::from).collect(),
+    }))
+}
+
+pub async fn token_metadata(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(query): Query<TokenMetadataQuery>,
+) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+    if query.off_chain && !state.off_chain_metadata() {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Fetching off-chain metadata is disabled on this server")
+            .with_code("FEATURE_DISABLED"));
+    }
+    let (metadata_pda, _) = Metadata::find_pda(&mint_pubkey);
+
+    let client = state.rpc()?;
+    let accounts = client.get_multiple_accounts(&[mint_pubkey, metadata_pda]).await?;
+    let mint_account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+
+    if !is_token_program(&mint_account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+
+    let state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?;
+
+    // Same precedence as the mint audit: inline Token-2022 metadata first.
+    let mut data = match state.get_variable_len_extension::<TokenMetadata>() {
+        Ok(metadata) => {
+            let update_authority: Option<Pubkey> = metadata.update_authority.into();
+            TokenMetadataData {
+                mint: mint_pubkey.to_string(),
+                source: "token2022",
+                metadata_address: mint_pubkey.to_string(),
+                update_authority: update_authority.map(|key| key.to_string()),
+                name: metadata.name,
+                symbol: metadata.symbol,
+                uri: metadata.uri,
+                seller_fee_basis_points: None,
+                creators: Vec::new(),
+                primary_sale_happened: None,
+                is_mutable: update_authority.is_some(),
+                token_standard: None,
+                collection: None,
+                additional_metadata: metadata.additional_metadata,
+                off_chain: None,
+                off_chain_error: None,
+            }
+        }
+        Err(_) => {
+            let metadata = accounts[1]
+                .as_ref()
+                .and_then(|account| Metadata::safe_deserialize(&account.data).ok())
+                .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "No metadata found for mint"))?;
+            metaplex_metadata_data(&mint_pubkey, &metadata_pda, &metadata)
+        }
+    };
+
+    // A broken or slow uri should not hide the on-chain view.
+    if query.off_chain && !data.uri.is_empty() {
+        match fetch_off_chain(&data.uri).await {
+            Ok(off_chain) => data.off_chain = Some(off_chain),
+            Err(error) => data.off_chain_error = Some(error),
+        }
+    }
+
+    Ok(success_response(data))
+}
+
+pub async fn token_balance(State(state): State<AppState>, Query(query): Query<TokenBalanceQuery>) -> ApiResult {
+    let TokenBalanceQuery { owner, mint } = query;
+
+    if owner.is_empty() || mint.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: owner or mint"));
+    }
+
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+
+    // Which token program owns the mint decides the ATA, so both candidates
+    // are fetched alongside the mint in one round trip.
+    let legacy_ata = get_associated_token_address_with_program_id(&owner_pubkey, &mint_pubkey, &spl_token::ID);
+    let token_2022_ata = get_associated_token_address_with_program_id(&owner_pubkey, &mint_pubkey, &spl_token_2022::ID);
+
+    let client = state.rpc()?;
+    let accounts = client.get_multiple_accounts(&[mint_pubkey, legacy_ata, token_2022_ata]).await?;
+    let mint_account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+
+    if !is_token_program(&mint_account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?
+        .base
+        .decimals;
+    let (token_account, account) = if mint_account.owner == spl_token::ID {
+        (legacy_ata, accounts[1].as_ref())
+    } else {
+        (token_2022_ata, accounts[2].as_ref())
+    };
+
+    let amount = match account {
+        Some(account) => {
+            StateWithExtensions::<TokenAccount>::unpack(&account.data)
+                .map_err(|_| ApiError::bad_request("Associated token account is not a valid token account"))?
+                .base
+                .amount
+        }
+        None => 0,
+    };
+
+    Ok(success_response(TokenBalanceData {
+        owner: owner_pubkey.to_string(),
+        mint: mint_pubkey.to_string(),
+        token_account: token_account.to_string(),
+        exists: account.is_some(),
+        amount,
+        decimals,
+        ui_amount: spl_token::amount_to_ui_amount(amount, decimals),
+    }))
+}
+
+/// Decodes a `jsonParsed` token account; the RPC node resolves the mint decimals for us.
+fn parsed_token_account(keyed: RpcKeyedAccount) -> Option<TokenAccountData> {
+    let UiAccountData::Json(parsed) = keyed.account.data else {
+        return None;
+    };
+    let info = &parsed.parsed["info"];
+    let token_amount = &info["tokenAmount"];
+    let amount: u64 = token_amount["amount"].as_str()?.parse().ok()?;
+    let decimals = u8::try_from(token_amount["decimals"].as_u64()?).ok()?;
+    let string_field = |field: &str| info[field].as_str().map(str::to_string);
+
+    Some(TokenAccountData {
+        pubkey: keyed.pubkey,
+        program_id: keyed.account.owner,
+        mint: string_field("mint")?,
+        owner: string_field("owner")?,
+        amount,
+        decimals,
+        ui_amount: spl_token::amount_to_ui_amount(amount, decimals),
+        delegate: string_field("delegate"),
+        delegated_amount: info["delegatedAmount"]["amount"]
+            .as_str()
+            .and_then(|amount| amount.parse().ok())
+            .unwrap_or(0),
+        close_authority: string_field("closeAuthority"),
+        state: string_field("state").unwrap_or_default(),
+        is_native: info["isNative"].as_bool().unwrap_or(false),
+    })
+}
+
+pub async fn token_accounts(State(state): State<AppState>, Path(owner): Path<String>) -> ApiResult {
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+
+    let client = state.rpc()?;
+    let mut accounts = Vec::new();
+    for program_id in [spl_token::ID, spl_token_2022::ID] {
+        let keyed = client
+            .get_token_accounts_by_owner(&owner_pubkey, TokenAccountsFilter::ProgramId(program_id))
+            .await?;
+        accounts.extend(keyed.into_iter().filter_map(parsed_token_account));
+    }
+    accounts.sort_by(|a, b| (&a.mint, &a.pubkey).cmp(&(&b.mint, &b.pubkey)));
+
+    Ok(success_response(TokenAccountsData { owner: owner_pubkey.to_string(), accounts }))
+}
+
+/// Every token account `delegate` is approved on; `delegated_amount` is what it may still move.
+pub async fn token_accounts_by_delegate(State(state): State<AppState>, Path(delegate): Path<String>) -> ApiResult {
+    let delegate_pubkey = parse_pubkey(&delegate, "delegate")?;
+
+    let client = state.rpc()?;
+    let mut accounts = Vec::new();
+    for program_id in [spl_token::ID, spl_token_2022::ID] {
+        let keyed = client
+            .get_token_accounts_by_delegate(&delegate_pubkey, TokenAccountsFilter::ProgramId(program_id))
+            .await?;
+        accounts.extend(keyed.into_iter().filter_map(parsed_token_account));
+    }
+    accounts.sort_by(|a, b| (&a.mint, &a.pubkey).cmp(&(&b.mint, &b.pubkey)));
+
+    Ok(success_response(DelegatedTokenAccountsData { delegate: delegate_pubkey.to_string(), accounts }))
+}
+
+pub async fn token_supply(State(state): State<AppState>, Path(mint): Path<String>) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+
+    let client = state.rpc()?;
+    let supply = client.get_token_supply(&mint_pubkey).await?;
+    let amount = supply
+        .amount
+        .parse()
+        .map_err(|_| ApiError::new(StatusCode::BAD_GATEWAY, "RPC returned an invalid token supply"))?;
+
+    Ok(success_response(TokenSupplyData {
+        mint: mint_pubkey.to_string(),
+        supply: amount,
+        decimals: supply.decimals,
+        ui_amount: spl_token::amount_to_ui_amount(amount, supply.decimals),
+    }))
+}
+
+pub async fn mint_info(State(state): State<AppState>, Path(pubkey): Path<String>) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&pubkey, "mint")?;
+
+    let client = state.rpc()?;
+    let account = client
+        .get_account_with_commitment(&mint_pubkey, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+
+    if !is_token_program(&account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?;
+    let base = state.base;
+    let mint_authority: Option<Pubkey> = base.mint_authority.into();
+    let freeze_authority: Option<Pubkey> = base.freeze_authority.into();
+
+    Ok(success_response(MintInfoData {
+        mint: mint_pubkey.to_string(),
+        program_id: account.owner.to_string(),
+        supply: base.supply,
+        decimals: base.decimals,
+        is_initialized: base.is_initialized,
+        mint_authority: mint_authority.map(|key| key.to_string()),
+        freeze_authority: freeze_authority.map(|key| key.to_string()),
+        extensions: extension_names(&state),
+    }))
+}
+
+pub async fn token_account_info(State(state): State<AppState>, Path(pubkey): Path<String>) -> ApiResult {
+    let account_pubkey = parse_pubkey(&pubkey, "token account")?;
+
+    let client = state.rpc()?;
+    let account = client
+        .get_account_with_commitment(&account_pubkey, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Token account not found"))?;
+
+    if !is_token_program(&account.owner) {
+        return Err(ApiError::bad_request("Account is not owned by a token program"));
+    }
+
+    let state = StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid token account"))?;
+    let base = state.base;
+    let delegate: Option<Pubkey> = base.delegate.into();
+    let close_authority: Option<Pubkey> = base.close_authority.into();
+    let rent_exempt_reserve: Option<u64> = base.is_native.into();
+
+    Ok(success_response(TokenAccountInfoData {
+        pubkey: account_pubkey.to_string(),
+        program_id: account.owner.to_string(),
+        mint: base.mint.to_string(),
+        owner: base.owner.to_string(),
+        amount: base.amount,
+        delegate: delegate.map(|key| key.to_string()),
+        delegated_amount: base.delegated_amount,
+        state: match base.state {
+            AccountState::Uninitialized => "uninitialized",
+            AccountState::Initialized => "initialized",
+            AccountState::Frozen => "frozen",
+        }
+        .to_string(),
+        is_native: rent_exempt_reserve.is_some(),
+        rent_exempt_reserve,
+        close_authority: close_authority.map(|key| key.to_string()),
+        extensions: extension_names(&state),
+    }))
+}
+
+pub async fn largest_accounts(State(state): State<AppState>, Path(mint): Path<String>) -> ApiResult {
+    let mint_pubkey = parse_pubkey(&mint, "mint")?;
+
+    let client = state.rpc()?;
+    let largest = client.get_token_largest_accounts(&mint_pubkey).await?;
+
+    // At most 20 balances come back, so the mint and every holder fit in one fetch.
+    let mut addresses = vec![mint_pubkey];
+    for balance in &largest {
+        addresses.push(parse_pubkey(&balance.address, "token account")?);
+    }
+    let accounts = client.get_multiple_accounts(&addresses).await?;
+
+    let mint_account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Mint account not found"))?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| ApiError::bad_request("Account is not a valid mint"))?
+        .base;
+
+    let holders = largest
+        .into_iter()
+        .zip(&accounts[1..])
+        .map(|(balance, account)| {
+            let amount = balance.amount.amount.parse().unwrap_or_default();
+            let owner = account
+                .as_ref()
+                .and_then(|account| StateWithExtensions::<TokenAccount>::unpack(&account.data).ok())
+                .map(|state| state.base.owner.to_string());
+            LargestAccountData {
+                address: balance.address,
+                owner,
+                amount,
+                ui_amount: spl_token::amount_to_ui_amount(amount, mint_state.decimals),
+                percent_of_supply: if mint_state.supply > 0 { percent_of(amount, mint_state.supply) } else { 0.0 },
+            }
+        })
+        .collect();
+
+    Ok(success_response(LargestAccountsData {
+        mint: mint_pubkey.to_string(),
+        supply: mint_state.supply,
+        decimals: mint_state.decimals,
+        accounts: holders,
+    }))
+}
@@ -0,0 +1,473 @@
+//! Instruction builders for the SPL token-swap program.
+//!
+//! Like the stake pool builders, instruction data and the swap account layout
+//! are encoded by hand; the swap's vaults, mints, and fee account are resolved
+//! from chain so callers only need the swap address.
+
+use axum::{extract::State, http::StatusCode, Json};
+use borsh::BorshDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_instruction::create_account,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::state::AppState;
+use crate::token::is_token_program;
+use crate::types::{
+    InstructionsData, SwapCurveType, TokenSwapDepositRequest, TokenSwapInitializeRequest, TokenSwapRequest,
+    TokenSwapWithdrawRequest,
+};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub const TOKEN_SWAP_PROGRAM_ID: Pubkey = pubkey!("SwapsVeCiPHMUAtzQWZw7RjsKjgCjhwU55QGu4U1Szw");
+
+const INITIALIZE_TAG: u8 = 0;
+const SWAP_TAG: u8 = 1;
+const DEPOSIT_ALL_TAG: u8 = 2;
+const WITHDRAW_ALL_TAG: u8 = 3;
+
+/// Version byte plus the packed `SwapV1` state.
+const SWAP_ACCOUNT_LEN: usize = 1 + 323;
+
+/// Leading fields of the on-chain `SwapV1` account, through the pool fee account.
+#[derive(BorshDeserialize)]
+struct SwapState {
+    version: u8,
+    is_initialized: u8,
+    _bump_seed: u8,
+    token_program_id: [u8; 32],
+    token_a: [u8; 32],
+    token_b: [u8; 32],
+    pool_mint: [u8; 32],
+    token_a_mint: [u8; 32],
+    token_b_mint: [u8; 32],
+    pool_fee_account: [u8; 32],
+}
+
+/// Resolved accounts of a token swap.
+struct SwapAccounts {
+    swap: Pubkey,
+    authority: Pubkey,
+    pool_token_program: Pubkey,
+    token_a: Pubkey,
+    token_b: Pubkey,
+    pool_mint: Pubkey,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    token_a_program: Pubkey,
+    token_b_program: Pubkey,
+    pool_fee_account: Pubkey,
+}
+
+impl SwapAccounts {
+    /// Vault, mint, and token program of side A or side B.
+    fn side(&self, a: bool) -> (Pubkey, Pubkey, Pubkey) {
+        if a {
+            (self.token_a, self.token_a_mint, self.token_a_program)
+        } else {
+            (self.token_b, self.token_b_mint, self.token_b_program)
+        }
+    }
+}
+
+fn swap_authority(swap: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[swap.as_ref()], &TOKEN_SWAP_PROGRAM_ID).0
+}
+
+/// Returns the token program that owns each mint.
+async fn mint_programs(client: &RpcClient, mints: &[Pubkey]) -> Result<Vec<Pubkey>, ApiError> {
+    client
+        .get_multiple_accounts(mints)
+        .await?
+        .into_iter()
+        .zip(mints)
+        .map(|(account, mint)| match account {
+            Some(account) if is_token_program(&account.owner) => Ok(account.owner),
+            Some(_) => Err(ApiError::bad_request(format!("{mint} is not a token mint"))),
+            None => Err(ApiError::new(StatusCode::NOT_FOUND, format!("Mint {mint} not found"))),
+        })
+        .collect()
+}
+
+async fn fetch_swap(client: &RpcClient, swap: &Pubkey) -> Result<SwapAccounts, ApiError> {
+    let account = client
+        .get_account_with_commitment(swap, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Token swap account not found"))?;
+
+    if account.owner != TOKEN_SWAP_PROGRAM_ID {
+        return Err(ApiError::bad_request("Account is not owned by the token swap program"));
+    }
+
+    let state = SwapState::deserialize(&mut account.data.as_slice())
+        .map_err(|_| ApiError::bad_request("Account is not a valid token swap"))?;
+    // SwapVersion::SwapV1
+    if state.version != 1 || state.is_initialized != 1 {
+        return Err(ApiError::bad_request("Account is not a valid token swap"));
+    }
+
+    let token_a_mint = Pubkey::new_from_array(state.token_a_mint);
+    let token_b_mint = Pubkey::new_from_array(state.token_b_mint);
+    let programs = mint_programs(client, &[token_a_mint, token_b_mint]).await?;
+
+    Ok(SwapAccounts {
+        swap: *swap,
+        authority: swap_authority(swap),
+        pool_token_program: Pubkey::new_from_array(state.token_program_id),
+        token_a: Pubkey::new_from_array(state.token_a),
+        token_b: Pubkey::new_from_array(state.token_b),
+        pool_mint: Pubkey::new_from_array(state.pool_mint),
+        token_a_mint,
+        token_b_mint,
+        token_a_program: programs[0],
+        token_b_program: programs[1],
+        pool_fee_account: Pubkey::new_from_array(state.pool_fee_account),
+    })
+}
+
+fn swap_instruction(tag: u8, amounts: &[u64], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = vec![tag];
+    for amount in amounts {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    Instruction { program_id: TOKEN_SWAP_PROGRAM_ID, accounts, data }
+}
+
+fn user_token_account(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    account: Option<&str>,
+    field: &str,
+) -> Result<Pubkey, ApiError> {
+    Ok(parse_optional_pubkey(account, field)?
+        .unwrap_or_else(|| get_associated_token_address_with_program_id(owner, mint, token_program)))
+}
+
+pub async fn initialize(State(state): State<AppState>, Json(payload): Json<TokenSwapInitializeRequest>) -> ApiResult {
+    let TokenSwapInitializeRequest {
+        swap,
+        payer,
+        token_a,
+        token_b,
+        pool_mint,
+        fee_account,
+        destination,
+        fees,
+        curve_type,
+        curve_parameter,
+    } = payload;
+
+    if swap.is_empty()
+        || token_a.is_empty()
+        || token_b.is_empty()
+        || pool_mint.is_empty()
+        || fee_account.is_empty()
+        || destination.is_empty()
+    {
+        return Err(ApiError::bad_request(
+            "Missing required fields: swap, tokenA, tokenB, poolMint, feeAccount, or destination",
+        ));
+    }
+
+    let swap_pubkey = parse_pubkey(&swap, "swap")?;
+    let payer_pubkey = parse_optional_pubkey(payer.as_deref(), "payer")?;
+    let token_a_pubkey = parse_pubkey(&token_a, "token A")?;
+    let token_b_pubkey = parse_pubkey(&token_b, "token B")?;
+    let pool_mint_pubkey = parse_pubkey(&pool_mint, "pool mint")?;
+    let fee_account_pubkey = parse_pubkey(&fee_account, "fee account")?;
+    let destination_pubkey = parse_pubkey(&destination, "destination")?;
+
+    let fees = fees.unwrap_or_default();
+    if fees.trade_fee_denominator == 0 && fees.trade_fee_numerator != 0
+        || fees.owner_trade_fee_denominator == 0 && fees.owner_trade_fee_numerator != 0
+        || fees.owner_withdraw_fee_denominator == 0 && fees.owner_withdraw_fee_numerator != 0
+        || fees.host_fee_denominator == 0 && fees.host_fee_numerator != 0
+    {
+        return Err(ApiError::bad_request("Fee denominators must be non-zero when a numerator is set"));
+    }
+
+    // The curve calculator is packed into a fixed 32-byte slot after the type byte.
+    let mut calculator = [0u8; 32];
+    let curve_tag = match curve_type.unwrap_or(SwapCurveType::ConstantProduct) {
+        SwapCurveType::ConstantProduct => 0,
+        curve => {
+            let parameter = curve_parameter
+                .filter(|parameter| *parameter > 0)
+                .ok_or_else(|| ApiError::bad_request("curveParameter must be greater than 0 for this curve"))?;
+            calculator[..8].copy_from_slice(&parameter.to_le_bytes());
+            match curve {
+                SwapCurveType::ConstantPrice => 1,
+                _ => 3,
+            }
+        }
+    };
+
+    let mut data = vec![INITIALIZE_TAG];
+    for value in [
+        fees.trade_fee_numerator,
+        fees.trade_fee_denominator,
+        fees.owner_trade_fee_numerator,
+        fees.owner_trade_fee_denominator,
+        fees.owner_withdraw_fee_numerator,
+        fees.owner_withdraw_fee_denominator,
+        fees.host_fee_numerator,
+        fees.host_fee_denominator,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(curve_tag);
+    data.extend_from_slice(&calculator);
+
+    let client = state.rpc()?;
+    let pool_token_program = mint_programs(client, &[pool_mint_pubkey]).await?[0];
+
+    let mut instructions = Vec::new();
+    if let Some(payer_pubkey) = payer_pubkey {
+        let rent = client.get_minimum_balance_for_rent_exemption(SWAP_ACCOUNT_LEN).await?;
+        instructions.push(create_account(
+            &payer_pubkey,
+            &swap_pubkey,
+            rent,
+            SWAP_ACCOUNT_LEN as u64,
+            &TOKEN_SWAP_PROGRAM_ID,
+        ));
+    }
+    instructions.push(Instruction {
+        program_id: TOKEN_SWAP_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(swap_pubkey, true),
+            AccountMeta::new_readonly(swap_authority(&swap_pubkey), false),
+            AccountMeta::new_readonly(token_a_pubkey, false),
+            AccountMeta::new_readonly(token_b_pubkey, false),
+            AccountMeta::new(pool_mint_pubkey, false),
+            AccountMeta::new_readonly(fee_account_pubkey, false),
+            AccountMeta::new(destination_pubkey, false),
+            AccountMeta::new_readonly(pool_token_program, false),
+        ],
+        data,
+    });
+
+    Ok(success_response(InstructionsData {
+        instructions: instructions.iter().map(instruction_to_data).collect(),
+    }))
+}
+
+pub async fn swap(State(state): State<AppState>, Json(payload): Json<TokenSwapRequest>) -> ApiResult {
+    let TokenSwapRequest {
+        swap,
+        owner,
+        source_mint,
+        amount_in,
+        minimum_amount_out,
+        source,
+        destination,
+        host_fee_account,
+    } = payload;
+
+    if swap.is_empty() || owner.is_empty() || source_mint.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: swap, owner, or sourceMint"));
+    }
+    if amount_in == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let swap_pubkey = parse_pubkey(&swap, "swap")?;
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+    let source_mint_pubkey = parse_pubkey(&source_mint, "source mint")?;
+    let host_fee_pubkey = parse_optional_pubkey(host_fee_account.as_deref(), "host fee account")?;
+
+    let client = state.rpc()?;
+    let swap = fetch_swap(client, &swap_pubkey).await?;
+
+    let a_to_b = if source_mint_pubkey == swap.token_a_mint {
+        true
+    } else if source_mint_pubkey == swap.token_b_mint {
+        false
+    } else {
+        return Err(ApiError::bad_request("sourceMint is not one of the swap's mints"));
+    };
+    let (swap_source, source_mint, source_program) = swap.side(a_to_b);
+    let (swap_destination, destination_mint, destination_program) = swap.side(!a_to_b);
+
+    let user_source = user_token_account(&owner_pubkey, &source_mint, &source_program, source.as_deref(), "source")?;
+    let user_destination = user_token_account(
+        &owner_pubkey,
+        &destination_mint,
+        &destination_program,
+        destination.as_deref(),
+        "destination",
+    )?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(swap.swap, false),
+        AccountMeta::new_readonly(swap.authority, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new(user_source, false),
+        AccountMeta::new(swap_source, false),
+        AccountMeta::new(swap_destination, false),
+        AccountMeta::new(user_destination, false),
+        AccountMeta::new(swap.pool_mint, false),
+        AccountMeta::new(swap.pool_fee_account, false),
+        AccountMeta::new_readonly(source_mint, false),
+        AccountMeta::new_readonly(destination_mint, false),
+        AccountMeta::new_readonly(source_program, false),
+        AccountMeta::new_readonly(destination_program, false),
+        AccountMeta::new_readonly(swap.pool_token_program, false),
+    ];
+    if let Some(host_fee_pubkey) = host_fee_pubkey {
+        accounts.push(AccountMeta::new(host_fee_pubkey, false));
+    }
+
+    let ix = swap_instruction(SWAP_TAG, &[amount_in, minimum_amount_out], accounts);
+
+    Ok(success_response(InstructionsData { instructions: vec![instruction_to_data(&ix)] }))
+}
+
+pub async fn deposit(State(state): State<AppState>, Json(payload): Json<TokenSwapDepositRequest>) -> ApiResult {
+    let TokenSwapDepositRequest {
+        swap,
+        owner,
+        pool_token_amount,
+        maximum_token_a_amount,
+        maximum_token_b_amount,
+        token_a_account,
+        token_b_account,
+        pool_token_account,
+    } = payload;
+
+    if swap.is_empty() || owner.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: swap or owner"));
+    }
+    if pool_token_amount == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let swap_pubkey = parse_pubkey(&swap, "swap")?;
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+
+    let client = state.rpc()?;
+    let swap = fetch_swap(client, &swap_pubkey).await?;
+
+    let user_a = user_token_account(
+        &owner_pubkey,
+        &swap.token_a_mint,
+        &swap.token_a_program,
+        token_a_account.as_deref(),
+        "token A account",
+    )?;
+    let user_b = user_token_account(
+        &owner_pubkey,
+        &swap.token_b_mint,
+        &swap.token_b_program,
+        token_b_account.as_deref(),
+        "token B account",
+    )?;
+    let user_pool = user_token_account(
+        &owner_pubkey,
+        &swap.pool_mint,
+        &swap.pool_token_program,
+        pool_token_account.as_deref(),
+        "pool token account",
+    )?;
+
+    let ix = swap_instruction(
+        DEPOSIT_ALL_TAG,
+        &[pool_token_amount, maximum_token_a_amount, maximum_token_b_amount],
+        vec![
+            AccountMeta::new_readonly(swap.swap, false),
+            AccountMeta::new_readonly(swap.authority, false),
+            AccountMeta::new_readonly(owner_pubkey, true),
+            AccountMeta::new(user_a, false),
+            AccountMeta::new(user_b, false),
+            AccountMeta::new(swap.token_a, false),
+            AccountMeta::new(swap.token_b, false),
+            AccountMeta::new(swap.pool_mint, false),
+            AccountMeta::new(user_pool, false),
+            AccountMeta::new_readonly(swap.token_a_mint, false),
+            AccountMeta::new_readonly(swap.token_b_mint, false),
+            AccountMeta::new_readonly(swap.token_a_program, false),
+            AccountMeta::new_readonly(swap.token_b_program, false),
+            AccountMeta::new_readonly(swap.pool_token_program, false),
+        ],
+    );
+
+    Ok(success_response(InstructionsData { instructions: vec![instruction_to_data(&ix)] }))
+}
+
+pub async fn withdraw(State(state): State<AppState>, Json(payload): Json<TokenSwapWithdrawRequest>) -> ApiResult {
+    let TokenSwapWithdrawRequest {
+        swap,
+        owner,
+        pool_token_amount,
+        minimum_token_a_amount,
+        minimum_token_b_amount,
+        token_a_account,
+        token_b_account,
+        pool_token_account,
+    } = payload;
+
+    if swap.is_empty() || owner.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: swap or owner"));
+    }
+    if pool_token_amount == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let swap_pubkey = parse_pubkey(&swap, "swap")?;
+    let owner_pubkey = parse_pubkey(&owner, "owner")?;
+
+    let client = state.rpc()?;
+    let swap = fetch_swap(client, &swap_pubkey).await?;
+
+    let user_a = user_token_account(
+        &owner_pubkey,
+        &swap.token_a_mint,
+        &swap.token_a_program,
+        token_a_account.as_deref(),
+        "token A account",
+    )?;
+    let user_b = user_token_account(
+        &owner_pubkey,
+        &swap.token_b_mint,
+        &swap.token_b_program,
+        token_b_account.as_deref(),
+        "token B account",
+    )?;
+    let user_pool = user_token_account(
+        &owner_pubkey,
+        &swap.pool_mint,
+        &swap.pool_token_program,
+        pool_token_account.as_deref(),
+        "pool token account",
+    )?;
+
+    let ix = swap_instruction(
+        WITHDRAW_ALL_TAG,
+        &[pool_token_amount, minimum_token_a_amount, minimum_token_b_amount],
+        vec![
+            AccountMeta::new_readonly(swap.swap, false),
+            AccountMeta::new_readonly(swap.authority, false),
+            AccountMeta::new_readonly(owner_pubkey, true),
+            AccountMeta::new(swap.pool_mint, false),
+            AccountMeta::new(user_pool, false),
+            AccountMeta::new(swap.token_a, false),
+            AccountMeta::new(swap.token_b, false),
+            AccountMeta::new(user_a, false),
+            AccountMeta::new(user_b, false),
+            AccountMeta::new(swap.pool_fee_account, false),
+            AccountMeta::new_readonly(swap.token_a_mint, false),
+            AccountMeta::new_readonly(swap.token_b_mint, false),
+            AccountMeta::new_readonly(swap.pool_token_program, false),
+            AccountMeta::new_readonly(swap.token_a_program, false),
+            AccountMeta::new_readonly(swap.token_b_program, false),
+        ],
+    );
+
+    Ok(success_response(InstructionsData { instructions: vec![instruction_to_data(&ix)] }))
+}
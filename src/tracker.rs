@@ -0,0 +1,258 @@
+//! Background rebroadcasting for submitted transactions.
+//!
+//! `/tx/submit?track=true` hands the transaction to a worker that resends it
+//! until it reaches the requested commitment, fails, or can no longer land.
+//! When the blockhash expires the worker re-signs against a fresh one if the
+//! request supplied the signers' keys; durable-nonce transactions never expire
+//! and are simply resent. With a fee policy, every rebuild also raises the
+//! compute unit price.
+
+use axum::{extract::Path, http::StatusCode};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, transaction::VersionedTransaction};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::compute_budget::{message_compute_budget, set_message_compute_unit_price};
+use crate::signer::KeySigner;
+use crate::tx::{sign_transaction, uses_durable_nonce};
+use crate::types::{FeePolicy, TrackedStatus, TrackedTransactionData};
+use crate::utils::{success_response, ApiError, ApiResult};
+
+const REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+/// Gives up on a transaction that has not landed after this long.
+const MAX_TRACKING_DURATION: Duration = Duration::from_secs(300);
+/// How long finished entries stay queryable.
+const FINISHED_RETENTION: Duration = Duration::from_secs(600);
+/// Durable-nonce transactions never expire, so fee escalation runs on a timer instead.
+const DURABLE_NONCE_REPRICE_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_FEE_INCREASE_PERCENT: u32 = 100;
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// A worker gives up after `MAX_TRACKING_DURATION`, so an entry still pending
+/// this long after being tracked has lost its worker.
+const STALLED_AFTER: Duration = Duration::from_secs(MAX_TRACKING_DURATION.as_secs() + 60);
+
+struct TrackedEntry {
+    data: TrackedTransactionData,
+    tracked_at: Instant,
+    finished_at: Option<Instant>,
+}
+
+fn tracked() -> &'static Mutex<HashMap<String, TrackedEntry>> {
+    static TRACKED: OnceLock<Mutex<HashMap<String, TrackedEntry>>> = OnceLock::new();
+    TRACKED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn update(id: &str, apply: impl FnOnce(&mut TrackedTransactionData)) {
+    let mut tracked = tracked().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(entry) = tracked.get_mut(id) {
+        apply(&mut entry.data);
+        if entry.data.status != TrackedStatus::Pending {
+            entry.finished_at.get_or_insert_with(Instant::now);
+        }
+    }
+}
+
+fn finish(id: &str, status: TrackedStatus, err: Option<String>) {
+    update(id, |data| {
+        data.status = status;
+        data.err = err;
+    });
+}
+
+/// A sent transaction and what the worker may do to get it landed.
+pub struct Submission {
+    pub client: Arc<RpcClient>,
+    pub transaction: VersionedTransaction,
+    /// Keys for re-signing; without them an expired transaction is abandoned.
+    pub signers: Vec<KeySigner>,
+    pub commitment: CommitmentConfig,
+    pub fee_policy: Option<FeePolicy>,
+}
+
+/// Registers an already-sent transaction and starts rebroadcasting it.
+/// Returns the tracking id, which is the transaction's first signature.
+pub fn track(submission: Submission) -> String {
+    let id = submission.transaction.signatures[0].to_string();
+    let compute_unit_price = submission
+        .fee_policy
+        .map(|_| message_compute_budget(&submission.transaction.message).unit_price);
+    {
+        let mut tracked = tracked().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        tracked.retain(|_, entry| entry.finished_at.is_none_or(|at| at.elapsed() < FINISHED_RETENTION));
+        tracked.insert(
+            id.clone(),
+            TrackedEntry {
+                data: TrackedTransactionData {
+                    id: id.clone(),
+                    status: TrackedStatus::Pending,
+                    signature: id.clone(),
+                    signatures: vec![id.clone()],
+                    attempts: 1,
+                    rebuilds: 0,
+                    compute_unit_price,
+                    err: None,
+                },
+                tracked_at: Instant::now(),
+                finished_at: None,
+            },
+        );
+    }
+
+    tokio::spawn(rebroadcast(id.clone(), submission));
+    id
+}
+
+/// Next price under `policy`, always at least one micro-lamport higher until
+/// capped. A price already above the cap is kept, never lowered.
+fn escalated_price(current: u64, policy: &FeePolicy) -> u64 {
+    let percent = u64::from(policy.increase_percent.unwrap_or(DEFAULT_FEE_INCREASE_PERCENT));
+    let raised = current.saturating_mul(100 + percent) / 100;
+    raised.max(current.saturating_add(1)).min(policy.max_compute_unit_price).max(current)
+}
+
+async fn rebroadcast(id: String, submission: Submission) {
+    let Submission { client, mut transaction, signers, commitment, fee_policy } = submission;
+    let started = Instant::now();
+    let durable_nonce = uses_durable_nonce(&transaction.message);
+    let config = RpcSendTransactionConfig { skip_preflight: true, ..Default::default() };
+    let mut signatures = vec![transaction.signatures[0]];
+    let mut last_build = Instant::now();
+
+    loop {
+        tokio::time::sleep(REBROADCAST_INTERVAL).await;
+
+        // Transient RPC trouble (including rate limiting) just delays the next round.
+        // An earlier version of a durable-nonce transaction can still land, so
+        // every signature it has carried is checked.
+        let status = match client.get_signature_statuses(&signatures).await {
+            Ok(statuses) => statuses.value.into_iter().flatten().next(),
+            Err(_) => None,
+        };
+        if let Some(status) = status {
+            if let Some(err) = status.err {
+                return finish(&id, TrackedStatus::Failed, Some(err.to_string()));
+            }
+            if status.satisfies_commitment(commitment) {
+                return finish(&id, TrackedStatus::Confirmed, None);
+            }
+            // Landed but not yet at the requested commitment; resending is pointless.
+            continue;
+        }
+
+        if started.elapsed() > MAX_TRACKING_DURATION {
+            return finish(&id, TrackedStatus::Expired, Some("Transaction did not land in time".to_string()));
+        }
+
+        // Rebuilding while the old version can still land risks executing twice,
+        // so regular transactions are only rebuilt once their blockhash expires.
+        // Durable-nonce versions exclude each other, so they can be repriced on a timer.
+        let rebuild = if durable_nonce {
+            fee_policy.is_some() && last_build.elapsed() >= DURABLE_NONCE_REPRICE_INTERVAL
+        } else {
+            let blockhash = transaction.message.recent_blockhash();
+            !client
+                .is_blockhash_valid(blockhash, CommitmentConfig::processed())
+                .await
+                .unwrap_or(true)
+        };
+        if rebuild {
+            if signers.is_empty() {
+                return finish(&id, TrackedStatus::Expired, Some("Blockhash expired".to_string()));
+            }
+            if !durable_nonce {
+                let Ok(blockhash) = client.get_latest_blockhash().await else {
+                    continue;
+                };
+                transaction.message.set_recent_blockhash(blockhash);
+            }
+            let price = fee_policy.map(|policy| {
+                let current = message_compute_budget(&transaction.message).unit_price;
+                let price = escalated_price(current, &policy);
+                set_message_compute_unit_price(&mut transaction.message, price);
+                price
+            });
+            if let Err(err) = sign_transaction(&mut transaction, &signers).await {
+                return finish(&id, TrackedStatus::Failed, Some(err.message));
+            }
+
+            last_build = Instant::now();
+            signatures.push(transaction.signatures[0]);
+            let signature = transaction.signatures[0].to_string();
+            update(&id, |data| {
+                data.rebuilds += 1;
+                data.signature = signature.clone();
+                data.signatures.push(signature);
+                if price.is_some() {
+                    data.compute_unit_price = price;
+                }
+            });
+        }
+
+        if client.send_transaction_with_config(&transaction, config).await.is_ok() {
+            update(&id, |data| data.attempts += 1);
+        }
+    }
+}
+
+/// How many tracked transactions are pending, and how many of those have
+/// stalled.
+pub fn queue_counts() -> (usize, usize) {
+    let tracked = tracked().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let pending = tracked.values().filter(|entry| entry.data.status == TrackedStatus::Pending);
+    let stalled = pending.clone().filter(|entry| entry.tracked_at.elapsed() > STALLED_AFTER).count();
+    (pending.count(), stalled)
+}
+
+/// Waits until no tracked transaction is pending or `deadline` passes, and
+/// returns the ids of those still pending.
+pub async fn drain(deadline: tokio::time::Instant) -> Vec<String> {
+    loop {
+        let pending: Vec<String> = {
+            let tracked = tracked().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            tracked
+                .values()
+                .filter(|entry| entry.data.status == TrackedStatus::Pending)
+                .map(|entry| entry.data.id.clone())
+                .collect()
+        };
+        if pending.is_empty() || tokio::time::Instant::now() >= deadline {
+            return pending;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL.min(deadline - tokio::time::Instant::now())).await;
+    }
+}
+
+pub async fn tracked_transaction(Path(id): Path<String>) -> ApiResult {
+    let tracked = tracked().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = tracked
+        .get(&id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "No tracked transaction with that id"))?;
+    Ok(success_response(&entry.data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_compute_unit_price: u64) -> FeePolicy {
+        FeePolicy { increase_percent: Some(50), max_compute_unit_price }
+    }
+
+    #[test]
+    fn escalation_raises_the_price_up_to_the_cap() {
+        assert_eq!(escalated_price(1_000, &policy(10_000)), 1_500);
+        assert_eq!(escalated_price(1, &policy(10_000)), 2);
+        assert_eq!(escalated_price(8_000, &policy(10_000)), 10_000);
+        assert_eq!(escalated_price(10_000, &policy(10_000)), 10_000);
+    }
+
+    #[test]
+    fn escalation_never_lowers_a_price_above_the_cap() {
+        assert_eq!(escalated_price(20_000, &policy(10_000)), 20_000);
+    }
+}
@@ -0,0 +1,524 @@
+//! Parsed views of transactions that have already landed on chain.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use solana_client::{
+    client_error::ClientErrorKind,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcBlockConfig, RpcTransactionConfig},
+    rpc_request::RpcError,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta, TransactionDetails, UiLoadedAddresses,
+    UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use solana_vote_interface::program::ID as VOTE_PROGRAM_ID;
+use tokio::task::JoinSet;
+
+use std::str::FromStr;
+
+use crate::config::CursorConfig;
+use crate::cursor::{Cursor, CursorError, Direction};
+use crate::rpc::commitment_config;
+use crate::state::AppState;
+use crate::tx::{decode_instruction_data, program_name};
+use crate::types::{
+    BalanceAtData, BalanceAtQuery, BalanceChangeData, BlockData, BlockQuery, Commitment, HistoryData, HistoryEntryData,
+    HistoryQuery, ParsedInstructionData, ParsedTransactionData, TokenBalanceChangeData, TransactionQuery,
+    TransferSummaryData,
+};
+use crate::utils::{parse_pubkey, success_response, ApiError, ApiResult};
+
+/// Highest transaction version this service can parse.
+pub const MAX_SUPPORTED_TRANSACTION_VERSION: u8 = 0;
+const DEFAULT_HISTORY_PAGE_SIZE: usize = 100;
+/// `getSignaturesForAddress` returns at most this many signatures per call.
+const MAX_HISTORY_PAGE_SIZE: usize = 1000;
+/// Hydration costs one `getTransaction` per signature, so hydrated pages are smaller.
+const MAX_HYDRATED_HISTORY: usize = 50;
+/// Point-in-time balances give up rather than page through more history than this.
+const MAX_BALANCE_HISTORY_SCAN: usize = 10_000;
+/// RPC errors for blocks that were cleaned up, are not available yet, or were skipped.
+const BLOCK_UNAVAILABLE_CODES: [i64; 4] = [-32001, -32004, -32007, -32009];
+/// RPC error for a missing account or one that is not a token account.
+const INVALID_PARAMS_CODE: i64 = -32602;
+
+fn token_amount(balance: &UiTransactionTokenBalance) -> u64 {
+    balance.ui_token_amount.amount.parse().unwrap_or_default()
+}
+
+/// Pairs pre- and post-transaction token balances by account, keeping only
+/// the accounts whose balance moved.
+fn token_balance_changes(
+    keys: &[String],
+    pre: Vec<UiTransactionTokenBalance>,
+    post: Vec<UiTransactionTokenBalance>,
+) -> Vec<TokenBalanceChangeData> {
+    let mut indexes: Vec<u8> = pre.iter().chain(&post).map(|balance| balance.account_index).collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    indexes
+        .into_iter()
+        .filter_map(|index| {
+            let before = pre.iter().find(|balance| balance.account_index == index);
+            let after = post.iter().find(|balance| balance.account_index == index);
+            let balance = after.or(before)?;
+            let pre_amount = before.map_or(0, token_amount);
+            let post_amount = after.map_or(0, token_amount);
+            if pre_amount == post_amount {
+                return None;
+            }
+            let decimals = balance.ui_token_amount.decimals;
+            Some(TokenBalanceChangeData {
+                account: keys.get(index as usize).cloned().unwrap_or_default(),
+                mint: balance.mint.clone(),
+                owner: Option::from(balance.owner.clone()),
+                decimals,
+                pre_amount,
+                post_amount,
+                ui_change: spl_token::amount_to_ui_amount(post_amount, decimals)
+                    - spl_token::amount_to_ui_amount(pre_amount, decimals),
+            })
+        })
+        .collect()
+}
+
+/// Every account the transaction loaded, in the order instructions index them.
+fn account_keys(transaction: &VersionedTransaction, meta: &UiTransactionStatusMeta) -> Vec<String> {
+    // Lookup-table addresses follow the static keys: writable ones first.
+    let mut keys: Vec<String> = transaction.message.static_account_keys().iter().map(Pubkey::to_string).collect();
+    let loaded: Option<&UiLoadedAddresses> = meta.loaded_addresses.as_ref().into();
+    if let Some(loaded) = loaded {
+        keys.extend(loaded.writable.iter().cloned());
+        keys.extend(loaded.readonly.iter().cloned());
+    }
+    keys
+}
+
+/// Builds the parsed view of a fetched transaction. Returns `None` when the
+/// RPC node omitted the binary transaction or its status metadata.
+pub fn parse_transaction(
+    slot: u64,
+    block_time: Option<i64>,
+    encoded: EncodedTransactionWithStatusMeta,
+) -> Option<ParsedTransactionData> {
+    let transaction = encoded.transaction.decode()?;
+    let meta = encoded.meta?;
+    let message = &transaction.message;
+    let keys = account_keys(&transaction, &meta);
+
+    let instructions = message
+        .instructions()
+        .iter()
+        .map(|ix| {
+            let program_id = message.static_account_keys().get(ix.program_id_index as usize);
+            ParsedInstructionData {
+                program_id: program_id.map(Pubkey::to_string).unwrap_or_default(),
+                program: program_id.and_then(program_name),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|index| keys.get(*index as usize).cloned().unwrap_or_default())
+                    .collect(),
+                data: bs58::encode(&ix.data).into_string(),
+                decoded: program_id.and_then(|program_id| decode_instruction_data(program_id, &ix.data)),
+            }
+        })
+        .collect();
+
+    let balance_changes = meta
+        .pre_balances
+        .iter()
+        .zip(&meta.post_balances)
+        .enumerate()
+        .filter(|(_, (pre, post))| pre != post)
+        .map(|(index, (pre, post))| BalanceChangeData {
+            account: keys.get(index).cloned().unwrap_or_default(),
+            pre_lamports: *pre,
+            post_lamports: *post,
+            change: *post as i64 - *pre as i64,
+        })
+        .collect();
+
+    let token_balance_changes = token_balance_changes(
+        &keys,
+        Option::from(meta.pre_token_balances).unwrap_or_default(),
+        Option::from(meta.post_token_balances).unwrap_or_default(),
+    );
+
+    Some(ParsedTransactionData {
+        signature: transaction.signatures.first().map(Signature::to_string).unwrap_or_default(),
+        slot,
+        block_time,
+        version: match message {
+            VersionedMessage::Legacy(_) => "legacy",
+            VersionedMessage::V0(_) => "v0",
+        },
+        success: meta.err.is_none(),
+        err: meta.err.map(|err| err.to_string()),
+        fee: meta.fee,
+        compute_units_consumed: Option::from(meta.compute_units_consumed),
+        accounts: keys,
+        instructions,
+        balance_changes,
+        token_balance_changes,
+        logs: Option::from(meta.log_messages).unwrap_or_default(),
+    })
+}
+
+/// `getTransaction` and friends only serve confirmed or finalized data.
+pub fn history_commitment(commitment: Option<Commitment>) -> Result<Option<Commitment>, ApiError> {
+    match commitment {
+        Some(Commitment::Processed) => Err(ApiError::bad_request("commitment must be confirmed or finalized")),
+        commitment => Ok(commitment),
+    }
+}
+
+/// Fetches a landed transaction, mapping unknown signatures to 404.
+async fn fetch_transaction(
+    client: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, ApiError> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(MAX_SUPPORTED_TRANSACTION_VERSION),
+    };
+    // The client fails to deserialize the null result returned for unknown signatures.
+    client
+        .get_transaction_with_config(signature, config)
+        .await
+        .map_err(|err| match err.kind() {
+            ClientErrorKind::SerdeJson(_) => ApiError::new(StatusCode::NOT_FOUND, "Transaction not found"),
+            _ => ApiError::from(err),
+        })
+}
+
+async fn fetch_parsed_transaction(
+    client: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<ParsedTransactionData, ApiError> {
+    let fetched = fetch_transaction(client, signature, commitment).await?;
+    parse_transaction(fetched.slot, fetched.block_time, fetched.transaction)
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "RPC returned a transaction that could not be parsed"))
+}
+
+pub async fn get_transaction(
+    State(state): State<AppState>,
+    Path(signature): Path<String>,
+    Query(query): Query<TransactionQuery>,
+) -> ApiResult {
+    let signature = Signature::from_str(&signature).map_err(|_| ApiError::bad_request("Invalid signature"))?;
+    let commitment = history_commitment(query.commitment)?;
+
+    let client = state.rpc()?;
+    let commitment = commitment.map(commitment_config).unwrap_or_else(|| client.commitment());
+    let parsed = fetch_parsed_transaction(client, &signature, commitment).await?;
+
+    Ok(success_response(parsed))
+}
+
+/// How a transaction moved `address`'s SOL and tokens.
+fn transfer_summary(address: &str, transaction: ParsedTransactionData) -> TransferSummaryData {
+    let lamports_change = transaction
+        .balance_changes
+        .iter()
+        .find(|change| change.account == address)
+        .map_or(0, |change| change.change);
+    let token_changes = transaction
+        .token_balance_changes
+        .into_iter()
+        .filter(|change| change.account == address || change.owner.as_deref() == Some(address))
+        .collect();
+
+    TransferSummaryData {
+        fee: transaction.fee,
+        fee_payer: transaction.accounts.first().cloned(),
+        lamports_change,
+        token_changes,
+    }
+}
+
+fn parse_cursor(
+    config: &CursorConfig,
+    token: Option<&str>,
+    direction: Direction,
+    field: &str,
+) -> Result<Option<Cursor>, ApiError> {
+    let Some(token) = token else {
+        return Ok(None);
+    };
+    let cursor = Cursor::decode(config, token)?;
+    if cursor.direction != direction {
+        return Err(ApiError::bad_request(format!("{field} does not accept this cursor")).with_code("CURSOR_INVALID"));
+    }
+    Ok(Some(cursor))
+}
+
+fn cursor_signature(cursor: Option<&Cursor>) -> Result<Option<Signature>, ApiError> {
+    cursor
+        .map(|cursor| Signature::from_str(&cursor.signature).map_err(|_| CursorError::Malformed.into()))
+        .transpose()
+}
+
+pub async fn address_history(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult {
+    let address = parse_pubkey(&pubkey, "address")?;
+    let commitment = history_commitment(query.commitment)?;
+
+    let max_limit = if query.hydrate { MAX_HYDRATED_HISTORY } else { MAX_HISTORY_PAGE_SIZE };
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_PAGE_SIZE.min(max_limit));
+    if limit == 0 || limit > max_limit {
+        return Err(ApiError::bad_request(format!("limit must be between 1 and {max_limit}")));
+    }
+
+    let before = parse_cursor(state.cursor(), query.before.as_deref(), Direction::Before, "before")?;
+    let until = parse_cursor(state.cursor(), query.until.as_deref(), Direction::After, "until")?;
+
+    let client = state.rpc()?;
+    let commitment = commitment.map(commitment_config).unwrap_or_else(|| client.commitment());
+    let config = GetConfirmedSignaturesForAddress2Config {
+        before: cursor_signature(before.as_ref())?,
+        until: cursor_signature(until.as_ref())?,
+        limit: Some(limit),
+        commitment: Some(commitment),
+    };
+    let statuses = client.get_signatures_for_address_with_config(&address, config).await?;
+
+    let mut transfers: Vec<Option<TransferSummaryData>> = statuses.iter().map(|_| None).collect();
+    if query.hydrate {
+        let address = address.to_string();
+        let mut fetches = JoinSet::new();
+        for (index, status) in statuses.iter().enumerate() {
+            let Ok(signature) = Signature::from_str(&status.signature) else {
+                continue;
+            };
+            let client = state.rpc_handle();
+            fetches.spawn(async move {
+                (index, fetch_parsed_transaction(&client, &signature, commitment).await)
+            });
+        }
+        while let Some(joined) = fetches.join_next().await {
+            if let Ok((index, fetched)) = joined {
+                transfers[index] = Some(transfer_summary(&address, fetched?));
+            }
+        }
+    }
+
+    // `next_cursor` pages further back; `latest_cursor` later fetches only newer activity.
+    let next_cursor = (statuses.len() == limit)
+        .then(|| statuses.last())
+        .flatten()
+        .map(|status| Cursor { slot: status.slot, signature: status.signature.clone(), direction: Direction::Before });
+    let latest_cursor = statuses
+        .first()
+        .map(|status| Cursor { slot: status.slot, signature: status.signature.clone(), direction: Direction::After })
+        .or(until);
+
+    let entries = statuses
+        .into_iter()
+        .zip(transfers)
+        .map(|(status, transfers)| HistoryEntryData {
+            signature: status.signature,
+            slot: status.slot,
+            block_time: status.block_time,
+            confirmation_status: status
+                .confirmation_status
+                .map(|status| format!("{:?}", status).to_lowercase()),
+            err: status.err.map(|err| err.to_string()),
+            memo: status.memo,
+            transfers,
+        })
+        .collect();
+
+    Ok(success_response(HistoryData {
+        address: address.to_string(),
+        signatures: entries,
+        next_cursor: next_cursor.map(|cursor| cursor.encode(state.cursor())),
+        latest_cursor: latest_cursor.map(|cursor| cursor.encode(state.cursor())),
+    }))
+}
+
+fn is_vote_transaction(transaction: &ParsedTransactionData) -> bool {
+    let vote_program = VOTE_PROGRAM_ID.to_string();
+    !transaction.instructions.is_empty()
+        && transaction.instructions.iter().all(|ix| ix.program_id == vote_program)
+}
+
+pub async fn get_block(
+    State(state): State<AppState>,
+    Path(slot): Path<u64>,
+    Query(query): Query<BlockQuery>,
+) -> ApiResult {
+    let commitment = history_commitment(query.commitment)?;
+    let addresses = query
+        .addresses
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| parse_pubkey(address, "address").map(|address| address.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let client = state.rpc()?;
+    let config = RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        transaction_details: Some(TransactionDetails::Full),
+        rewards: Some(false),
+        commitment: Some(commitment.map(commitment_config).unwrap_or_else(|| client.commitment())),
+        max_supported_transaction_version: Some(MAX_SUPPORTED_TRANSACTION_VERSION),
+    };
+    let block = client.get_block_with_config(slot, config).await.map_err(|err| match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if BLOCK_UNAVAILABLE_CODES.contains(code) =>
+        {
+            ApiError::new(StatusCode::NOT_FOUND, format!("No block available for slot {slot}"))
+        }
+        _ => ApiError::from(err),
+    })?;
+
+    let encoded = block.transactions.unwrap_or_default();
+    let transaction_count = encoded.len();
+    let transactions = encoded
+        .into_iter()
+        .filter_map(|transaction| parse_transaction(slot, block.block_time, transaction))
+        .filter(|transaction| query.include_votes || !is_vote_transaction(transaction))
+        .filter(|transaction| {
+            addresses.is_empty() || transaction.accounts.iter().any(|account| addresses.contains(account))
+        })
+        .collect();
+
+    Ok(success_response(BlockData {
+        slot,
+        blockhash: block.blockhash,
+        previous_blockhash: block.previous_blockhash,
+        parent_slot: block.parent_slot,
+        block_time: block.block_time,
+        block_height: block.block_height,
+        transaction_count,
+        transactions,
+    }))
+}
+
+/// Balance of a token account as of a past slot or time.
+///
+/// The balance only changes in transactions that touch the account, so the
+/// balance at the target is the pre-balance of the first such transaction
+/// after it, or the current balance when nothing touched it since.
+pub async fn token_balance_at(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Query(query): Query<BalanceAtQuery>,
+) -> ApiResult {
+    let account = parse_pubkey(&pubkey, "token account")?;
+    let commitment = history_commitment(query.commitment)?;
+    if query.slot.is_none() == query.timestamp.is_none() {
+        return Err(ApiError::bad_request("Exactly one of slot or timestamp is required"));
+    }
+
+    let is_after_target = |slot: u64, block_time: Option<i64>| match (query.slot, query.timestamp) {
+        (Some(target), _) => slot > target,
+        // Without a block time the transaction cannot be placed, so it is assumed recent.
+        (None, Some(target)) => block_time.is_none_or(|time| time > target),
+        (None, None) => false,
+    };
+
+    let client = state.rpc()?;
+    let commitment = commitment.map(commitment_config).unwrap_or_else(|| client.commitment());
+
+    // Walk back through the history until reaching the target.
+    let mut boundary: Option<Signature> = None;
+    let mut scanned = 0;
+    let mut before = None;
+    'walk: loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(MAX_HISTORY_PAGE_SIZE),
+            commitment: Some(commitment),
+        };
+        let page = client.get_signatures_for_address_with_config(&account, config).await?;
+        for status in &page {
+            if !is_after_target(status.slot, status.block_time) {
+                break 'walk;
+            }
+            scanned += 1;
+            boundary = Signature::from_str(&status.signature).ok();
+        }
+        if page.len() < MAX_HISTORY_PAGE_SIZE {
+            break;
+        }
+        if scanned >= MAX_BALANCE_HISTORY_SCAN {
+            return Err(ApiError::bad_request(format!(
+                "More than {} transactions touched the account since the target; pick a more recent one",
+                MAX_BALANCE_HISTORY_SCAN
+            ))
+            .with_code("TOO_MANY_RESULTS"));
+        }
+        before = boundary;
+    }
+
+    let (amount, decimals) = match boundary {
+        Some(signature) => {
+            let fetched = fetch_transaction(client, &signature, commitment).await?;
+            let transaction = fetched.transaction.transaction.decode();
+            let meta = fetched.transaction.meta;
+            let (Some(transaction), Some(meta)) = (transaction, meta) else {
+                let message = "RPC returned a transaction that could not be parsed";
+                return Err(ApiError::new(StatusCode::BAD_GATEWAY, message));
+            };
+            let keys = account_keys(&transaction, &meta);
+            let address = account.to_string();
+            let index = keys.iter().position(|key| *key == address);
+            let pre_balances: Vec<UiTransactionTokenBalance> =
+                Option::from(meta.pre_token_balances).unwrap_or_default();
+            let post_balances: Vec<UiTransactionTokenBalance> =
+                Option::from(meta.post_token_balances).unwrap_or_default();
+            // No pre-balance means the token account did not exist yet.
+            let pre = pre_balances.iter().find(|balance| Some(balance.account_index as usize) == index);
+            let post = post_balances.iter().find(|balance| Some(balance.account_index as usize) == index);
+            (pre.map_or(0, token_amount), pre.or(post).map(|balance| balance.ui_token_amount.decimals))
+        }
+        None => match client.get_token_account_balance_with_commitment(&account, commitment).await {
+            Ok(balance) => (balance.value.amount.parse().unwrap_or_default(), Some(balance.value.decimals)),
+            // Closed, or never a token account.
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ClientErrorKind::RpcError(RpcError::RpcResponseError { code: INVALID_PARAMS_CODE, .. })
+                ) =>
+            {
+                (0, None)
+            }
+            Err(err) => return Err(ApiError::from(err)),
+        },
+    };
+
+    Ok(success_response(BalanceAtData {
+        pubkey: account.to_string(),
+        slot: query.slot,
+        timestamp: query.timestamp,
+        amount,
+        decimals,
+        ui_amount: decimals.map(|decimals| spl_token::amount_to_ui_amount(amount, decimals)),
+        source_signature: boundary.map(|signature| signature.to_string()),
+        transactions_scanned: scanned,
+    }))
+}
@@ -0,0 +1,824 @@
+//! Transaction assembly and handling for instructions built elsewhere in the API.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use serde_json::json;
+use solana_client::{
+    nonblocking::{nonce_utils, rpc_client::RpcClient},
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+};
+use solana_loader_v3_interface::instruction::UpgradeableLoaderInstruction;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    ed25519_program,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::{Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    secp256k1_program,
+    signature::Signature,
+    system_instruction::{advance_nonce_account, SystemInstruction},
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_sdk_ids::{address_lookup_table, bpf_loader_upgradeable, compute_budget, system_program};
+use solana_stake_interface::{instruction::StakeInstruction, program::ID as STAKE_PROGRAM_ID};
+use solana_vote_interface::{instruction::VoteInstruction, program::ID as VOTE_PROGRAM_ID};
+use spl_associated_token_account::instruction::AssociatedTokenAccountInstruction;
+use tokio::task::JoinSet;
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::bubblegum::{ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID};
+use crate::candy_machine::{CANDY_GUARD_PROGRAM_ID, CANDY_MACHINE_PROGRAM_ID};
+use crate::compute_budget::{
+    compute_unit_price_instruction, message_compute_budget, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT,
+    MAX_COMPUTE_UNIT_LIMIT,
+};
+use crate::keystore::resolve_signers;
+use crate::rpc::{check_rate_limit, commitment_config, provider_name};
+use crate::signer::KeySigner;
+use crate::stake_pool::STAKE_POOL_PROGRAM_ID;
+use crate::state::AppState;
+use crate::token_swap::TOKEN_SWAP_PROGRAM_ID;
+use crate::tracker::{self, Submission};
+use crate::types::{
+    BlockhashData, BlockhashQuery, DataEncoding, DecodedAccountData, DecodedInstructionData, InstructionInput,
+    PackedTransactionData, SignatureCheckData, SignatureData, SignatureStatus, TxBuildData, TxBuildRequest,
+    TxDecodeData, TxDecodeRequest, TxEstimateCuData, TxEstimateCuRequest, TxFeeData, TxFeeRequest, TxPackData,
+    TxPackRequest, TxSignData, TxSignRequest, TxSignersData, TxSignersRequest, TxStatusData, TxStatusQuery,
+    TxSubmitData, TxSubmitQuery, TxSubmitRequest, TxVerifyData, TxVerifyRequest,
+};
+use crate::utils::{instruction_to_data, parse_optional_pubkey, parse_pubkey, success_response, ApiError, ApiResult};
+
+/// Upper bound on how long `/tx/submit` waits; blockhash expiry usually ends the wait sooner.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_CU_MARGIN_PERCENT: u32 = 10;
+
+/// Builds an `Instruction` from its JSON form, which mirrors `instruction_to_data` output.
+pub fn parse_instruction(input: &InstructionInput) -> Result<Instruction, ApiError> {
+    let program_id = parse_pubkey(&input.program_id, "program id")?;
+    let accounts = input
+        .accounts
+        .iter()
+        .map(|account| {
+            Ok(AccountMeta {
+                pubkey: parse_pubkey(&account.pubkey, "account")?,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let data = match input.encoding.unwrap_or(DataEncoding::Base58) {
+        DataEncoding::Base58 => bs58::decode(&input.data).into_vec().ok(),
+        DataEncoding::Base64 => STANDARD.decode(&input.data).ok(),
+    }
+    .ok_or_else(|| ApiError::bad_request("Invalid instruction data encoding"))?;
+
+    Ok(Instruction { program_id, accounts, data })
+}
+
+pub fn parse_blockhash(value: &str) -> Result<Hash, ApiError> {
+    Hash::from_str(value).map_err(|_| ApiError::bad_request("Invalid recent blockhash"))
+}
+
+/// Serializes a transaction in the wire format wallets and RPC nodes accept.
+pub fn encode_transaction<T: Serialize>(transaction: &T) -> Result<String, ApiError> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|e| ApiError::bad_request(format!("Failed to serialize transaction: {e}")))?;
+    if bytes.len() > PACKET_DATA_SIZE {
+        return Err(ApiError::bad_request(format!(
+            "Transaction is {} bytes, over the {PACKET_DATA_SIZE}-byte limit",
+            bytes.len()
+        )));
+    }
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Decodes a base64 wire-format transaction, legacy or versioned.
+pub fn decode_transaction(encoded: &str) -> Result<VersionedTransaction, ApiError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| ApiError::bad_request("Transaction must be base64-encoded"))?;
+    let transaction: VersionedTransaction =
+        bincode::deserialize(&bytes).map_err(|_| ApiError::bad_request("Invalid transaction encoding"))?;
+    if transaction.message.sanitize().is_err() {
+        return Err(ApiError::bad_request("Transaction message is malformed"));
+    }
+    Ok(transaction)
+}
+
+/// Reads the current durable nonce, checking the account is initialized and
+/// controlled by `authority`.
+async fn fetch_durable_nonce(client: &RpcClient, nonce_account: &Pubkey, authority: &Pubkey) -> Result<Hash, ApiError> {
+    let account = client
+        .get_account_with_commitment(nonce_account, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Nonce account not found"))?;
+    let data = nonce_utils::data_from_account(&account)
+        .map_err(|e| ApiError::bad_request(format!("Invalid nonce account: {e}")))?;
+
+    if data.authority != *authority {
+        return Err(ApiError::bad_request(format!(
+            "Nonce authority is {}, not {}",
+            data.authority, authority
+        )));
+    }
+    Ok(data.blockhash())
+}
+
+/// Signer keys in signature order.
+pub fn required_signers(message: &Message) -> Vec<String> {
+    message.account_keys[..message.header.num_required_signatures as usize]
+        .iter()
+        .map(|key| key.to_string())
+        .collect()
+}
+
+pub async fn tx_build(State(state): State<AppState>, Json(payload): Json<TxBuildRequest>) -> ApiResult {
+    let TxBuildRequest { instructions, fee_payer, fee_payer_key, recent_blockhash, nonce_account, nonce_authority } =
+        payload;
+
+    if instructions.is_empty() || (fee_payer.is_empty() && fee_payer_key.is_none()) {
+        return Err(ApiError::bad_request("Missing required fields: instructions or feePayer"));
+    }
+
+    if nonce_authority.is_some() && nonce_account.is_none() {
+        return Err(ApiError::bad_request("nonceAuthority requires nonceAccount"));
+    }
+
+    let fee_payer_pubkey = match fee_payer_key {
+        Some(_) if !fee_payer.is_empty() => {
+            return Err(ApiError::bad_request("Provide either feePayer or feePayerKey, not both"));
+        }
+        Some(name) => state.fee_payer(&name)?.pubkey(),
+        None => parse_pubkey(&fee_payer, "fee payer")?,
+    };
+    let nonce_account = parse_optional_pubkey(nonce_account.as_deref(), "nonce account")?;
+    let nonce_authority =
+        parse_optional_pubkey(nonce_authority.as_deref(), "nonce authority")?.unwrap_or(fee_payer_pubkey);
+    let mut instructions = instructions.iter().map(parse_instruction).collect::<Result<Vec<_>, _>>()?;
+
+    let blockhash = match (recent_blockhash, nonce_account) {
+        (Some(blockhash), _) => parse_blockhash(&blockhash)?,
+        (None, Some(nonce_account)) => fetch_durable_nonce(state.rpc()?, &nonce_account, &nonce_authority).await?,
+        (None, None) => state.rpc()?.get_latest_blockhash().await?,
+    };
+
+    // The runtime only treats a transaction as durable when the advance is its first instruction.
+    if let Some(nonce_account) = nonce_account {
+        instructions.insert(0, advance_nonce_account(&nonce_account, &nonce_authority));
+    }
+
+    let message = Message::new_with_blockhash(&instructions, Some(&fee_payer_pubkey), &blockhash);
+    let signers = required_signers(&message);
+    let transaction = Transaction::new_unsigned(message);
+
+    Ok(success_response(TxBuildData {
+        transaction: encode_transaction(&transaction)?,
+        recent_blockhash: blockhash.to_string(),
+        nonce_account: nonce_account.map(|key| key.to_string()),
+        signers,
+    }))
+}
+
+/// Required signer keys paired with their current signature, if any.
+fn signer_slots(transaction: &VersionedTransaction) -> Vec<SignatureData> {
+    let required = transaction.message.header().num_required_signatures as usize;
+    transaction.message.static_account_keys()[..required]
+        .iter()
+        .enumerate()
+        .map(|(index, key)| SignatureData {
+            pubkey: key.to_string(),
+            signature: transaction
+                .signatures
+                .get(index)
+                .filter(|signature| **signature != Signature::default())
+                .map(|signature| signature.to_string()),
+        })
+        .collect()
+}
+
+/// Fills the signature slots belonging to `signers`, leaving the others as they are.
+pub async fn sign_transaction(transaction: &mut VersionedTransaction, signers: &[KeySigner]) -> Result<(), ApiError> {
+    let required = transaction.message.header().num_required_signatures as usize;
+    let signer_keys = transaction.message.static_account_keys()[..required].to_vec();
+    transaction.signatures.resize(required, Signature::default());
+
+    let message = transaction.message.serialize();
+    for signer in signers {
+        let index = signer_keys
+            .iter()
+            .position(|key| *key == signer.pubkey())
+            .ok_or_else(|| ApiError::bad_request(format!("{} is not a required signer", signer.pubkey())))?;
+        transaction.signatures[index] = signer.sign(&message).await?;
+    }
+    Ok(())
+}
+
+/// Whether the message starts with `advance_nonce_account`, making it a durable-nonce transaction.
+pub fn uses_durable_nonce(message: &VersionedMessage) -> bool {
+    message.instructions().first().is_some_and(|ix| {
+        message.static_account_keys().get(ix.program_id_index as usize) == Some(&system_program::ID)
+            && matches!(
+                bincode::deserialize::<SystemInstruction>(&ix.data),
+                Ok(SystemInstruction::AdvanceNonceAccount)
+            )
+    })
+}
+
+pub async fn tx_sign(State(state): State<AppState>, Json(payload): Json<TxSignRequest>) -> ApiResult {
+    let TxSignRequest { transaction, secret_keys, key_ids, fee_payer_key } = payload;
+
+    if transaction.is_empty() || (secret_keys.is_empty() && key_ids.is_empty() && fee_payer_key.is_none()) {
+        return Err(ApiError::bad_request("Missing required fields: transaction, secretKeys, keyIds or feePayerKey"));
+    }
+
+    // Signing only fills the slots for the supplied keys, so parties can sign
+    // the same transaction in turn without clobbering each other.
+    let mut transaction = decode_transaction(&transaction)?;
+    let mut signers = resolve_signers(&state, &secret_keys, &key_ids)?;
+    if let Some(name) = fee_payer_key {
+        signers.push(state.fee_payer(&name)?.clone());
+    }
+    sign_transaction(&mut transaction, &signers).await?;
+
+    Ok(success_response(TxSignData {
+        transaction: encode_transaction(&transaction)?,
+        signatures: signer_slots(&transaction),
+    }))
+}
+
+pub async fn tx_signers(Json(payload): Json<TxSignersRequest>) -> ApiResult {
+    let TxSignersRequest { transaction } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    let transaction = decode_transaction(&transaction)?;
+    let signatures = signer_slots(&transaction);
+    let missing: Vec<String> = signatures
+        .iter()
+        .filter(|slot| slot.signature.is_none())
+        .map(|slot| slot.pubkey.clone())
+        .collect();
+
+    Ok(success_response(TxSignersData { complete: missing.is_empty(), missing, signatures }))
+}
+
+/// Human-readable names for the programs this service builds instructions for.
+pub fn program_name(program_id: &Pubkey) -> Option<&'static str> {
+    let names: [(Pubkey, &str); 22] = [
+        (system_program::ID, "system"),
+        (spl_token::ID, "spl-token"),
+        (spl_token_2022::ID, "spl-token-2022"),
+        (spl_associated_token_account::ID, "spl-associated-token-account"),
+        (spl_memo::ID, "spl-memo"),
+        (spl_memo::v1::ID, "spl-memo-v1"),
+        (compute_budget::ID, "compute-budget"),
+        (STAKE_PROGRAM_ID, "stake"),
+        (VOTE_PROGRAM_ID, "vote"),
+        (address_lookup_table::ID, "address-lookup-table"),
+        (bpf_loader_upgradeable::ID, "bpf-loader-upgradeable"),
+        (ed25519_program::ID, "ed25519"),
+        (secp256k1_program::ID, "secp256k1"),
+        (mpl_token_metadata::ID, "token-metadata"),
+        (BUBBLEGUM_PROGRAM_ID, "bubblegum"),
+        (ACCOUNT_COMPRESSION_PROGRAM_ID, "spl-account-compression"),
+        (NOOP_PROGRAM_ID, "spl-noop"),
+        (CANDY_MACHINE_PROGRAM_ID, "candy-machine"),
+        (CANDY_GUARD_PROGRAM_ID, "candy-guard"),
+        (STAKE_POOL_PROGRAM_ID, "spl-stake-pool"),
+        (TOKEN_SWAP_PROGRAM_ID, "spl-token-swap"),
+        (crate::nft::TOKEN_AUTH_RULES_PROGRAM_ID, "token-auth-rules"),
+    ];
+    names.iter().find(|(id, _)| id == program_id).map(|(_, name)| *name)
+}
+
+/// Decodes instruction data for programs with a published Rust interface.
+pub fn decode_instruction_data(program_id: &Pubkey, data: &[u8]) -> Option<String> {
+    if *program_id == system_program::ID {
+        bincode::deserialize::<SystemInstruction>(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == spl_token::ID {
+        spl_token::instruction::TokenInstruction::unpack(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::instruction::TokenInstruction::unpack(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == spl_associated_token_account::ID {
+        // An empty payload is the original `Create`.
+        if data.is_empty() {
+            Some(format!("{:?}", AssociatedTokenAccountInstruction::Create))
+        } else {
+            AssociatedTokenAccountInstruction::try_from_slice(data).ok().map(|ix| format!("{ix:?}"))
+        }
+    } else if *program_id == spl_memo::ID || *program_id == spl_memo::v1::ID {
+        std::str::from_utf8(data).ok().map(|memo| format!("Memo({memo:?})"))
+    } else if *program_id == compute_budget::ID {
+        ComputeBudgetInstruction::try_from_slice(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == STAKE_PROGRAM_ID {
+        bincode::deserialize::<StakeInstruction>(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == VOTE_PROGRAM_ID {
+        bincode::deserialize::<VoteInstruction>(data).ok().map(|ix| format!("{ix:?}"))
+    } else if *program_id == bpf_loader_upgradeable::ID {
+        bincode::deserialize::<UpgradeableLoaderInstruction>(data).ok().map(|ix| format!("{ix:?}"))
+    } else {
+        None
+    }
+}
+
+pub async fn tx_decode(Json(payload): Json<TxDecodeRequest>) -> ApiResult {
+    let TxDecodeRequest { transaction } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    let transaction = decode_transaction(&transaction)?;
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    // v0 messages reference lookup-table entries after the static keys:
+    // all writable entries first, then all readonly ones.
+    let mut keys: Vec<DecodedAccountData> = static_keys
+        .iter()
+        .map(|key| DecodedAccountData {
+            pubkey: Some(key.to_string()),
+            lookup_table: None,
+            lookup_index: None,
+            is_signer: false,
+            is_writable: false,
+        })
+        .collect();
+    let lookups = message.address_table_lookups().unwrap_or_default();
+    for writable in [true, false] {
+        for lookup in lookups {
+            let indexes = if writable { &lookup.writable_indexes } else { &lookup.readonly_indexes };
+            keys.extend(indexes.iter().map(|index| DecodedAccountData {
+                pubkey: None,
+                lookup_table: Some(lookup.account_key.to_string()),
+                lookup_index: Some(*index),
+                is_signer: false,
+                is_writable: false,
+            }));
+        }
+    }
+    for (index, key) in keys.iter_mut().enumerate() {
+        key.is_signer = message.is_signer(index);
+        key.is_writable = message.is_maybe_writable(index, None);
+    }
+
+    let instructions = message
+        .instructions()
+        .iter()
+        .map(|ix| {
+            let program_id = static_keys
+                .get(ix.program_id_index as usize)
+                .ok_or_else(|| ApiError::bad_request("Instruction references a missing program account"))?;
+            let accounts = ix
+                .accounts
+                .iter()
+                .map(|index| {
+                    keys.get(*index as usize)
+                        .cloned()
+                        .ok_or_else(|| ApiError::bad_request("Instruction references a missing account"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(DecodedInstructionData {
+                program_id: program_id.to_string(),
+                program: program_name(program_id),
+                accounts,
+                data: bs58::encode(&ix.data).into_string(),
+                decoded: decode_instruction_data(program_id, &ix.data),
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(success_response(TxDecodeData {
+        version: match message {
+            VersionedMessage::Legacy(_) => "legacy",
+            VersionedMessage::V0(_) => "v0",
+        },
+        fee_payer: static_keys.first().map(|key| key.to_string()),
+        recent_blockhash: message.recent_blockhash().to_string(),
+        signatures: signer_slots(&transaction),
+        address_table_lookups: lookups.iter().map(|lookup| lookup.account_key.to_string()).collect(),
+        instructions,
+    }))
+}
+
+/// Outcome of waiting for a submitted transaction.
+pub struct Confirmation {
+    pub confirmed: bool,
+    pub confirmation_status: Option<String>,
+    pub slot: Option<u64>,
+    pub err: Option<String>,
+}
+
+/// Polls the signature until it reaches `commitment`, fails, or can no longer land.
+pub async fn await_confirmation(
+    client: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+    blockhash: &Hash,
+) -> Result<Confirmation, ApiError> {
+    let deadline = Instant::now() + CONFIRMATION_TIMEOUT;
+    loop {
+        let status = client.get_signature_statuses(&[*signature]).await?.value.pop().flatten();
+        let reached = status.as_ref().is_some_and(|status| status.satisfies_commitment(commitment));
+        let failed = status.as_ref().is_some_and(|status| status.err.is_some());
+
+        // Once the blockhash has expired an unseen transaction will never be processed.
+        if reached
+            || failed
+            || Instant::now() >= deadline
+            || !client.is_blockhash_valid(blockhash, CommitmentConfig::processed()).await?
+        {
+            return Ok(Confirmation {
+                confirmed: reached && !failed,
+                confirmation_status: status
+                    .as_ref()
+                    .map(|status| format!("{:?}", status.confirmation_status()).to_lowercase()),
+                slot: status.as_ref().map(|status| status.slot),
+                err: status.and_then(|status| status.err).map(|err| err.to_string()),
+            });
+        }
+
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+    }
+}
+
+/// Sends to every broadcast endpoint concurrently and returns the first acceptance,
+/// leaving the remaining sends to finish in the background.
+async fn broadcast(
+    state: &AppState,
+    transaction: &VersionedTransaction,
+    config: RpcSendTransactionConfig,
+) -> Result<(Signature, String), ApiError> {
+    let primary = (state.rpc_url().to_string(), state.rpc_handle());
+    let extra = state
+        .broadcast_endpoints()
+        .iter()
+        .filter(|endpoint| check_rate_limit(&provider_name(&endpoint.url)).is_ok())
+        .map(|endpoint| (endpoint.url.clone(), endpoint.client.clone()));
+
+    let mut sends = JoinSet::new();
+    for (url, client) in std::iter::once(primary).chain(extra) {
+        let transaction = transaction.clone();
+        sends.spawn(async move {
+            let signature = client.send_transaction_with_config(&transaction, config).await?;
+            Ok::<_, ApiError>((signature, provider_name(&url)))
+        });
+    }
+
+    let mut first_error = None;
+    while let Some(result) = sends.join_next().await {
+        match result {
+            Ok(Ok(accepted)) => {
+                sends.detach_all();
+                return Ok(accepted);
+            }
+            Ok(Err(err)) => {
+                first_error.get_or_insert(err);
+            }
+            Err(_) => {}
+        }
+    }
+    Err(first_error
+        .unwrap_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "No RPC endpoint accepted the transaction")))
+}
+
+pub async fn tx_submit(
+    State(state): State<AppState>,
+    Query(query): Query<TxSubmitQuery>,
+    Json(payload): Json<TxSubmitRequest>,
+) -> ApiResult {
+    let TxSubmitRequest { transaction, commitment, skip_preflight, secret_keys, key_ids, fee_policy, fanout } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    let transaction = decode_transaction(&transaction)?;
+    let missing: Vec<String> = signer_slots(&transaction)
+        .into_iter()
+        .filter(|slot| slot.signature.is_none())
+        .map(|slot| slot.pubkey)
+        .collect();
+    if !missing.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "Transaction is missing signatures from: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let has_keys = !secret_keys.is_empty() || !key_ids.is_empty();
+    if (has_keys || fee_policy.is_some()) && !query.track {
+        return Err(ApiError::bad_request("secretKeys, keyIds and feePolicy are only used with track=true"));
+    }
+
+    if fee_policy.is_some() {
+        if !has_keys {
+            return Err(ApiError::bad_request("feePolicy requires secretKeys or keyIds to re-sign with a higher fee"));
+        }
+        if compute_unit_price_instruction(&transaction.message).is_none() {
+            return Err(ApiError::bad_request("feePolicy requires a SetComputeUnitPrice instruction"));
+        }
+    }
+
+    // Re-signing needs every required signer, otherwise the keys are of no use.
+    let signers = resolve_signers(&state, &secret_keys, &key_ids)?;
+    if !signers.is_empty() {
+        let required = transaction.message.header().num_required_signatures as usize;
+        let uncovered: Vec<String> = transaction.message.static_account_keys()[..required]
+            .iter()
+            .filter(|key| !signers.iter().any(|signer| signer.pubkey() == **key))
+            .map(|key| key.to_string())
+            .collect();
+        if !uncovered.is_empty() {
+            return Err(ApiError::bad_request(format!(
+                "secretKeys and keyIds must cover every signer; missing: {}",
+                uncovered.join(", ")
+            )));
+        }
+    }
+
+    let client = state.rpc()?;
+    let config = RpcSendTransactionConfig {
+        skip_preflight,
+        preflight_commitment: Some(client.commitment().commitment),
+        ..Default::default()
+    };
+    let (signature, accepted_by) = if fanout {
+        let (signature, provider) = broadcast(&state, &transaction, config).await?;
+        (signature, Some(provider))
+    } else {
+        (client.send_transaction_with_config(&transaction, config).await?, None)
+    };
+
+    if query.track {
+        let commitment = commitment_config(commitment.unwrap_or(state.commitment()));
+        let tracking_id = tracker::track(Submission {
+            client: state.rpc_handle(),
+            transaction,
+            signers,
+            commitment,
+            fee_policy,
+        });
+        return Ok(success_response(TxSubmitData {
+            signature: signature.to_string(),
+            tracking_id: Some(tracking_id),
+            accepted_by,
+            confirmed: false,
+            confirmation_status: None,
+            slot: None,
+            err: None,
+        }));
+    }
+
+    let confirmation = match commitment {
+        Some(commitment) => {
+            let blockhash = transaction.message.recent_blockhash();
+            await_confirmation(client, &signature, commitment_config(commitment), blockhash).await?
+        }
+        None => Confirmation { confirmed: false, confirmation_status: None, slot: None, err: None },
+    };
+
+    Ok(success_response(TxSubmitData {
+        signature: signature.to_string(),
+        tracking_id: None,
+        accepted_by,
+        confirmed: confirmation.confirmed,
+        confirmation_status: confirmation.confirmation_status,
+        slot: confirmation.slot,
+        err: confirmation.err,
+    }))
+}
+
+pub async fn tx_fee(State(state): State<AppState>, Json(payload): Json<TxFeeRequest>) -> ApiResult {
+    let TxFeeRequest { transaction } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    let transaction = decode_transaction(&transaction)?;
+    let client = state.rpc()?;
+    let fee = match &transaction.message {
+        VersionedMessage::Legacy(message) => client.get_fee_for_message(message).await?,
+        VersionedMessage::V0(message) => client.get_fee_for_message(message).await?,
+    };
+
+    let budget = message_compute_budget(&transaction.message);
+    let priority_fee = budget.priority_fee().min(fee);
+
+    Ok(success_response(TxFeeData {
+        fee,
+        base_fee: fee - priority_fee,
+        priority_fee,
+        compute_unit_limit: budget.unit_limit,
+        compute_unit_price: budget.unit_price,
+    }))
+}
+
+pub async fn tx_estimate_cu(State(state): State<AppState>, Json(payload): Json<TxEstimateCuRequest>) -> ApiResult {
+    let TxEstimateCuRequest { transaction, margin_percent } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    // Signatures and blockhash are irrelevant to compute usage, so unsigned
+    // transactions built with a stale blockhash can be estimated too.
+    let transaction = decode_transaction(&transaction)?;
+    let client = state.rpc()?;
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(client.commitment()),
+        ..Default::default()
+    };
+    let simulation = client.simulate_transaction_with_config(&transaction, config).await?.value;
+
+    if let Some(err) = simulation.err {
+        return Err(ApiError::bad_request(format!("Simulation failed: {err}"))
+            .with_code("SIMULATION_FAILED")
+            .with_details(json!({ "logs": simulation.logs.unwrap_or_default() })));
+    }
+    let units_consumed = simulation
+        .units_consumed
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_GATEWAY, "Simulation did not report compute units"))?;
+
+    let margin = u64::from(margin_percent.unwrap_or(DEFAULT_CU_MARGIN_PERCENT));
+    let compute_unit_limit =
+        units_consumed.saturating_mul(100 + margin).div_ceil(100).min(u64::from(MAX_COMPUTE_UNIT_LIMIT)) as u32;
+
+    Ok(success_response(TxEstimateCuData {
+        units_consumed,
+        compute_unit_limit,
+        instruction: instruction_to_data(&ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit)),
+    }))
+}
+
+pub async fn tx_status(
+    State(state): State<AppState>,
+    Path(signature): Path<String>,
+    Query(query): Query<TxStatusQuery>,
+) -> ApiResult {
+    let signature = Signature::from_str(&signature).map_err(|_| ApiError::bad_request("Invalid signature"))?;
+
+    let client = state.rpc()?;
+    let statuses = if query.search_history {
+        client.get_signature_statuses_with_history(&[signature]).await?
+    } else {
+        client.get_signature_statuses(&[signature]).await?
+    };
+    let status = statuses.value.into_iter().next().flatten();
+
+    Ok(success_response(TxStatusData {
+        signature: signature.to_string(),
+        found: status.is_some(),
+        confirmation_status: status
+            .as_ref()
+            .map(|status| format!("{:?}", status.confirmation_status()).to_lowercase()),
+        confirmations: status.as_ref().and_then(|status| status.confirmations),
+        slot: status.as_ref().map(|status| status.slot),
+        err: status.and_then(|status| status.err).map(|err| err.to_string()),
+    }))
+}
+
+/// Wire size of `instructions` as a single unsigned transaction.
+fn transaction_size(instructions: &[Instruction], fee_payer: &Pubkey, blockhash: &Hash) -> usize {
+    let message = Message::new_with_blockhash(instructions, Some(fee_payer), blockhash);
+    bincode::serialized_size(&Transaction::new_unsigned(message)).map_or(usize::MAX, |size| size as usize)
+}
+
+pub async fn tx_pack(State(state): State<AppState>, Json(payload): Json<TxPackRequest>) -> ApiResult {
+    let TxPackRequest { instructions, fee_payer, recent_blockhash, compute_units_per_instruction } = payload;
+
+    if instructions.is_empty() || fee_payer.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: instructions or feePayer"));
+    }
+
+    let units_per_instruction = compute_units_per_instruction.unwrap_or(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT);
+    if units_per_instruction == 0 || units_per_instruction > MAX_COMPUTE_UNIT_LIMIT {
+        return Err(ApiError::bad_request(format!(
+            "computeUnitsPerInstruction must be between 1 and {MAX_COMPUTE_UNIT_LIMIT}"
+        )));
+    }
+    let max_per_transaction = (MAX_COMPUTE_UNIT_LIMIT / units_per_instruction) as usize;
+
+    let fee_payer_pubkey = parse_pubkey(&fee_payer, "fee payer")?;
+    let instructions = instructions.iter().map(parse_instruction).collect::<Result<Vec<_>, _>>()?;
+
+    let blockhash = match recent_blockhash {
+        Some(blockhash) => parse_blockhash(&blockhash)?,
+        None => state.rpc()?.get_latest_blockhash().await?,
+    };
+
+    // Prefixes each batch with its compute unit limit when the caller sized instructions.
+    let with_budget = |batch: &[Instruction]| -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(batch.len() + 1);
+        if compute_units_per_instruction.is_some() {
+            let limit = units_per_instruction * batch.len() as u32;
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        instructions.extend_from_slice(batch);
+        instructions
+    };
+
+    // Order matters to callers, so batches are contiguous; filling each one
+    // greedily is then optimal since adding an instruction never shrinks a batch.
+    let fits = |batch: &[Instruction]| {
+        batch.len() <= max_per_transaction
+            && transaction_size(&with_budget(batch), &fee_payer_pubkey, &blockhash) <= PACKET_DATA_SIZE
+    };
+    let mut batches: Vec<Vec<Instruction>> = Vec::new();
+    let mut current: Vec<Instruction> = Vec::new();
+    for (index, instruction) in instructions.into_iter().enumerate() {
+        current.push(instruction);
+        if fits(&current) {
+            continue;
+        }
+
+        let instruction = current.pop().expect("just pushed");
+        if !current.is_empty() {
+            batches.push(std::mem::replace(&mut current, vec![instruction]));
+        }
+        if current.is_empty() || !fits(&current) {
+            return Err(ApiError::bad_request(format!(
+                "Instruction {index} does not fit in a transaction on its own"
+            )));
+        }
+    }
+    batches.push(current);
+
+    let transactions = batches
+        .iter()
+        .map(|batch| {
+            let message = Message::new_with_blockhash(&with_budget(batch), Some(&fee_payer_pubkey), &blockhash);
+            let signers = required_signers(&message);
+            Ok(PackedTransactionData {
+                transaction: encode_transaction(&Transaction::new_unsigned(message))?,
+                instruction_count: batch.len(),
+                signers,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(success_response(TxPackData { recent_blockhash: blockhash.to_string(), transactions }))
+}
+
+pub async fn tx_verify(Json(payload): Json<TxVerifyRequest>) -> ApiResult {
+    let TxVerifyRequest { transaction } = payload;
+
+    if transaction.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: transaction"));
+    }
+
+    let transaction = decode_transaction(&transaction)?;
+    let message = transaction.message.serialize();
+    let required = transaction.message.header().num_required_signatures as usize;
+
+    let signatures: Vec<SignatureCheckData> = transaction.message.static_account_keys()[..required]
+        .iter()
+        .enumerate()
+        .map(|(index, key)| {
+            let signature = transaction.signatures.get(index).filter(|signature| **signature != Signature::default());
+            let status = match signature {
+                None => SignatureStatus::Missing,
+                Some(signature) if signature.verify(key.as_ref(), &message) => SignatureStatus::Valid,
+                Some(_) => SignatureStatus::Invalid,
+            };
+            SignatureCheckData {
+                pubkey: key.to_string(),
+                signature: signature.map(|signature| signature.to_string()),
+                status,
+            }
+        })
+        .collect();
+
+    Ok(success_response(TxVerifyData {
+        valid: signatures.iter().all(|check| check.status == SignatureStatus::Valid),
+        signatures,
+    }))
+}
+
+pub async fn latest_blockhash(State(state): State<AppState>, Query(query): Query<BlockhashQuery>) -> ApiResult {
+    let client = state.rpc()?;
+    let commitment = query.commitment.map(commitment_config).unwrap_or_else(|| client.commitment());
+    let (blockhash, last_valid_block_height) = client.get_latest_blockhash_with_commitment(commitment).await?;
+
+    Ok(success_response(BlockhashData { blockhash: blockhash.to_string(), last_valid_block_height }))
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
+use crate::amount::{deserialize_optional_u64, deserialize_u64};
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateTokenRequest {
-    pub mintAuthority: Option<String>,
+    #[serde(rename = "mintAuthority")]
+    pub mint_authority: Option<String>,
     pub mint: Option<String>,
     pub decimals: u8,
 }
@@ -38,27 +41,257 @@ pub struct TokenMintRequest {
     pub mint: Option<String>,
     pub destination: Option<String>,
     pub authority: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
     pub amount: Option<u64>
 }
 
+/// How `message` is written in sign and verify requests. Binary payloads
+/// that are not valid UTF-8 need base64 or hex.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageEncoding {
+    #[default]
+    Utf8,
+    Base64,
+    Hex,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureFormat {
+    #[default]
+    Base58,
+    Base64,
+    Hex,
+}
+
+/// What is actually signed for a message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageStandard {
+    /// The message bytes themselves.
+    #[default]
+    Raw,
+    /// The message wrapped in a version 0 Solana offchain message, as the
+    /// CLI's `sign-offchain-message` and wallets produce.
+    Offchain,
+}
+
+/// How /message/verify decides which bytes the signature covers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyMode {
+    /// Only the requested `standard`.
+    #[default]
+    Exact,
+    /// Every convention wallet adapters' `signMessage` is known to sign:
+    /// the raw bytes, an offchain message, or the JSON-stringified text.
+    Wallet,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct SignMsgRequest {
     pub message: String,
-    pub secret: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    #[serde(default)]
+    pub standard: MessageStandard,
+    /// Encoding of the returned signature.
+    #[serde(default)]
+    pub format: SignatureFormat,
+    pub secret: Option<SecretKey>,
+    /// Keystore key to sign with instead of `secret`.
+    pub key_id: Option<String>,
+}
+
+/// A secret key as a string (base58, hex or a JSON byte array) or as a raw
+/// byte array, in which case it is exactly a solana-keygen keypair file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SecretKey {
+    Encoded(String),
+    Bytes(Vec<u8>),
+}
+
+/// Never prints the key, so request types holding one can derive `Debug`.
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretKey({})", crate::redact::REDACTED)
+    }
+}
+
+impl SecretKey {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            SecretKey::Encoded(secret) => secret.is_empty(),
+            SecretKey::Bytes(bytes) => bytes.is_empty(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignMsgBatchRequest {
+    #[serde(default)]
+    pub messages: Vec<BatchMessage>,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    #[serde(default)]
+    pub standard: MessageStandard,
+    #[serde(default)]
+    pub format: SignatureFormat,
+    /// Key for every message that does not name its own.
+    pub secret: Option<SecretKey>,
+    pub key_id: Option<String>,
+}
+
+/// A message to sign with the batch's key, or with a key of its own.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchMessage {
+    Text(String),
+    Keyed(KeyedMessage),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyedMessage {
+    pub message: String,
+    pub secret: Option<SecretKey>,
+    pub key_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignedMessageData {
+    pub pubkey: String,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignMsgBatchData {
+    /// One per message, in request order.
+    pub signatures: Vec<SignedMessageData>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct VerifyMsgRequest {
+    #[serde(default)]
     pub message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    #[serde(default)]
+    pub standard: MessageStandard,
+    #[serde(default)]
+    pub mode: VerifyMode,
+    /// Base58, base64 or hex signature.
+    #[serde(default)]
     pub signature: String,
+    #[serde(default)]
     pub pubkey: String,
+    /// Only accept the signature if the message carries an unused challenge
+    /// issued to `pubkey`, and use that challenge up.
+    #[serde(default)]
+    pub require_challenge: bool,
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct VerifyMsgData {
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeQuery {
+    pub pubkey: Option<String>,
+    pub ttl_seconds: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChallengeData {
+    pub nonce: String,
+    pub pubkey: String,
+    pub expires_at: String,
+    /// Suggested text to sign; any message with the same `Nonce:` line works.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiwsInputRequest {
+    /// Host the user signs in to, e.g. `example.com`.
+    #[serde(default)]
+    pub domain: String,
+    /// Restricts the sign-in to one account; wallets fill it in otherwise.
+    pub address: Option<String>,
+    pub statement: Option<String>,
+    pub uri: Option<String>,
+    /// e.g. `mainnet` or `devnet`.
+    pub chain_id: Option<String>,
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// How long the sign-in may take; defaults to 10 minutes.
+    pub ttl_seconds: Option<u64>,
+}
+
+/// A sign-in input, shaped for a wallet's `signIn` method as-is.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SiwsInput {
+    pub domain: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    pub nonce: String,
+    /// RFC 3339 UTC timestamps.
+    pub issued_at: String,
+    pub expiration_time: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SiwsInputData {
+    pub input: SiwsInput,
+    /// The text a wallet signs for `input`, for wallets without `signIn`.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiwsVerifyRequest {
+    /// The signed message the wallet returned.
+    #[serde(default)]
+    pub signed_message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    /// Base58, base64 or hex signature.
+    #[serde(default)]
     pub signature: String,
+    /// Account that signed.
+    #[serde(default)]
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SiwsVerifyData {
+    pub address: String,
+    pub domain: String,
+    pub nonce: String,
+    pub issued_at: String,
+    pub expiration_time: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerifyMsgData {
+    /// Whether the signature matches; malformed input is an error instead.
+    pub valid: bool,
     pub pubkey: String,
     pub message: String,
+    /// The convention the signature matched: `raw`, `offchain` or `json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -72,7 +305,9 @@ pub struct VerifyMsgResponse {
 pub struct SendSOLRequest {
     pub from: String,
     pub to: String,
+    #[serde(deserialize_with = "deserialize_u64")]
     pub lamports: u64,
+    pub memo: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -80,17 +315,1993 @@ pub struct SendTokenRequest {
     pub destination: Option<String>,
     pub mint: Option<String>,
     pub owner: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
     pub amount: Option<u64>,
+    pub memo: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct SendTokenResponse {
-    pub success: bool,
-    pub data: TokenAccount
+#[serde(rename_all = "camelCase")]
+pub struct StakeDeactivateRequest {
+    pub stake_account: String,
+    pub authority: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct TokenAccount {
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeWithdrawRequest {
+    pub stake_account: String,
+    pub withdraw_authority: String,
+    pub recipient: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub lamports: u64,
+    pub custodian: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeSplitRequest {
+    pub stake_account: String,
+    pub authority: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub lamports: u64,
+    pub base: String,
+    pub seed: String,
+    /// When set, prepends a transfer funding the rent-exempt reserve of the new account.
+    pub payer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeMergeRequest {
+    pub destination_stake_account: String,
+    pub source_stake_account: String,
+    pub authority: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StakeSplitData {
+    pub split_stake_account: String,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InstructionsData {
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountBatchRequest {
+    pub pubkeys: Vec<String>,
+    /// Include the decoded `parsed` view of each account; defaults to true.
+    pub decode: Option<bool>,
+}
+
+/// How `/accounts/multiple` decodes one account.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum AccountDecodeHint {
+    /// Pick the layout from the owning program, as `/accounts/batch` does.
+    Auto,
+    Mint,
+    TokenAccount,
+    Nonce,
+    Stake,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountRequestEntry {
+    pub pubkey: String,
+    /// Layout to decode the account as; without one only the raw data is returned.
+    pub decode: Option<AccountDecodeHint>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountsMultipleRequest {
+    pub accounts: Vec<AccountRequestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountInfoData {
+    pub lamports: u64,
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    /// Raw account data, base64 encoded.
+    pub data: String,
+    /// Decoded view of the data for account layouts we understand.
+    pub parsed: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfoQuery {
+    /// Start of the returned data slice; defaults to 0.
+    pub data_offset: Option<usize>,
+    /// Returns only this many bytes of data when set.
+    pub data_length: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BalanceQuery {
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BalanceData {
+    pub pubkey: String,
+    pub lamports: u64,
+    pub sol: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropRequest {
+    pub pubkey: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub lamports: u64,
+    /// Commitment to wait for; the call returns right after requesting when omitted.
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AirdropData {
+    pub signature: String,
+    pub pubkey: String,
+    pub lamports: u64,
+    pub confirmed: bool,
+    pub confirmation_status: Option<String>,
+    pub err: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountBatchEntry {
+    pub pubkey: String,
+    pub account: Option<AccountInfoData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemcmpFilterInput {
+    pub offset: usize,
+    pub bytes: String,
+    /// Encoding of `bytes`; defaults to base58.
+    pub encoding: Option<DataEncoding>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramAccountsRequest {
+    pub program_id: String,
+    pub data_size: Option<u64>,
+    #[serde(default)]
+    pub memcmp: Vec<MemcmpFilterInput>,
+    pub data_offset: Option<usize>,
+    pub data_length: Option<usize>,
+    pub limit: Option<usize>,
+    /// Cursor from a previous page's `next_after`.
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProgramAccountsData {
+    pub program_id: String,
+    /// Accounts matching the filters across all pages.
+    pub total: usize,
+    /// Sorted by pubkey.
+    pub accounts: Vec<AccountBatchEntry>,
+    /// Pass as `after` to fetch the next page; absent on the last page.
+    pub next_after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StakeAuthorizeType {
+    Staker,
+    Withdrawer,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeAuthorizeRequest {
+    pub stake_account: String,
+    pub authority: String,
+    pub new_authority: String,
+    pub authorize_type: StakeAuthorizeType,
+    pub custodian: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeSetLockupRequest {
+    pub stake_account: String,
+    /// Current lockup custodian, or the withdraw authority when no lockup is in force.
+    pub custodian: String,
+    pub unix_timestamp: Option<i64>,
+    pub epoch: Option<u64>,
+    pub new_custodian: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StakeDelegationData {
+    pub voter: String,
+    pub stake: u64,
+    pub activation_epoch: u64,
+    /// `None` while the stake has not been deactivated.
+    pub deactivation_epoch: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StakeAccountData {
+    pub pubkey: String,
+    pub lamports: u64,
+    /// One of `initialized`, `activating`, `active`, `deactivating`, or `inactive`.
+    pub status: String,
+    pub staker: String,
+    pub withdrawer: String,
+    pub rent_exempt_reserve: u64,
+    pub lockup_unix_timestamp: i64,
+    pub lockup_epoch: u64,
+    pub lockup_custodian: String,
+    pub delegation: Option<StakeDelegationData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StakeAccountsData {
+    pub epoch: u64,
+    pub accounts: Vec<StakeAccountData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InflationRewardQuery {
+    /// Defaults to the last completed epoch.
+    pub epoch: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InflationRewardData {
+    pub pubkey: String,
+    pub epoch: Option<u64>,
+    /// False when the account earned nothing in the epoch.
+    pub found: bool,
+    /// Reward in lamports.
+    pub amount: u64,
+    pub post_balance: Option<u64>,
+    pub effective_slot: Option<u64>,
+    /// Vote account commission when the reward was credited.
+    pub commission: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransferFeeData {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub config_authority: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HolderConcentrationData {
+    /// Share of supply held by the largest account, as a percentage.
+    pub top_1_percent: f64,
+    /// Share of supply held by the ten largest accounts, as a percentage.
+    pub top_10_percent: f64,
+    pub largest_accounts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MintAuditData {
+    pub mint: String,
+    pub program_id: String,
+    pub supply: u64,
+    pub decimals: u8,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    /// `None` when no Metaplex or Token-2022 metadata exists for the mint.
+    pub metadata_mutable: Option<bool>,
+    pub metadata_update_authority: Option<String>,
+    pub extensions: Vec<String>,
+    pub transfer_fee: Option<TransferFeeData>,
+    pub permanent_delegate: Option<String>,
+    pub concentration: Option<HolderConcentrationData>,
+    /// Short machine-readable flags for each risk found, e.g. `mint_authority_active`.
+    pub risks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakePoolDepositSolRequest {
+    pub pool: String,
+    pub funder: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub lamports: u64,
+    /// Defaults to the funder's associated token account for the pool mint.
+    pub pool_token_account: Option<String>,
+    pub referrer_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakePoolWithdrawSolRequest {
+    pub pool: String,
+    pub authority: String,
+    pub destination: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub pool_tokens: u64,
+    pub pool_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakePoolDepositStakeRequest {
+    pub pool: String,
+    pub stake_account: String,
+    /// Owner of the minted pool tokens; defaults to the stake account's withdrawer.
+    pub owner: Option<String>,
+    pub pool_token_account: Option<String>,
+    pub referrer_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakePoolWithdrawStakeRequest {
+    pub pool: String,
+    pub authority: String,
+    /// Fresh keypair address that receives the split stake; must sign the transaction.
+    pub stake_receiver: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub pool_tokens: u64,
+    /// Validator to withdraw from; the reserve is used when omitted.
+    pub vote_account: Option<String>,
+    pub new_stake_authority: Option<String>,
+    pub pool_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MemoRequest {
+    pub memo: String,
+    /// Pubkeys that must sign the memo; may be empty.
+    #[serde(default)]
+    pub signers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputeBudgetRequest {
+    pub compute_unit_limit: Option<u32>,
+    /// Price per compute unit in micro-lamports.
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub compute_unit_price: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Ed25519InstructionRequest {
     pub pubkey: String,
-    pub isSigner: bool,
-}
\ No newline at end of file
+    pub message: String,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Secp256k1InstructionRequest {
+    /// 20-byte Ethereum address, hex encoded.
+    pub eth_address: String,
+    pub message: String,
+    /// Hex encoded `r || s`, optionally followed by the recovery byte `v`.
+    pub signature: String,
+    pub recovery_id: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramSetAuthorityRequest {
+    pub program: String,
+    pub authority: String,
+    pub new_authority: Option<String>,
+    /// Removes the upgrade authority, making the program permanently immutable.
+    #[serde(default)]
+    pub make_immutable: bool,
+    /// Uses `SetAuthorityChecked`, which also requires the new authority to sign.
+    #[serde(default)]
+    pub checked: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProgramUpgradeRequest {
+    pub program: String,
+    pub buffer: String,
+    pub authority: String,
+    /// Receives the lamports of the consumed buffer.
+    pub spill: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProgramCloseRequest {
+    pub program: Option<String>,
+    pub buffer: Option<String>,
+    pub recipient: String,
+    pub authority: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSwapFees {
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub trade_fee_numerator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub trade_fee_denominator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub owner_trade_fee_numerator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub owner_trade_fee_denominator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub owner_withdraw_fee_numerator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub owner_withdraw_fee_denominator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub host_fee_numerator: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub host_fee_denominator: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SwapCurveType {
+    ConstantProduct,
+    ConstantPrice,
+    Offset,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSwapInitializeRequest {
+    /// Fresh keypair address for the swap state; must sign the transaction.
+    pub swap: String,
+    /// When set, a `create_account` for the swap state is prepended.
+    pub payer: Option<String>,
+    /// Swap-owned token accounts, already funded and owned by the swap authority.
+    pub token_a: String,
+    pub token_b: String,
+    pub pool_mint: String,
+    pub fee_account: String,
+    /// Receives the initial pool tokens.
+    pub destination: String,
+    pub fees: Option<TokenSwapFees>,
+    pub curve_type: Option<SwapCurveType>,
+    /// Token B price for `constantPrice`, or token B offset for `offset`.
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub curve_parameter: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSwapRequest {
+    pub swap: String,
+    pub owner: String,
+    /// Mint being sold; must be one of the swap's two mints.
+    pub source_mint: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub amount_in: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub minimum_amount_out: u64,
+    /// Defaults to the owner's associated token accounts.
+    pub source: Option<String>,
+    pub destination: Option<String>,
+    pub host_fee_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSwapDepositRequest {
+    pub swap: String,
+    pub owner: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub pool_token_amount: u64,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub maximum_token_a_amount: u64,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub maximum_token_b_amount: u64,
+    pub token_a_account: Option<String>,
+    pub token_b_account: Option<String>,
+    pub pool_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSwapWithdrawRequest {
+    pub swap: String,
+    pub owner: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub pool_token_amount: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub minimum_token_a_amount: u64,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub minimum_token_b_amount: u64,
+    pub token_a_account: Option<String>,
+    pub token_b_account: Option<String>,
+    pub pool_token_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteWithdrawRequest {
+    pub vote_account: String,
+    pub withdraw_authority: String,
+    pub recipient: String,
+    /// Defaults to the full balance above the rent-exempt minimum.
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub lamports: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VoteAuthorizeType {
+    Voter,
+    Withdrawer,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteAuthorizeRequest {
+    pub vote_account: String,
+    pub authority: String,
+    pub new_authority: String,
+    pub authorize_type: VoteAuthorizeType,
+    /// Uses `AuthorizeChecked`, which also requires the new authority to sign.
+    #[serde(default)]
+    pub checked: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorRequest {
+    pub address: String,
+    pub share: u8,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataCreateRequest {
+    pub mint: String,
+    pub mint_authority: String,
+    /// Defaults to the mint authority.
+    pub payer: Option<String>,
+    /// Defaults to the mint authority.
+    pub update_authority: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorRequest>>,
+    pub is_mutable: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MetadataCreateData {
+    pub metadata: String,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftMintRequest {
+    /// Fresh keypair address for the mint; must sign the transaction.
+    pub mint: String,
+    /// Pays for all accounts and acts as the initial mint authority.
+    pub payer: String,
+    /// Receives the NFT; defaults to the payer.
+    pub owner: Option<String>,
+    /// Defaults to the payer.
+    pub update_authority: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorRequest>>,
+    pub is_mutable: Option<bool>,
+    /// Number of prints allowed from the master edition; defaults to 0.
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub max_supply: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NftMintData {
+    pub mint: String,
+    pub token_account: String,
+    pub metadata: String,
+    pub master_edition: String,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftTransferRequest {
+    pub mint: String,
+    /// Current owner of the NFT.
+    pub owner: String,
+    /// Wallet that receives the NFT.
+    pub destination: String,
+    /// Owner or delegate signing the transfer; defaults to the owner.
+    pub authority: Option<String>,
+    /// Defaults to the authority.
+    pub payer: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NftInstructionsData {
+    pub token_standard: &'static str,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftBurnRequest {
+    pub mint: String,
+    pub owner: String,
+    /// Owner or utility delegate signing the burn; defaults to the owner.
+    pub authority: Option<String>,
+    /// Defaults to the owner's associated token account.
+    pub token_account: Option<String>,
+    /// Mint of the master edition; required when burning a print edition.
+    pub master_edition_mint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CnftTreeCreateRequest {
+    /// Fresh keypair address for the tree account; must sign the transaction.
+    pub merkle_tree: String,
+    pub payer: String,
+    /// Defaults to the payer.
+    pub tree_creator: Option<String>,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: Option<u32>,
+    /// Lets anyone mint into the tree.
+    pub public: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CnftTreeData {
+    pub merkle_tree: String,
+    pub tree_config: String,
+    pub account_size: usize,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CnftMintRequest {
+    pub merkle_tree: String,
+    pub payer: String,
+    /// Tree creator or delegate; defaults to the payer.
+    pub tree_delegate: Option<String>,
+    pub leaf_owner: String,
+    /// Defaults to the leaf owner.
+    pub leaf_delegate: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorRequest>>,
+    pub is_mutable: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CnftTransferRequest {
+    pub asset_id: String,
+    pub new_leaf_owner: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadataQuery {
+    /// Also fetch and include the JSON document at the metadata uri.
+    #[serde(default)]
+    pub off_chain: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatorData {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CollectionData {
+    pub key: String,
+    pub verified: bool,
+}
+
+#[derive(Serialize)]
+pub struct TokenMetadataData {
+    pub mint: String,
+    /// `metaplex` or `token2022`, depending on where the metadata lives.
+    pub source: &'static str,
+    pub metadata_address: String,
+    pub update_authority: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: Option<u16>,
+    pub creators: Vec<CreatorData>,
+    pub primary_sale_happened: Option<bool>,
+    pub is_mutable: bool,
+    pub token_standard: Option<&'static str>,
+    pub collection: Option<CollectionData>,
+    /// Token-2022 key/value pairs beyond name, symbol, and uri.
+    pub additional_metadata: Vec<(String, String)>,
+    pub off_chain: Option<serde_json::Value>,
+    pub off_chain_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NftsByOwnerQuery {
+    /// 1-based page number.
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct OwnedNftData {
+    pub mint: String,
+    pub token_account: String,
+    /// `None` for NFT-shaped holdings without Metaplex metadata.
+    pub metadata: Option<TokenMetadataData>,
+}
+
+#[derive(Serialize)]
+pub struct NftsByOwnerData {
+    pub owner: String,
+    /// Number of NFT-shaped holdings across all pages.
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+    pub items: Vec<OwnedNftData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandyMachineMintRequest {
+    pub candy_machine: String,
+    /// Minter and recipient of the NFT; must sign the transaction.
+    pub buyer: String,
+    /// Fresh keypair address for the NFT mint; must sign the transaction.
+    pub nft_mint: String,
+    /// Defaults to the buyer.
+    pub payer: Option<String>,
+    /// Guard group label, required when the candy guard defines groups.
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CandyMachineMintData {
+    pub nft_mint: String,
+    pub candy_guard: String,
+    pub items_remaining: u64,
+    pub instructions: Vec<TokenData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum DataEncoding {
+    Base58,
+    Base64,
+}
+
+/// Accepts both camelCase and the snake_case keys instruction endpoints return.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetaInput {
+    pub pubkey: String,
+    #[serde(default, alias = "is_signer")]
+    pub is_signer: bool,
+    #[serde(default, alias = "is_writable")]
+    pub is_writable: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionInput {
+    #[serde(alias = "program_id")]
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<AccountMetaInput>,
+    #[serde(default, alias = "instruction_data", alias = "instructionData")]
+    pub data: String,
+    /// Encoding of `data`; defaults to base58.
+    pub encoding: Option<DataEncoding>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxBuildRequest {
+    pub instructions: Vec<InstructionInput>,
+    #[serde(default)]
+    pub fee_payer: String,
+    /// Name of a server-configured fee payer, instead of `fee_payer`.
+    pub fee_payer_key: Option<String>,
+    /// Fetched from the RPC when omitted. With a nonce account this is the
+    /// stored nonce, which lets the transaction be built fully offline.
+    pub recent_blockhash: Option<String>,
+    /// Durable nonce account; `advance_nonce_account` is prepended and the
+    /// nonce replaces the blockhash so the transaction does not expire.
+    pub nonce_account: Option<String>,
+    /// Defaults to the fee payer.
+    pub nonce_authority: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxBuildData {
+    /// Unsigned transaction, bincode-serialized and base64-encoded.
+    pub transaction: String,
+    /// The durable nonce when `nonce_account` is set.
+    pub recent_blockhash: String,
+    pub nonce_account: Option<String>,
+    /// Accounts whose signatures the transaction requires, in signature order.
+    pub signers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSignRequest {
+    /// Base64 wire-format transaction, legacy or versioned.
+    pub transaction: String,
+    #[serde(default)]
+    pub secret_keys: Vec<SecretKey>,
+    /// Keystore keys to sign with, alongside or instead of `secretKeys`.
+    #[serde(default)]
+    pub key_ids: Vec<String>,
+    /// Name of a server-configured fee payer to sign with as well.
+    pub fee_payer_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeePayerData {
+    pub name: String,
+    pub pubkey: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignatureData {
+    pub pubkey: String,
+    /// `None` while the signer has not signed yet.
+    pub signature: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxSignData {
+    pub transaction: String,
+    pub signatures: Vec<SignatureData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSignersRequest {
+    pub transaction: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxSignersData {
+    /// True once every required signer has signed.
+    pub complete: bool,
+    pub missing: Vec<String>,
+    pub signatures: Vec<SignatureData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxDecodeRequest {
+    pub transaction: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DecodedAccountData {
+    /// `None` for accounts loaded from an address lookup table.
+    pub pubkey: Option<String>,
+    pub lookup_table: Option<String>,
+    pub lookup_index: Option<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Serialize)]
+pub struct DecodedInstructionData {
+    pub program_id: String,
+    pub program: Option<&'static str>,
+    pub accounts: Vec<DecodedAccountData>,
+    /// Raw instruction data, base58-encoded.
+    pub data: String,
+    /// Debug rendering of the instruction for programs the service can decode.
+    pub decoded: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TxDecodeData {
+    pub version: &'static str,
+    pub fee_payer: Option<String>,
+    pub recent_blockhash: String,
+    pub signatures: Vec<SignatureData>,
+    pub address_table_lookups: Vec<String>,
+    pub instructions: Vec<DecodedInstructionData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CommitmentQuery {
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClusterQuery {
+    pub cluster: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSubmitRequest {
+    /// Fully signed transaction, base64-encoded.
+    pub transaction: String,
+    /// Commitment to wait for; the call returns right after sending when omitted.
+    pub commitment: Option<Commitment>,
+    #[serde(default)]
+    pub skip_preflight: bool,
+    /// Keys the tracker may use to re-sign with a fresh blockhash once the
+    /// original expires. Only used with `track=true`.
+    #[serde(default)]
+    pub secret_keys: Vec<SecretKey>,
+    /// Keystore keys the tracker may re-sign with, like `secretKeys`.
+    #[serde(default)]
+    pub key_ids: Vec<String>,
+    /// Raises the compute unit price whenever a tracked transaction is rebuilt.
+    pub fee_policy: Option<FeePolicy>,
+    /// Send through every configured broadcast endpoint at once.
+    #[serde(default)]
+    pub fanout: bool,
+}
+
+/// Priority fee escalation for tracked submissions. The transaction must
+/// already carry a `SetComputeUnitPrice` instruction, whose price is raised on
+/// every rebuild until it reaches `maxComputeUnitPrice`.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct FeePolicy {
+    /// Percentage added to the price on each rebuild; defaults to 100 (doubling).
+    pub increase_percent: Option<u32>,
+    /// Cap in micro-lamports per compute unit.
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub max_compute_unit_price: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TxSubmitQuery {
+    /// Keep rebroadcasting in the background until the transaction lands.
+    #[serde(default)]
+    pub track: bool,
+}
+
+#[derive(Serialize)]
+pub struct TxSubmitData {
+    pub signature: String,
+    /// Id for `/tx/tracked/{id}` when the submission is tracked.
+    pub tracking_id: Option<String>,
+    /// Provider that accepted the transaction first when fanning out.
+    pub accepted_by: Option<String>,
+    /// Whether the requested commitment was reached before the blockhash expired.
+    pub confirmed: bool,
+    pub confirmation_status: Option<String>,
+    pub slot: Option<u64>,
+    /// Set when the transaction landed but failed on chain.
+    pub err: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxFeeRequest {
+    pub transaction: String,
+}
+
+#[derive(Serialize)]
+pub struct TxFeeData {
+    /// Total fee in lamports as reported by the cluster.
+    pub fee: u64,
+    pub base_fee: u64,
+    pub priority_fee: u64,
+    pub compute_unit_limit: u32,
+    /// Micro-lamports per compute unit.
+    pub compute_unit_price: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxEstimateCuRequest {
+    pub transaction: String,
+    /// Headroom added on top of the simulated usage; defaults to 10%.
+    pub margin_percent: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct TxEstimateCuData {
+    pub units_consumed: u64,
+    pub compute_unit_limit: u32,
+    /// `set_compute_unit_limit` instruction for the recommended limit.
+    pub instruction: TokenData,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PriorityFeesQuery {
+    /// Comma-separated accounts the transaction will write to.
+    pub accounts: Option<String>,
+}
+
+/// Suggested compute unit prices in micro-lamports.
+#[derive(Serialize)]
+pub struct PriorityFeesData {
+    pub accounts: Vec<String>,
+    pub samples: usize,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub max: u64,
+}
+
+#[derive(Serialize)]
+pub struct ClusterInfoData {
+    /// Host of the RPC endpoint answering for the cluster.
+    pub rpc: String,
+    /// Latest processed slot.
+    pub slot: u64,
+    pub epoch: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    /// Slot the epoch figures refer to, at the configured commitment.
+    pub absolute_slot: u64,
+    pub block_height: u64,
+    pub transaction_count: Option<u64>,
+    pub version: String,
+    pub feature_set: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidatorSort {
+    Stake,
+    Commission,
+    LastVote,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorsQuery {
+    /// Defaults to `stake`.
+    pub sort: Option<ValidatorSort>,
+    /// Defaults to descending, or ascending when sorting by commission.
+    pub order: Option<SortOrder>,
+    pub max_commission: Option<u8>,
+    /// Minimum activated stake in lamports.
+    pub min_stake: Option<u64>,
+    #[serde(default)]
+    pub include_delinquent: bool,
+    /// 1-based page number.
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ValidatorData {
+    pub vote_account: String,
+    /// Identity of the validator node.
+    pub node: String,
+    pub activated_stake: u64,
+    pub commission: u8,
+    pub last_vote: u64,
+    pub root_slot: u64,
+    /// Whether the vote account is staked for the current epoch.
+    pub epoch_vote_account: bool,
+    pub delinquent: bool,
+}
+
+#[derive(Serialize)]
+pub struct ValidatorsData {
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+    pub validators: Vec<ValidatorData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlockhashQuery {
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlockhashData {
+    pub blockhash: String,
+    /// Last block height at which transactions using this blockhash can land.
+    pub last_valid_block_height: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransactionQuery {
+    /// `confirmed` or `finalized`; processed transactions cannot be fetched.
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize)]
+pub struct ParsedInstructionData {
+    pub program_id: String,
+    pub program: Option<&'static str>,
+    pub accounts: Vec<String>,
+    /// Raw instruction data, base58-encoded.
+    pub data: String,
+    pub decoded: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceChangeData {
+    pub account: String,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+    pub change: i64,
+}
+
+#[derive(Serialize)]
+pub struct TokenBalanceChangeData {
+    /// The token account whose balance changed.
+    pub account: String,
+    pub mint: String,
+    pub owner: Option<String>,
+    pub decimals: u8,
+    pub pre_amount: u64,
+    pub post_amount: u64,
+    pub ui_change: f64,
+}
+
+#[derive(Serialize)]
+pub struct ParsedTransactionData {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub version: &'static str,
+    pub success: bool,
+    pub err: Option<String>,
+    pub fee: u64,
+    pub compute_units_consumed: Option<u64>,
+    /// Static keys followed by any addresses loaded from lookup tables.
+    pub accounts: Vec<String>,
+    pub instructions: Vec<ParsedInstructionData>,
+    /// Lamport balances that changed, fee included.
+    pub balance_changes: Vec<BalanceChangeData>,
+    pub token_balance_changes: Vec<TokenBalanceChangeData>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HistoryQuery {
+    /// `next_cursor` from a previous page, to continue further back in time.
+    pub before: Option<String>,
+    /// `latest_cursor` from a previous page, to fetch only newer activity.
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+    /// Fetch each transaction and summarize how it moved the address's funds.
+    #[serde(default)]
+    pub hydrate: bool,
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize)]
+pub struct TransferSummaryData {
+    pub fee: u64,
+    pub fee_payer: Option<String>,
+    /// Net lamport change of the address, fee included when it paid.
+    pub lamports_change: i64,
+    /// Token balance changes on the address itself or on accounts it owns.
+    pub token_changes: Vec<TokenBalanceChangeData>,
+}
+
+#[derive(Serialize)]
+pub struct HistoryEntryData {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub confirmation_status: Option<String>,
+    pub err: Option<String>,
+    pub memo: Option<String>,
+    /// Present when `hydrate=true`.
+    pub transfers: Option<TransferSummaryData>,
+}
+
+#[derive(Serialize)]
+pub struct HistoryData {
+    pub address: String,
+    /// Newest first.
+    pub signatures: Vec<HistoryEntryData>,
+    /// Absent once the start of the address's history is reached.
+    pub next_cursor: Option<String>,
+    pub latest_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BalanceAtQuery {
+    pub slot: Option<u64>,
+    /// Unix timestamp; used instead of `slot`.
+    pub timestamp: Option<i64>,
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceAtData {
+    pub pubkey: String,
+    pub slot: Option<u64>,
+    pub timestamp: Option<i64>,
+    pub amount: u64,
+    /// Unknown when the account never held tokens.
+    pub decimals: Option<u8>,
+    pub ui_amount: Option<f64>,
+    /// First transaction after the target, whose pre-balance is the answer;
+    /// absent when the account has not been touched since.
+    pub source_signature: Option<String>,
+    pub transactions_scanned: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockQuery {
+    /// Comma-separated addresses; only transactions touching one of them are returned.
+    pub addresses: Option<String>,
+    /// Vote transactions make up most of a block and are left out unless requested.
+    #[serde(default)]
+    pub include_votes: bool,
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize)]
+pub struct BlockData {
+    pub slot: u64,
+    pub blockhash: String,
+    pub previous_blockhash: String,
+    pub parent_slot: u64,
+    pub block_time: Option<i64>,
+    pub block_height: Option<u64>,
+    /// All transactions in the block, before filtering.
+    pub transaction_count: usize,
+    pub transactions: Vec<ParsedTransactionData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxStatusQuery {
+    /// Also search the ledger beyond the node's recent status cache.
+    #[serde(default)]
+    pub search_history: bool,
+}
+
+#[derive(Serialize)]
+pub struct TxStatusData {
+    pub signature: String,
+    pub found: bool,
+    pub confirmation_status: Option<String>,
+    /// `None` once the block is rooted.
+    pub confirmations: Option<usize>,
+    pub slot: Option<u64>,
+    pub err: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPackRequest {
+    pub instructions: Vec<InstructionInput>,
+    pub fee_payer: String,
+    /// Fetched from the RPC when omitted.
+    pub recent_blockhash: Option<String>,
+    /// Expected usage per instruction. When set, each transaction also gets a
+    /// matching `set_compute_unit_limit`; otherwise the runtime default is assumed.
+    pub compute_units_per_instruction: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct PackedTransactionData {
+    /// Unsigned transaction, bincode-serialized and base64-encoded.
+    pub transaction: String,
+    /// Number of input instructions carried, not counting compute budget ones.
+    pub instruction_count: usize,
+    pub signers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TxPackData {
+    pub recent_blockhash: String,
+    pub transactions: Vec<PackedTransactionData>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileOptions {
+    pub fee_payer: String,
+    /// A blockhash, or `"auto"` (the default) to fetch the latest one.
+    pub recent_blockhash: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CompiledMessageData {
+    /// Serialized message, base64-encoded; this is what signers sign.
+    pub message: String,
+    /// The same message as an unsigned wire-format transaction, base64-encoded.
+    pub transaction: String,
+    pub recent_blockhash: String,
+    pub signers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxVerifyRequest {
+    pub transaction: String,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    Valid,
+    Missing,
+    Invalid,
+}
+
+#[derive(Serialize)]
+pub struct SignatureCheckData {
+    pub pubkey: String,
+    pub signature: Option<String>,
+    pub status: SignatureStatus,
+}
+
+#[derive(Serialize)]
+pub struct TxVerifyData {
+    /// True only when every required signature is present and valid.
+    pub valid: bool,
+    pub signatures: Vec<SignatureCheckData>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackedStatus {
+    Pending,
+    Confirmed,
+    Failed,
+    Expired,
+}
+
+#[derive(Serialize)]
+pub struct TrackedTransactionData {
+    pub id: String,
+    pub status: TrackedStatus,
+    /// Signature of the latest broadcast version of the transaction.
+    pub signature: String,
+    /// Every signature the transaction has carried, oldest first.
+    pub signatures: Vec<String>,
+    pub attempts: u32,
+    /// Times the transaction was re-signed with a fresh blockhash or a higher fee.
+    pub rebuilds: u32,
+    /// Current price in micro-lamports when a fee policy is in effect.
+    pub compute_unit_price: Option<u64>,
+    pub err: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JitoTipRequest {
+    pub from: String,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub lamports: u64,
+    /// One of the block engine's tip accounts; picked at random when omitted.
+    pub tip_account: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSubmitRequest {
+    /// Fully signed transactions, base64-encoded, executed in order and atomically.
+    pub transactions: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BundleSubmitData {
+    pub bundle_id: String,
+    pub signatures: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TokenBalanceQuery {
+    pub owner: String,
+    pub mint: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenBalanceData {
+    pub owner: String,
+    pub mint: String,
+    /// The owner's associated token account for the mint.
+    pub token_account: String,
+    /// False when the associated token account has not been created; the amount is then 0.
+    pub exists: bool,
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenAccountData {
+    pub pubkey: String,
+    /// spl-token or Token-2022.
+    pub program_id: String,
+    pub mint: String,
+    pub owner: String,
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: f64,
+    pub delegate: Option<String>,
+    pub delegated_amount: u64,
+    pub close_authority: Option<String>,
+    /// `initialized` or `frozen`.
+    pub state: String,
+    pub is_native: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenAccountsData {
+    pub owner: String,
+    pub accounts: Vec<TokenAccountData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DelegatedTokenAccountsData {
+    pub delegate: String,
+    pub accounts: Vec<TokenAccountData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenSupplyData {
+    pub mint: String,
+    pub supply: u64,
+    pub decimals: u8,
+    pub ui_amount: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LargestAccountData {
+    pub address: String,
+    /// Wallet owning the token account; `None` if it could not be decoded.
+    pub owner: Option<String>,
+    pub amount: u64,
+    pub ui_amount: f64,
+    pub percent_of_supply: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LargestAccountsData {
+    pub mint: String,
+    pub supply: u64,
+    pub decimals: u8,
+    pub accounts: Vec<LargestAccountData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HoldersQuery {
+    /// 1-based page number.
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    /// Discard a finished snapshot and take a new one.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+#[derive(Serialize)]
+pub struct HolderData {
+    pub owner: String,
+    /// Summed across all of the owner's token accounts for the mint.
+    pub amount: u64,
+    pub ui_amount: f64,
+}
+
+#[derive(Serialize)]
+pub struct HoldersSnapshotData {
+    pub mint: String,
+    pub status: SnapshotStatus,
+    /// Slot observed just before the scan started.
+    pub slot: Option<u64>,
+    /// Unix timestamp at which the scan finished.
+    pub taken_at: Option<u64>,
+    pub decimals: u8,
+    pub total_accounts: usize,
+    /// Owners with a non-zero balance; holders are listed largest first.
+    pub total_holders: usize,
+    pub page: usize,
+    pub limit: usize,
+    pub holders: Vec<HolderData>,
+    pub err: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MintInfoData {
+    pub mint: String,
+    pub program_id: String,
+    pub supply: u64,
+    pub decimals: u8,
+    pub is_initialized: bool,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    /// Token-2022 extension types on the mint; always empty for spl-token mints.
+    pub extensions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenAccountInfoData {
+    pub pubkey: String,
+    pub program_id: String,
+    pub mint: String,
+    pub owner: String,
+    pub amount: u64,
+    pub delegate: Option<String>,
+    pub delegated_amount: u64,
+    /// `uninitialized`, `initialized` or `frozen`.
+    pub state: String,
+    pub is_native: bool,
+    /// Lamports held back for rent on wrapped SOL accounts.
+    pub rent_exempt_reserve: Option<u64>,
+    pub close_authority: Option<String>,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeriveKeypairRequest {
+    #[serde(default)]
+    pub mnemonic: String,
+    pub passphrase: Option<String>,
+    /// Hardened BIP44 path; `{index}` is replaced by each derived index.
+    pub path: Option<String>,
+    pub start_index: Option<u32>,
+    pub count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DerivedKeypairData {
+    pub index: u32,
+    pub path: String,
+    pub pubkey: String,
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DerivedKeypairsData {
+    pub accounts: Vec<DerivedKeypairData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretFormat {
+    Base58,
+    /// 64-element byte array, as in solana-keygen keypair files.
+    Json,
+    Hex,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeypairQuery {
+    pub format: Option<SecretFormat>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeypairData {
+    pub pubkey: String,
+    /// A string, or a byte array for the `json` format.
+    pub secret: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VanityRequest {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    /// Defaults to true.
+    pub case_sensitive: Option<bool>,
+    /// Worker threads; defaults to the number of cores.
+    pub threads: Option<usize>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VanityStatus {
+    Pending,
+    Found,
+    Expired,
+}
+
+#[derive(Serialize, Clone)]
+pub struct VanityJobData {
+    pub id: String,
+    pub status: VanityStatus,
+    pub prefix: String,
+    pub suffix: String,
+    pub case_sensitive: bool,
+    pub threads: usize,
+    /// Keypairs generated so far, across all threads.
+    pub attempts: u64,
+    pub elapsed_ms: u64,
+    pub pubkey: Option<String>,
+    /// Base58 secret key, set once a match is found.
+    pub secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedEncoding {
+    Hex,
+    Base64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeypairFromSeedRequest {
+    #[serde(default)]
+    pub seed: String,
+    /// Defaults to hex.
+    pub encoding: Option<SeedEncoding>,
+    pub format: Option<SecretFormat>,
+}
+
+/// How the ed25519 secret was obtained from the supplied seed.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedDerivation {
+    /// The seed was exactly 32 bytes and used as is.
+    Raw,
+    /// The seed was hashed to 32 bytes.
+    Sha256,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeypairFromSeedData {
+    pub pubkey: String,
+    pub secret: serde_json::Value,
+    pub derivation: SeedDerivation,
+}
+
+/// Where a remote key lives. The key material never leaves the backend.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeyBackendConfig {
+    /// Full key version name, `projects/../cryptoKeyVersions/N`.
+    #[serde(rename_all = "camelCase")]
+    GcpKms { key_version: String },
+    /// Key id, ARN or alias.
+    #[serde(rename_all = "camelCase")]
+    AwsKms { key_id: String, region: String },
+    #[serde(rename_all = "camelCase")]
+    Pkcs11 {
+        /// Name of a token under `[pkcs11]` in the server configuration.
+        backend: String,
+        /// Hex CKA_ID of the key on the token.
+        key_id: String,
+        /// Base58 address of the key.
+        pubkey: String,
+    },
+    /// A Ledger running the Solana app, attached to this host.
+    #[serde(rename_all = "camelCase")]
+    Ledger {
+        /// Defaults to `m/44'/501'/0'/0'`.
+        derivation_path: Option<String>,
+        /// hidraw node such as `/dev/hidraw0`, which must belong to a Ledger; the first
+        /// Ledger found when omitted.
+        device: Option<String>,
+    },
+}
+
+impl KeyBackendConfig {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyBackendConfig::GcpKms { .. } => "gcp_kms",
+            KeyBackendConfig::AwsKms { .. } => "aws_kms",
+            KeyBackendConfig::Pkcs11 { .. } => "pkcs11",
+            KeyBackendConfig::Ledger { .. } => "ledger",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateKeyRequest {
+    /// Secret to import; a new key is generated when neither this nor `backend` is given.
+    pub secret: Option<SecretKey>,
+    /// Remote backend holding the key instead of this server.
+    pub backend: Option<KeyBackendConfig>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateKeyRequest {
+    /// Replaces the key's labels.
+    pub labels: Option<Vec<String>>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeysQuery {
+    /// Only keys carrying exactly this label.
+    pub label: Option<String>,
+    /// Case-insensitive substring of the id, address or a label.
+    pub search: Option<String>,
+    pub disabled: Option<bool>,
+    /// 1-based page number.
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StoredKeyData {
+    pub id: String,
+    pub pubkey: String,
+    /// `local`, `gcp_kms`, `aws_kms`, `pkcs11` or `ledger`.
+    pub backend: String,
+    pub labels: Vec<String>,
+    /// Unix timestamps.
+    pub created_at: u64,
+    /// Signatures made with the key.
+    pub usage_count: u64,
+    pub last_used_at: Option<u64>,
+    pub disabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StoredKeysData {
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
+    pub keys: Vec<StoredKeyData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportKeysRequest {
+    #[serde(default)]
+    pub passphrase: String,
+}
+
+/// An encrypted keystore export. Binary fields are base64.
+#[derive(Serialize, Deserialize)]
+pub struct KeystoreArchive {
+    pub version: u8,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    /// Number of keys inside; informational only.
+    #[serde(default)]
+    pub keys: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportKeysRequest {
+    #[serde(default)]
+    pub passphrase: String,
+    pub archive: KeystoreArchive,
+    /// Replace keys whose ids already exist instead of skipping them.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportKeysData {
+    pub imported: Vec<String>,
+    /// Ids left untouched because they already existed.
+    pub skipped: Vec<String>,
+}
+
+/// Wallets whose private key exports can be produced for a stored key.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletFormat {
+    /// Base58 private key.
+    Phantom,
+    /// Base58 private key.
+    Backpack,
+    /// JSON byte array keypair file, which Solflare imports alongside base58 keys.
+    Solflare,
+    /// solana-keygen JSON byte array keypair file.
+    Cli,
+}
+
+impl WalletFormat {
+    pub fn secret_format(self) -> SecretFormat {
+        match self {
+            WalletFormat::Phantom | WalletFormat::Backpack => SecretFormat::Base58,
+            WalletFormat::Solflare | WalletFormat::Cli => SecretFormat::Json,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletImportRequest {
+    /// Private key as exported by a wallet: base58 or a JSON keypair file.
+    pub secret: SecretKey,
+    /// Address the wallet showed for the key; the import is refused if it differs.
+    pub expected_address: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletExportRequest {
+    pub wallet: WalletFormat,
+    pub expected_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletExportData {
+    pub id: String,
+    pub pubkey: String,
+    pub wallet: WalletFormat,
+    /// Base58 string or JSON byte array, as the wallet expects it.
+    pub secret: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SplitKeyRequest {
+    pub secret: SecretKey,
+    /// Number of shares to produce, at most 255.
+    pub shares: u8,
+    /// Shares needed to reconstruct the key, at least 2.
+    pub threshold: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SplitKeyData {
+    pub pubkey: String,
+    pub threshold: u8,
+    /// Base58 shares.
+    pub shares: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReconstructKeyRequest {
+    #[serde(default)]
+    pub shares: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReconstructKeyData {
+    pub pubkey: String,
+    /// Base58 64-byte keypair.
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PdaSeedType {
+    Utf8,
+    Base58,
+    Hex,
+    /// A base58 public key, validated and used as its 32 bytes.
+    Pubkey,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PdaSeed {
+    #[serde(rename = "type")]
+    pub kind: PdaSeedType,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivePdaRequest {
+    #[serde(default)]
+    pub program_id: String,
+    #[serde(default)]
+    pub seeds: Vec<PdaSeed>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PdaData {
+    pub address: String,
+    pub bump: u8,
+    pub program_id: String,
+    /// Each seed's raw bytes, hex-encoded, without the bump.
+    pub seeds: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ValidatePubkeyRequest {
+    #[serde(default)]
+    pub pubkey: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ValidatePubkeyData {
+    pub input: String,
+    pub valid: bool,
+    pub valid_base58: bool,
+    pub byte_length: Option<usize>,
+    /// True for keys a keypair can own; false for program derived addresses.
+    pub on_curve: Option<bool>,
+    /// Canonical base58 form, without surrounding whitespace.
+    pub normalized: Option<String>,
+    /// Why the input is not a valid public key.
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthData {
+    pub status: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Down,
+    /// Switched off in the configuration.
+    Disabled,
+}
+
+#[derive(Serialize)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Whether the server is not ready while this is down.
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReadyData {
+    pub ready: bool,
+    pub checks: Vec<DependencyCheck>,
+}
+
+#[derive(Serialize)]
+pub struct ApiKeyData {
+    pub name: String,
+    /// Path prefixes the key may call; any path when empty.
+    pub routes: Vec<String>,
+    pub daily_quota: Option<u64>,
+    pub used_today: u64,
+    /// Unix time the daily count starts over.
+    pub resets_at: u64,
+}
@@ -0,0 +1,272 @@
+use axum::{
+    body::{to_bytes, Body, Bytes, HttpBody},
+    http::{header, request, response, HeaderValue, StatusCode}, response::{IntoResponse, Response}, Json
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http_body_util::LengthLimitError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_keypair::{keypair_from_seed, Keypair};
+use solana_offchain_message::{v0, OffchainMessage};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+
+use std::{error::Error, str::FromStr, time::Duration};
+
+use crate::types::{AccountMetaResponse, MessageEncoding, MessageStandard, SecretKey, SignatureFormat, TokenData};
+
+pub type ApiResult = Result<Response, ApiError>;
+
+/// Largest body middleware buffers to look inside.
+pub const MAX_BUFFERED_BODY: usize = 2 * 1024 * 1024;
+
+/// Error returned by handlers, rendered as `{"success": false, "error": ...}`.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+    /// Machine-readable code for errors clients are expected to branch on.
+    pub code: Option<&'static str>,
+    /// Extra fields merged into the error body.
+    pub details: Option<Value>,
+    /// Sent as a `Retry-After` header when set.
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into(), code: None, details: None, retry_after: None }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "success": false,
+            "error": self.message
+        });
+        if let Some(code) = self.code {
+            body["code"] = json!(code);
+        }
+        if let Some(Value::Object(details)) = self.details {
+            for (key, value) in details {
+                body[key] = value;
+            }
+        }
+
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(retry_after) = self.retry_after {
+            // Retry-After is whole seconds; round up so clients never retry early.
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+/// Buffers a request body for middleware to inspect, answering 413 when it
+/// is larger than `MAX_BUFFERED_BODY`.
+pub async fn buffer_request(request: axum::extract::Request) -> Result<(request::Parts, Bytes), Response> {
+    let (parts, body) = request.into_parts();
+    match to_bytes(body, MAX_BUFFERED_BODY).await {
+        Ok(bytes) => Ok((parts, bytes)),
+        Err(err) if err.source().is_some_and(|source| source.is::<LengthLimitError>()) => {
+            let message = format!("Request body is larger than {MAX_BUFFERED_BODY} bytes");
+            Err(ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, message).with_code("PAYLOAD_TOO_LARGE").into_response())
+        }
+        Err(_) => Err(ApiError::bad_request("Failed to read request body").into_response()),
+    }
+}
+
+/// Buffers a response body for middleware to rewrite. A response larger than
+/// `MAX_BUFFERED_BODY`, or of unknown length, is handed back untouched.
+pub async fn buffer_response(response: Response) -> Result<(response::Parts, Bytes), Response> {
+    let fits = response.body().size_hint().upper().is_some_and(|upper| upper <= MAX_BUFFERED_BODY as u64);
+    if !fits {
+        return Err(response);
+    }
+    let (parts, body) = response.into_parts();
+    match to_bytes(body, MAX_BUFFERED_BODY).await {
+        Ok(bytes) => Ok((parts, bytes)),
+        Err(_) => Err(Response::from_parts(parts, Body::empty())),
+    }
+}
+
+/// How many items precede 1-based `page` of `limit`-sized pages. Saturates,
+/// so an absurd page number is just an empty page.
+pub fn page_offset(page: usize, limit: usize) -> usize {
+    page.saturating_sub(1).saturating_mul(limit)
+}
+
+pub fn success_response<T: Serialize>(data: T) -> Response {
+    (StatusCode::OK, Json(json!({
+        "success": true,
+        "data": data
+    }))).into_response()
+}
+
+/// Parses a base58 pubkey, naming the offending field in the error.
+pub fn parse_pubkey(value: &str, field: &str) -> Result<Pubkey, ApiError> {
+    Pubkey::from_str(value)
+        .map_err(|_| ApiError::bad_request(format!("Invalid {} public key format", field)))
+}
+
+pub fn parse_optional_pubkey(value: Option<&str>, field: &str) -> Result<Option<Pubkey>, ApiError> {
+    value.map(|value| parse_pubkey(value, field)).transpose()
+}
+
+/// Decodes a secret key written as a solana-keygen JSON byte array, hex
+/// (optionally `0x`-prefixed), or base58. The encodings never overlap: hex
+/// keys are 64 or 128 characters, base58 ones 43-44 or 87-88.
+pub fn decode_secret_key(secret: &str) -> Result<Vec<u8>, ApiError> {
+    let secret = secret.trim();
+    let invalid = || ApiError::bad_request("Invalid secret key format");
+    if secret.starts_with('[') {
+        return serde_json::from_str(secret).map_err(|_| invalid());
+    }
+    let hex_digits = secret.strip_prefix("0x").unwrap_or(secret);
+    if matches!(hex_digits.len(), 64 | 128) && hex_digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return hex::decode(hex_digits).map_err(|_| invalid());
+    }
+    bs58::decode(secret).into_vec().map_err(|_| invalid())
+}
+
+/// Builds a keypair from either a 64-byte keypair or a 32-byte seed.
+pub fn keypair_from_secret_bytes(bytes: &[u8]) -> Result<Keypair, ApiError> {
+    match bytes.len() {
+        64 => Keypair::try_from(bytes).map_err(|_| ApiError::bad_request("Invalid secret key format")),
+        32 => keypair_from_seed(bytes).map_err(|_| ApiError::bad_request("Failed to create keypair from seed")),
+        _ => Err(ApiError::bad_request("Invalid secret key format")),
+    }
+}
+
+/// Parses a secret key in any format `decode_secret_key` accepts.
+pub fn parse_keypair(secret: &str) -> Result<Keypair, ApiError> {
+    keypair_from_secret_bytes(&decode_secret_key(secret)?)
+}
+
+pub fn parse_secret_key(secret: &SecretKey) -> Result<Keypair, ApiError> {
+    match secret {
+        SecretKey::Encoded(secret) => parse_keypair(secret),
+        SecretKey::Bytes(bytes) => keypair_from_secret_bytes(bytes),
+    }
+}
+
+/// Formats Unix seconds as an RFC 3339 UTC timestamp, `2024-01-31T12:00:00Z`.
+pub fn format_utc_timestamp(seconds: u64) -> String {
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`.
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// The bytes a sign or verify request refers to.
+pub fn decode_message(message: &str, encoding: MessageEncoding) -> Result<Vec<u8>, ApiError> {
+    match encoding {
+        MessageEncoding::Utf8 => Ok(message.as_bytes().to_vec()),
+        MessageEncoding::Base64 => STANDARD
+            .decode(message.trim())
+            .map_err(|_| ApiError::bad_request("Invalid base64 message")),
+        MessageEncoding::Hex => {
+            let message = message.trim();
+            hex::decode(message.strip_prefix("0x").unwrap_or(message))
+                .map_err(|_| ApiError::bad_request("Invalid hex message"))
+        }
+    }
+}
+
+/// The bytes a signature over `message` covers under `standard`.
+pub fn message_signing_bytes(
+    message: &str,
+    encoding: MessageEncoding,
+    standard: MessageStandard,
+) -> Result<Vec<u8>, ApiError> {
+    let bytes = decode_message(message, encoding)?;
+    match standard {
+        MessageStandard::Raw => Ok(bytes),
+        MessageStandard::Offchain => OffchainMessage::new(0, &bytes)
+            .and_then(|message| message.serialize())
+            .map_err(|_| {
+                ApiError::bad_request(format!(
+                    "Offchain messages must be 1 to {} bytes",
+                    v0::OffchainMessage::MAX_LEN
+                ))
+            }),
+    }
+}
+
+pub fn encode_signature(signature: &Signature, format: SignatureFormat) -> String {
+    match format {
+        SignatureFormat::Base58 => signature.to_string(),
+        SignatureFormat::Base64 => STANDARD.encode(signature),
+        SignatureFormat::Hex => hex::encode(signature),
+    }
+}
+
+/// Decodes a signature written in base58, padded base64 or hex (optionally
+/// `0x`-prefixed). For 64 bytes these never overlap: hex is 128 characters,
+/// base64 ends in `==`, and base58 is at most 88 characters without either.
+pub fn decode_signature(signature: &str) -> Result<Signature, ApiError> {
+    let signature = signature.trim();
+    let invalid = || ApiError::bad_request("Invalid signature format").with_code("INVALID_SIGNATURE_FORMAT");
+    let hex_digits = signature.strip_prefix("0x").unwrap_or(signature);
+    let bytes = if hex_digits.len() == 128 && hex_digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        hex::decode(hex_digits).map_err(|_| invalid())?
+    } else if signature.contains(['=', '+', '/']) {
+        STANDARD.decode(signature).map_err(|_| invalid())?
+    } else {
+        bs58::decode(signature).into_vec().map_err(|_| invalid())?
+    };
+    Signature::try_from(bytes.as_slice()).map_err(|_| {
+        ApiError::bad_request(format!("Signature must be 64 bytes long, got {}", bytes.len()))
+            .with_code("INVALID_SIGNATURE_FORMAT")
+    })
+}
+
+pub fn instruction_to_data(ix: &Instruction) -> TokenData {
+    let accounts: Vec<AccountMetaResponse> = ix.accounts.iter().map(|account| {
+        AccountMetaResponse {
+            pubkey: account.pubkey.to_string(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }
+    }).collect();
+
+    TokenData {
+        program_id: ix.program_id.to_string(),
+        accounts,
+        instruction_data: bs58::encode(&ix.data).into_string(),
+    }
+}
@@ -0,0 +1,36 @@
+//! Pre-flight checks for user-supplied addresses.
+
+use axum::Json;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::types::{ValidatePubkeyData, ValidatePubkeyRequest};
+use crate::utils::{success_response, ApiError, ApiResult};
+
+/// Reports whether `pubkey` is a usable address instead of failing on bad
+/// input. On-curve addresses can belong to a keypair; off-curve ones are
+/// program derived addresses, which a wallet never controls directly.
+pub async fn validate_pubkey(Json(payload): Json<ValidatePubkeyRequest>) -> ApiResult {
+    let ValidatePubkeyRequest { pubkey } = payload;
+
+    if pubkey.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: pubkey"));
+    }
+
+    let decoded = bs58::decode(pubkey.trim()).into_vec().ok();
+    let parsed = decoded.as_deref().and_then(|bytes| <[u8; 32]>::try_from(bytes).ok()).map(Pubkey::new_from_array);
+    let reason = match (&decoded, &parsed) {
+        (None, _) => Some("Not valid base58".to_string()),
+        (Some(bytes), None) => Some(format!("Decodes to {} bytes; public keys are 32", bytes.len())),
+        (Some(_), Some(_)) => None,
+    };
+
+    Ok(success_response(ValidatePubkeyData {
+        input: pubkey,
+        valid: parsed.is_some(),
+        valid_base58: decoded.is_some(),
+        byte_length: decoded.as_ref().map(Vec::len),
+        on_curve: parsed.map(|key| key.is_on_curve()),
+        normalized: parsed.map(|key| key.to_string()),
+        reason,
+    }))
+}
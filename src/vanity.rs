@@ -0,0 +1,209 @@
+//! Vanity address grinding.
+//!
+//! Finding a key whose address starts or ends with a chosen pattern is a brute
+//! force search that can run for minutes, so `/keypair/vanity` starts a job on
+//! dedicated threads and `/keypair/vanity/{job}` reports progress and, once
+//! found, the keypair. Jobs give up after a while and finished ones are kept
+//! for a while before being dropped.
+
+use axum::{extract::Path, http::StatusCode, Json};
+use solana_keypair::Keypair;
+use solana_sdk::signer::Signer;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::types::{VanityJobData, VanityRequest, VanityStatus};
+use crate::utils::{success_response, ApiError, ApiResult};
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// Each extra character makes the search 58 times longer; beyond this it rarely finishes.
+const MAX_PATTERN_LEN: usize = 6;
+const MAX_GRIND_DURATION: Duration = Duration::from_secs(1800);
+/// Grinding saturates the cores it gets, so only a couple of jobs run at once.
+const MAX_RUNNING_JOBS: usize = 2;
+const MAX_THREADS: usize = 16;
+/// How long finished jobs stay queryable.
+const FINISHED_RETENTION: Duration = Duration::from_secs(600);
+/// Workers publish their attempt counts in batches to keep the counter uncontended.
+const ATTEMPT_BATCH: u64 = 1000;
+
+struct VanityJob {
+    data: VanityJobData,
+    started: Instant,
+    attempts: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    finished_at: Option<Instant>,
+}
+
+fn jobs() -> &'static Mutex<HashMap<String, VanityJob>> {
+    static JOBS: OnceLock<Mutex<HashMap<String, VanityJob>>> = OnceLock::new();
+    JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Rejects characters no address can contain. Case-insensitive patterns
+/// only need one of the two cases to be valid base58.
+fn validate_pattern(pattern: &str, field: &str, case_sensitive: bool) -> Result<(), ApiError> {
+    let in_alphabet = |c: char| BASE58_ALPHABET.contains(c);
+    let valid = |c: char| {
+        in_alphabet(c)
+            || !case_sensitive && (in_alphabet(c.to_ascii_lowercase()) || in_alphabet(c.to_ascii_uppercase()))
+    };
+    match pattern.chars().find(|c| !valid(*c)) {
+        Some(c) => Err(ApiError::bad_request(format!("{field} contains '{c}', which never appears in an address"))),
+        None => Ok(()),
+    }
+}
+
+struct Pattern {
+    prefix: String,
+    suffix: String,
+    case_sensitive: bool,
+}
+
+impl Pattern {
+    fn matches(&self, address: &str) -> bool {
+        if self.case_sensitive {
+            return address.starts_with(&self.prefix) && address.ends_with(&self.suffix);
+        }
+        let address = address.to_ascii_lowercase();
+        address.starts_with(&self.prefix) && address.ends_with(&self.suffix)
+    }
+}
+
+/// Searches until a match is found, the job is stopped, or `deadline` passes; workers quit on their
+/// own even if nobody polls the job again.
+fn grind(id: String, pattern: Arc<Pattern>, attempts: Arc<AtomicU64>, stop: Arc<AtomicBool>, deadline: Instant) {
+    let mut batch = 0;
+    while !stop.load(Ordering::Relaxed) && Instant::now() < deadline {
+        let keypair = Keypair::new();
+        batch += 1;
+        if batch == ATTEMPT_BATCH {
+            attempts.fetch_add(batch, Ordering::Relaxed);
+            batch = 0;
+        }
+        if !pattern.matches(&keypair.pubkey().to_string()) {
+            continue;
+        }
+
+        // Only the first worker to find a match reports it.
+        if stop.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        attempts.fetch_add(batch, Ordering::Relaxed);
+        let mut jobs = jobs().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(job) = jobs.get_mut(&id) {
+            job.data.status = VanityStatus::Found;
+            job.data.elapsed_ms = job.started.elapsed().as_millis() as u64;
+            job.data.pubkey = Some(keypair.pubkey().to_string());
+            job.data.secret = Some(keypair.to_base58_string());
+            job.finished_at = Some(Instant::now());
+        }
+        return;
+    }
+}
+
+/// Ends jobs that ran out of time, drops expired ones, and refreshes progress.
+fn sweep(jobs: &mut HashMap<String, VanityJob>) {
+    jobs.retain(|_, job| job.finished_at.is_none_or(|at| at.elapsed() < FINISHED_RETENTION));
+    for job in jobs.values_mut() {
+        job.data.attempts = job.attempts.load(Ordering::Relaxed);
+        if job.data.status == VanityStatus::Pending {
+            job.data.elapsed_ms = job.started.elapsed().as_millis() as u64;
+            if job.started.elapsed() > MAX_GRIND_DURATION {
+                job.stop.store(true, Ordering::Relaxed);
+                job.data.status = VanityStatus::Expired;
+                job.finished_at = Some(Instant::now());
+            }
+        }
+    }
+}
+
+pub async fn start_vanity(Json(payload): Json<VanityRequest>) -> ApiResult {
+    let VanityRequest { prefix, suffix, case_sensitive, threads } = payload;
+    let prefix = prefix.unwrap_or_default();
+    let suffix = suffix.unwrap_or_default();
+    let case_sensitive = case_sensitive.unwrap_or(true);
+
+    if prefix.is_empty() && suffix.is_empty() {
+        return Err(ApiError::bad_request("Missing required fields: prefix or suffix"));
+    }
+    if prefix.chars().count() + suffix.chars().count() > MAX_PATTERN_LEN {
+        return Err(ApiError::bad_request(format!(
+            "prefix and suffix must be at most {MAX_PATTERN_LEN} characters combined"
+        )));
+    }
+    validate_pattern(&prefix, "prefix", case_sensitive)?;
+    validate_pattern(&suffix, "suffix", case_sensitive)?;
+
+    let available = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let threads = threads.unwrap_or(available);
+    if threads == 0 || threads > MAX_THREADS {
+        return Err(ApiError::bad_request(format!("threads must be between 1 and {MAX_THREADS}")));
+    }
+
+    let id = hex::encode(rand::random::<[u8; 16]>());
+    let data = VanityJobData {
+        id: id.clone(),
+        status: VanityStatus::Pending,
+        prefix: prefix.clone(),
+        suffix: suffix.clone(),
+        case_sensitive,
+        threads,
+        attempts: 0,
+        elapsed_ms: 0,
+        pubkey: None,
+        secret: None,
+    };
+    let normalize = |pattern: String| if case_sensitive { pattern } else { pattern.to_ascii_lowercase() };
+    let pattern = Arc::new(Pattern { prefix: normalize(prefix), suffix: normalize(suffix), case_sensitive });
+    let attempts = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let deadline = Instant::now() + MAX_GRIND_DURATION;
+
+    {
+        let mut jobs = jobs().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        sweep(&mut jobs);
+        let running = jobs.values().filter(|job| job.data.status == VanityStatus::Pending).count();
+        if running >= MAX_RUNNING_JOBS {
+            return Err(ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("At most {MAX_RUNNING_JOBS} vanity searches can run at once"),
+            )
+            .with_code("TOO_MANY_JOBS"));
+        }
+        jobs.insert(
+            id.clone(),
+            VanityJob {
+                data: data.clone(),
+                started: Instant::now(),
+                attempts: attempts.clone(),
+                stop: stop.clone(),
+                finished_at: None,
+            },
+        );
+    }
+
+    for _ in 0..threads {
+        let (id, pattern, attempts, stop) = (id.clone(), pattern.clone(), attempts.clone(), stop.clone());
+        thread::spawn(move || grind(id, pattern, attempts, stop, deadline));
+    }
+
+    Ok(success_response(data))
+}
+
+pub async fn vanity_job(Path(id): Path<String>) -> ApiResult {
+    let mut jobs = jobs().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    sweep(&mut jobs);
+    let job = jobs
+        .get(&id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "No vanity job with that id"))?;
+    Ok(success_response(&job.data))
+}
@@ -0,0 +1,73 @@
+use axum::{extract::State, Json};
+use solana_vote_interface::{
+    instruction::{authorize, authorize_checked, withdraw},
+    program::ID as VOTE_PROGRAM_ID,
+    state::VoteAuthorize,
+};
+
+use crate::state::AppState;
+use crate::types::{VoteAuthorizeRequest, VoteAuthorizeType, VoteWithdrawRequest};
+use crate::utils::{instruction_to_data, parse_pubkey, success_response, ApiError, ApiResult};
+
+pub async fn vote_withdraw(State(state): State<AppState>, Json(payload): Json<VoteWithdrawRequest>) -> ApiResult {
+    let VoteWithdrawRequest { vote_account, withdraw_authority, recipient, lamports } = payload;
+
+    if vote_account.is_empty() || withdraw_authority.is_empty() || recipient.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: voteAccount, withdrawAuthority, or recipient",
+        ));
+    }
+
+    let vote_pubkey = parse_pubkey(&vote_account, "vote account")?;
+    let withdraw_authority_pubkey = parse_pubkey(&withdraw_authority, "withdraw authority")?;
+    let recipient_pubkey = parse_pubkey(&recipient, "recipient")?;
+
+    let lamports = match lamports {
+        Some(lamports) => lamports,
+        // Sweep everything above the rent-exempt minimum, leaving the account open.
+        None => {
+            let client = state.rpc()?;
+            let account = client.get_account(&vote_pubkey).await?;
+            if account.owner != VOTE_PROGRAM_ID {
+                return Err(ApiError::bad_request("Account is not a vote account"));
+            }
+            let reserve = client.get_minimum_balance_for_rent_exemption(account.data.len()).await?;
+            account.lamports.saturating_sub(reserve)
+        }
+    };
+
+    if lamports == 0 {
+        return Err(ApiError::bad_request("Amount must be greater than 0"));
+    }
+
+    let ix = withdraw(&vote_pubkey, &withdraw_authority_pubkey, lamports, &recipient_pubkey);
+
+    Ok(success_response(instruction_to_data(&ix)))
+}
+
+pub async fn vote_authorize(Json(payload): Json<VoteAuthorizeRequest>) -> ApiResult {
+    let VoteAuthorizeRequest { vote_account, authority, new_authority, authorize_type, checked } = payload;
+
+    if vote_account.is_empty() || authority.is_empty() || new_authority.is_empty() {
+        return Err(ApiError::bad_request(
+            "Missing required fields: voteAccount, authority, or newAuthority",
+        ));
+    }
+
+    let vote_pubkey = parse_pubkey(&vote_account, "vote account")?;
+    let authority_pubkey = parse_pubkey(&authority, "authority")?;
+    let new_authority_pubkey = parse_pubkey(&new_authority, "new authority")?;
+
+    let vote_authorize = match authorize_type {
+        VoteAuthorizeType::Voter => VoteAuthorize::Voter,
+        VoteAuthorizeType::Withdrawer => VoteAuthorize::Withdrawer,
+    };
+
+    let ix = if checked {
+        authorize_checked(&vote_pubkey, &authority_pubkey, &new_authority_pubkey, vote_authorize)
+    } else {
+        authorize(&vote_pubkey, &authority_pubkey, &new_authority_pubkey, vote_authorize)
+    };
+
+    Ok(success_response(instruction_to_data(&ix)))
+}